@@ -0,0 +1,148 @@
+use std::fmt;
+use std::str::FromStr;
+use rand::Rng;
+use crate::accounts::AccountType;
+
+/// Number of random characters in the body of an account number
+pub const ACCOUNT_NUMBER_BODY_LENGTH: usize = 10;
+
+/// Maximum number of attempts made to generate an account number that does not collide
+const MAX_GENERATION_ATTEMPTS: usize = 100;
+
+const BODY_CHARSET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+/// Validated account number of the form `<AccountType>-<BODY>-<CHECK>`, e.g. `Basic-7KQ2M9X4TR-40`.
+///
+/// The two check digits follow ISO 7064 MOD 97-10 (the scheme used by IBAN) computed over the
+/// account type and the body, so any single mistyped character is detected.
+/// The prefix names the type the account was opened as and does not change when it is converted.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct AccountNumber {
+    account_type: AccountType,
+    body: String,
+    check_digits: u8,
+    formatted: String,
+}
+
+impl AccountNumber {
+    /// Generates a new random account number for the given account type
+    pub fn generate(account_type: AccountType) -> Self {
+        let mut rng = rand::rng();
+        let body = (0..ACCOUNT_NUMBER_BODY_LENGTH)
+            .map(|_| BODY_CHARSET[rng.random_range(0..BODY_CHARSET.len())] as char)
+            .collect::<String>();
+
+        Self::from_parts(account_type, body)
+    }
+
+    /// Generates a new account number, retrying while `is_taken` reports a collision
+    pub fn generate_unique<F>(account_type: AccountType, is_taken: F) -> Result<Self, String>
+    where
+        F: Fn(&str) -> bool,
+    {
+        for _ in 0..MAX_GENERATION_ATTEMPTS {
+            let candidate = Self::generate(account_type.clone());
+            if !is_taken(candidate.as_str()) {
+                return Ok(candidate);
+            }
+        }
+        Err(format!(
+            "Could not generate a unique account number after {} attempts",
+            MAX_GENERATION_ATTEMPTS
+        ))
+    }
+
+    /// Parses and validates an account number, rejecting malformed input and bad check digits
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = value.split('-').collect();
+        if parts.len() != 3 {
+            return Err(format!("Malformed account number: {}", value));
+        }
+
+        let account_type = match parts[0] {
+            "Basic" => AccountType::Basic,
+            "Premium" => AccountType::Premium,
+            _ => return Err(format!("Unknown account type in account number: {}", value)),
+        };
+
+        let body = parts[1];
+        if body.len() != ACCOUNT_NUMBER_BODY_LENGTH
+            || !body.bytes().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+        {
+            return Err(format!("Malformed account number: {}", value));
+        }
+
+        let check = parts[2];
+        if check.len() != 2 || !check.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(format!("Malformed account number: {}", value));
+        }
+
+        let expected = Self::from_parts(account_type, body.to_string());
+        if expected.formatted != value {
+            return Err(format!("Invalid check digits in account number: {}", value));
+        }
+        Ok(expected)
+    }
+
+    /// Returns true when the value is a well-formed account number with valid check digits
+    pub fn is_valid(value: &str) -> bool {
+        Self::parse(value).is_ok()
+    }
+
+    /// Account type the number was issued for. The prefix is historical: an account keeps its number
+    /// when it is upgraded or downgraded, so use `Account::get_account_type` for the current type.
+    pub fn issued_account_type(&self) -> AccountType {
+        self.account_type.clone()
+    }
+
+    /// Random part of the number between the account type and the check digits
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+
+    pub fn check_digits(&self) -> u8 {
+        self.check_digits
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.formatted
+    }
+
+    fn from_parts(account_type: AccountType, body: String) -> Self {
+        let check_digits = Self::compute_check_digits(&account_type, &body);
+        let formatted = format!("{}-{}-{:02}", account_type, body, check_digits);
+        AccountNumber {
+            account_type,
+            body,
+            check_digits,
+            formatted,
+        }
+    }
+
+    /// ISO 7064 MOD 97-10 over a fixed-width encoding: every character becomes two digits
+    /// (`0`-`9` as `00`-`09`, `A`-`Z` as `10`-`35`), the number is extended by `00` and the
+    /// check digits are `98 - (n mod 97)`. Unlike the variable-width IBAN encoding this keeps
+    /// every position aligned, so no single substitution can leave the remainder unchanged.
+    fn compute_check_digits(account_type: &AccountType, body: &str) -> u8 {
+        let input = format!("{}{}", account_type.to_string().to_uppercase(), body);
+        let remainder = input
+            .chars()
+            .fold(0u32, |acc, c| (acc * 100 + c.to_digit(36).unwrap_or(0)) % 97);
+        let remainder = (remainder * 100) % 97;
+        (98 - remainder) as u8
+    }
+}
+
+impl fmt::Display for AccountNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.formatted)
+    }
+}
+
+impl FromStr for AccountNumber {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AccountNumber::parse(s)
+    }
+}
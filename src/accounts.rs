@@ -1,8 +1,12 @@
-use rand::{distributions::Alphanumeric, Rng};
 use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use crate::events::EventBus;
+use crate::rendering::{AccountSummary, Render, RenderFormat};
+use crate::transactions::Transaction;
 
 /// Enum defining account types
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum AccountType {
     Basic,
     Premium,
@@ -41,8 +45,33 @@ impl fmt::Display for AccountType {
     }
 }
 
+impl FromStr for AccountType {
+    type Err = String;
+
+    /// Parses `basic` or `premium`, ignoring case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "basic" => Ok(AccountType::Basic),
+            "premium" => Ok(AccountType::Premium),
+            _ => Err(format!("Unknown account type: {}", s)),
+        }
+    }
+}
+
+/// Structured view of an account, rendered on demand instead of printed
 pub trait DisplayAccount {
-    fn display_details(&self);
+    fn summary(&self) -> AccountSummary;
+
+    /// Summary formatted as an aligned text table, JSON or Markdown
+    fn render(&self, format: RenderFormat) -> String {
+        self.summary().render(format)
+    }
+
+    /// Prints the text rendering to standard output
+    #[deprecated(note = "use `render(RenderFormat::Text)` and print the returned string")]
+    fn display_details(&self) {
+        print!("{}", self.render(RenderFormat::Text));
+    }
 }
 
 /// Trait defining common account operations
@@ -50,11 +79,30 @@ pub trait Account : DisplayAccount {
     fn get_balance(&self) -> f64;
     fn deposit(&mut self, amount: f64) -> AccountResponse;
     fn withdraw(&mut self, amount: f64) -> AccountResponse;
+    /// Deposit recorded in the history as a transfer from the given account
+    fn deposit_from(&mut self, amount: f64, from_account_number: &str) -> AccountResponse;
+    /// Withdrawal recorded in the history as a transfer to the given account
+    fn withdraw_to(&mut self, amount: f64, to_account_number: &str) -> AccountResponse;
+    /// Undoes the latest transfer to the given account, e.g. one the recipient refused.
+    /// The transfer is removed from the history rather than offset by a fake incoming transfer.
+    fn reverse_transfer_out(&mut self, amount: f64, to_account_number: &str) -> AccountResponse;
+    /// Takes a charge from the account, recorded in the history as a fee
+    fn charge_fee(&mut self, amount: f64) -> AccountResponse {
+        self.withdraw(amount)
+    }
+    /// Pays interest into the account, recorded in the history as interest
+    fn pay_interest(&mut self, amount: f64) -> AccountResponse {
+        self.deposit(amount)
+    }
     fn get_currency(&self) -> &str;
     fn get_account_number(&self) -> &str;
     fn get_account_type(&self) -> AccountType;
+    /// Successful balance movements, oldest first
+    fn get_history(&self) -> &[Transaction];
     fn transfer(&mut self, to_account: &mut dyn Account, amount: f64) -> AccountTransferResponse;
     fn get_overdraft_limit(&self) -> f64;
+    /// Attaches the bus that receives this account's events, or detaches it with `None`
+    fn set_event_bus(&mut self, event_bus: Option<EventBus>);
 
     fn default_impl(&self) {
         println!("Default implementation for Account trait");
@@ -0,0 +1,337 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use crate::events::{EventBus, SubscriptionId, WalletEvent};
+use crate::transactions::{current_timestamp, Transaction, TransactionKind};
+
+/// Default number of seconds during which a rule that fired stays quiet
+pub const DEFAULT_ALERT_COOLDOWN_SECONDS: u64 = 60 * 60;
+
+/// Enum defining the conditions an account can be watched for
+#[derive(Clone, Debug, PartialEq)]
+pub enum AlertRule {
+    /// A debit left the balance below `threshold`
+    BalanceBelow { threshold: f64 },
+    /// A debit took the balance from zero or above to below zero
+    OverdraftEntered,
+    /// At least `percent` of the account's overdraft limit is in use
+    OverdraftUsage { percent: f64 },
+    /// A single deposit or incoming transfer of at least `threshold`
+    LargeDeposit { threshold: f64 },
+}
+
+impl fmt::Display for AlertRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AlertRule::BalanceBelow { .. } => write!(f, "BalanceBelow"),
+            AlertRule::OverdraftEntered => write!(f, "OverdraftEntered"),
+            AlertRule::OverdraftUsage { .. } => write!(f, "OverdraftUsage"),
+            AlertRule::LargeDeposit { .. } => write!(f, "LargeDeposit"),
+        }
+    }
+}
+
+/// Alert raised when a rule matched an account event
+#[derive(Clone, Debug, PartialEq)]
+pub struct Alert {
+    pub account_number: String,
+    pub currency: String,
+    pub rule: AlertRule,
+    pub balance: f64,
+    pub message: String,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+/// Trait defining how alerts reach the account holder
+pub trait Notifier {
+    fn notify(&mut self, alert: &Alert) -> Result<(), String>;
+}
+
+/// Notifier appending one tab-separated line per alert to a local outbox file
+pub struct OutboxNotifier {
+    path: PathBuf,
+}
+
+impl OutboxNotifier {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        OutboxNotifier {
+            path: path.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Notifier for OutboxNotifier {
+    fn notify(&mut self, alert: &Alert) -> Result<(), String> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| format!("Failed to open outbox {}: {}", self.path.display(), e))?;
+        writeln!(file, "{}\t{}\t{}\t{}", alert.timestamp, alert.account_number, alert.rule, alert.message)
+            .map_err(|e| format!("Failed to write to outbox {}: {}", self.path.display(), e))
+    }
+}
+
+struct WatchedRule {
+    rule: AlertRule,
+    last_fired_at: Option<u64>,
+}
+
+/// Alerts raised by an outgoing transfer that has not settled yet, with the cool-down each rule had before
+struct PendingTransfer {
+    account_number: String,
+    transaction: Transaction,
+    alerts: Vec<(Alert, Option<u64>)>,
+}
+
+/// Evaluates per-account alert rules against account events and delivers the resulting
+/// alerts to a notifier. A rule that fired is not fired again within the cool-down window.
+///
+/// Alerts raised by an outgoing transfer are held until the transfer settles: they are delivered
+/// once the recipient is credited, the wallet reports the transfer completed or the account moves
+/// again, and dropped when the debit is reversed.
+pub struct AlertEngine {
+    rules: HashMap<String, Vec<WatchedRule>>,
+    cooldown_seconds: u64,
+    notifier: Box<dyn Notifier>,
+    alerts: Vec<Alert>,
+    pending: Vec<PendingTransfer>,
+    delivery_errors: Vec<String>,
+}
+
+impl AlertEngine {
+    pub fn new(notifier: Box<dyn Notifier>) -> Self {
+        AlertEngine {
+            rules: HashMap::new(),
+            cooldown_seconds: DEFAULT_ALERT_COOLDOWN_SECONDS,
+            notifier,
+            alerts: Vec::new(),
+            pending: Vec::new(),
+            delivery_errors: Vec::new(),
+        }
+    }
+
+    /// Subscribes a shared engine to an event bus so rules are evaluated after each operation
+    pub fn subscribe_to(engine: &Rc<RefCell<AlertEngine>>, event_bus: &EventBus) -> SubscriptionId {
+        let engine = engine.clone();
+        event_bus.subscribe(move |event| {
+            engine.borrow_mut().evaluate(event);
+        })
+    }
+
+    pub fn set_cooldown_seconds(&mut self, seconds: u64) {
+        self.cooldown_seconds = seconds;
+    }
+
+    pub fn add_rule(&mut self, account_number: &str, rule: AlertRule) -> Result<(), String> {
+        match rule {
+            AlertRule::OverdraftUsage { percent } if !(0.0..=100.0).contains(&percent) => {
+                return Err(format!("Overdraft usage must be between 0 and 100 percent, got {}", percent));
+            }
+            AlertRule::LargeDeposit { threshold } if threshold <= 0.0 => {
+                return Err("Large deposit threshold must be positive".to_string());
+            }
+            _ => {}
+        }
+        self.rules.entry(account_number.to_string()).or_default().push(WatchedRule {
+            rule,
+            last_fired_at: None,
+        });
+        Ok(())
+    }
+
+    /// Removes every rule watching the account
+    pub fn clear_rules(&mut self, account_number: &str) {
+        self.rules.remove(account_number);
+    }
+
+    pub fn get_rules(&self, account_number: &str) -> Vec<AlertRule> {
+        self.rules.get(account_number)
+            .map(|rules| rules.iter().map(|watched| watched.rule.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Alerts raised so far, oldest first
+    pub fn get_alerts(&self) -> &[Alert] {
+        &self.alerts
+    }
+
+    /// Alerts held until their outgoing transfer settles, oldest first
+    pub fn get_pending_alerts(&self) -> Vec<Alert> {
+        self.pending.iter()
+            .flat_map(|pending| pending.alerts.iter().map(|(alert, _)| alert.clone()))
+            .collect()
+    }
+
+    /// Errors returned by the notifier, oldest first
+    pub fn get_delivery_errors(&self) -> &[String] {
+        &self.delivery_errors
+    }
+
+    /// Evaluates the rules of the event's account, returning the alerts delivered because of the event
+    pub fn evaluate(&mut self, event: &WalletEvent) -> Vec<Alert> {
+        let mut delivered = self.settle_pending(event);
+        let (account_number, currency) = match event {
+            WalletEvent::AccountCredited { account_number, currency, .. }
+            | WalletEvent::AccountDebited { account_number, currency, .. }
+            | WalletEvent::OverdraftUsed { account_number, currency, .. } => (account_number, currency),
+            _ => return self.deliver(delivered),
+        };
+        if !self.rules.contains_key(account_number) {
+            return self.deliver(delivered);
+        }
+        if let WalletEvent::AccountDebited { transaction, .. } = event {
+            if transaction.kind == TransactionKind::TransferOut {
+                self.pending.push(PendingTransfer {
+                    account_number: account_number.clone(),
+                    transaction: transaction.clone(),
+                    alerts: Vec::new(),
+                });
+            }
+        }
+
+        let now = current_timestamp();
+        let cooldown_seconds = self.cooldown_seconds;
+        let rules = self.rules.get_mut(account_number).unwrap();
+        let mut raised = Vec::new();
+        for watched in rules.iter_mut() {
+            let (balance, message) = match Self::check(&watched.rule, account_number, currency, event) {
+                Some(matched) => matched,
+                None => continue,
+            };
+            if let Some(last_fired_at) = watched.last_fired_at {
+                if now < last_fired_at + cooldown_seconds {
+                    continue;
+                }
+            }
+            raised.push((Alert {
+                account_number: account_number.clone(),
+                currency: currency.clone(),
+                rule: watched.rule.clone(),
+                balance,
+                message,
+                timestamp: now,
+            }, watched.last_fired_at));
+            watched.last_fired_at = Some(now);
+        }
+
+        // a debit alert of an unsettled transfer waits for the transfer, including the overdraft it caused
+        let is_debit = matches!(event, WalletEvent::AccountDebited { .. } | WalletEvent::OverdraftUsed { .. });
+        match self.pending.iter_mut().rev().find(|pending| &pending.account_number == account_number) {
+            Some(pending) if is_debit => pending.alerts.extend(raised),
+            _ => delivered.extend(raised.into_iter().map(|(alert, _)| alert)),
+        }
+        self.deliver(delivered)
+    }
+
+    /// Drops the alerts of a reversed transfer and releases those of transfers the event shows settled
+    fn settle_pending(&mut self, event: &WalletEvent) -> Vec<Alert> {
+        let mut released = Vec::new();
+        let mut index = 0;
+        while index < self.pending.len() {
+            let pending = &self.pending[index];
+            let is_reversed = matches!(event, WalletEvent::AccountDebitReversed { account_number, transaction, .. }
+                if account_number == &pending.account_number && transaction == &pending.transaction);
+            let is_settled = match event {
+                WalletEvent::TransferCompleted { sender_account_number, recipient_account_number, amount, .. } => {
+                    sender_account_number == &pending.account_number
+                        && Some(recipient_account_number) == pending.transaction.counterparty_account_number.as_ref()
+                        && *amount == pending.transaction.amount
+                }
+                WalletEvent::AccountCredited { account_number, transaction, .. } if account_number != &pending.account_number => {
+                    transaction.kind == TransactionKind::TransferIn
+                        && Some(account_number) == pending.transaction.counterparty_account_number.as_ref()
+                        && transaction.counterparty_account_number.as_ref() == Some(&pending.account_number)
+                        && transaction.amount == pending.transaction.amount
+                }
+                // a rollback reverses the latest debit straight away, so a new movement means it settled
+                WalletEvent::AccountCredited { account_number, .. } | WalletEvent::AccountDebited { account_number, .. } => {
+                    account_number == &pending.account_number
+                }
+                _ => false,
+            };
+            if is_reversed {
+                let pending = self.pending.remove(index);
+                self.restore_cooldowns(&pending);
+            } else if is_settled {
+                let pending = self.pending.remove(index);
+                released.extend(pending.alerts.into_iter().map(|(alert, _)| alert));
+            } else {
+                index += 1;
+            }
+        }
+        released
+    }
+
+    /// Lets the rules of a reversed transfer fire again as if the transfer never happened
+    fn restore_cooldowns(&mut self, pending: &PendingTransfer) {
+        let rules = match self.rules.get_mut(&pending.account_number) {
+            Some(rules) => rules,
+            None => return,
+        };
+        for (alert, previous_fired_at) in pending.alerts.iter() {
+            if let Some(watched) = rules.iter_mut()
+                .find(|watched| watched.rule == alert.rule && watched.last_fired_at == Some(alert.timestamp))
+            {
+                watched.last_fired_at = *previous_fired_at;
+            }
+        }
+    }
+
+    fn deliver(&mut self, alerts: Vec<Alert>) -> Vec<Alert> {
+        for alert in alerts.iter() {
+            if let Err(error) = self.notifier.notify(alert) {
+                self.delivery_errors.push(error);
+            }
+        }
+        self.alerts.extend(alerts.iter().cloned());
+        alerts
+    }
+
+    /// Returns the balance and message when the rule matches the event
+    fn check(rule: &AlertRule, account_number: &str, currency: &str, event: &WalletEvent) -> Option<(f64, String)> {
+        match (rule, event) {
+            (AlertRule::BalanceBelow { threshold }, WalletEvent::AccountDebited { transaction, .. })
+                if transaction.balance_after < *threshold =>
+            {
+                Some((
+                    transaction.balance_after,
+                    format!("Balance of {} {} on account {} is below {}", transaction.balance_after, currency, account_number, threshold),
+                ))
+            }
+            (AlertRule::OverdraftEntered, WalletEvent::OverdraftUsed { balance, entered: true, .. }) => Some((
+                *balance,
+                format!("Account {} is overdrawn with a balance of {} {}", account_number, balance, currency),
+            )),
+            (AlertRule::OverdraftUsage { percent }, WalletEvent::OverdraftUsed { balance, overdraft_limit, .. })
+                if *overdraft_limit > 0.0 && -balance / overdraft_limit * 100.0 >= *percent =>
+            {
+                Some((
+                    *balance,
+                    format!(
+                        "Account {} has used {:.0}% of its overdraft limit of {} {}",
+                        account_number, -balance / overdraft_limit * 100.0, overdraft_limit, currency
+                    ),
+                ))
+            }
+            (AlertRule::LargeDeposit { threshold }, WalletEvent::AccountCredited { transaction, .. })
+                if transaction.amount >= *threshold =>
+            {
+                Some((
+                    transaction.balance_after,
+                    format!("Large deposit of {} {} into account {}", transaction.amount, currency, account_number),
+                ))
+            }
+            _ => None,
+        }
+    }
+}
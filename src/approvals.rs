@@ -0,0 +1,325 @@
+use rand::distr::Alphanumeric;
+use rand::Rng;
+use crate::accounts::AccountResponse;
+use crate::auth::{AuthorizedWallet, Role};
+use crate::transactions::current_timestamp;
+use crate::wallets::{TransferResponse, Wallet, WithdrawWalletResponse};
+
+/// Default number of seconds a pending transfer can collect approvals
+pub const DEFAULT_APPROVAL_VALIDITY_SECONDS: u64 = 24 * 60 * 60;
+
+/// Transfers above `threshold` in `currency` need `required_approvals` of the `approvers`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ApprovalPolicy {
+    pub currency: String,
+    pub threshold: f64,
+    pub required_approvals: usize,
+    pub approvers: Vec<String>,
+}
+
+impl ApprovalPolicy {
+    pub fn new(currency: &str, threshold: f64, required_approvals: usize, approvers: &[&str]) -> Result<Self, String> {
+        if threshold < 0.0 {
+            return Err("Approval threshold cannot be negative".to_string());
+        }
+        let mut unique_approvers: Vec<String> = Vec::new();
+        for approver in approvers {
+            if !unique_approvers.iter().any(|a| a == approver) {
+                unique_approvers.push(approver.to_string());
+            }
+        }
+        if required_approvals == 0 || required_approvals > unique_approvers.len() {
+            return Err(format!(
+                "Required approvals must be between 1 and the number of approvers ({})",
+                unique_approvers.len()
+            ));
+        }
+        Ok(ApprovalPolicy {
+            currency: currency.to_string(),
+            threshold,
+            required_approvals,
+            approvers: unique_approvers,
+        })
+    }
+
+    pub fn applies_to(&self, currency: &str, amount: f64) -> bool {
+        self.currency == currency && amount > self.threshold
+    }
+}
+
+/// Enum defining the lifecycle of a pending transfer
+#[derive(Clone, Debug, PartialEq)]
+pub enum PendingTransferStatus {
+    Pending,
+    Executed,
+    Rejected,
+    Expired,
+    /// The quorum was reached but the wallet refused the transfer
+    Failed,
+}
+
+/// Transfer waiting for approvals before it is made
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingTransfer {
+    pub transfer_id: String,
+    pub requested_by: String,
+    pub recipient_wallet_id: String,
+    pub currency: String,
+    pub amount: f64,
+    /// Approvers and quorum copied from the policy when the transfer was submitted
+    pub approvers: Vec<String>,
+    pub required_approvals: usize,
+    pub approvals: Vec<String>,
+    pub rejected_by: Option<String>,
+    pub status: PendingTransferStatus,
+    /// Seconds since the Unix epoch
+    pub created_at: u64,
+    /// Approvals and execution are accepted while the current time is before this instant
+    pub expires_at: u64,
+    pub error_message: Option<String>,
+}
+
+impl PendingTransfer {
+    pub fn has_quorum(&self) -> bool {
+        self.approvals.len() >= self.required_approvals
+    }
+}
+
+/// Wallet whose large transfers must be approved by several people before they are made.
+/// Requesters and approvers authenticate against the users of the wrapped `AuthorizedWallet`,
+/// and approved transfers go through its wallet's `Wallet::transfer`.
+pub struct ApprovalWallet {
+    wallet: AuthorizedWallet,
+    policies: Vec<ApprovalPolicy>,
+    pending_transfers: Vec<PendingTransfer>,
+    approval_validity_seconds: u64,
+}
+
+impl ApprovalWallet {
+    pub fn new(wallet: AuthorizedWallet) -> Self {
+        ApprovalWallet {
+            wallet,
+            policies: Vec::new(),
+            pending_transfers: Vec::new(),
+            approval_validity_seconds: DEFAULT_APPROVAL_VALIDITY_SECONDS,
+        }
+    }
+
+    pub fn get_wallet(&self) -> &AuthorizedWallet {
+        &self.wallet
+    }
+
+    pub fn get_wallet_id(&self) -> &str {
+        self.wallet.get_wallet_id()
+    }
+
+    pub fn add_policy(&mut self, policy: ApprovalPolicy) {
+        self.policies.push(policy);
+    }
+
+    pub fn get_policies(&self) -> &[ApprovalPolicy] {
+        &self.policies
+    }
+
+    pub fn set_approval_validity_seconds(&mut self, seconds: u64) {
+        self.approval_validity_seconds = seconds;
+    }
+
+    /// Policy governing a transfer; the one with the highest threshold wins when several apply
+    pub fn find_policy(&self, currency: &str, amount: f64) -> Option<&ApprovalPolicy> {
+        self.policies.iter()
+            .filter(|policy| policy.applies_to(currency, amount))
+            .max_by(|a, b| a.threshold.total_cmp(&b.threshold))
+    }
+
+    pub fn balance(&mut self, user_id: &str, secret: &str, currency: &str) -> Result<f64, String> {
+        self.wallet.balance(user_id, secret, currency)
+    }
+
+    pub fn deposit(&mut self, user_id: &str, secret: &str, currency: &str, amount: f64) -> Result<AccountResponse, String> {
+        self.wallet.deposit(user_id, secret, currency, amount)
+    }
+
+    /// Withdraws directly. Refused when the amount needs approval.
+    pub fn withdraw(&mut self, user_id: &str, secret: &str, currency: &str, amount: f64) -> Result<WithdrawWalletResponse, String> {
+        self.check_no_approval_needed(currency, amount)?;
+        self.wallet.withdraw(user_id, secret, currency, amount)
+    }
+
+    /// Transfers directly. Refused when the amount needs approval; use `submit_transfer` instead.
+    pub fn transfer(
+        &mut self,
+        user_id: &str,
+        secret: &str,
+        to_wallet: &mut dyn Wallet,
+        currency: &str,
+        amount: f64,
+    ) -> Result<TransferResponse, String> {
+        self.check_no_approval_needed(currency, amount)?;
+        self.wallet.transfer(user_id, secret, to_wallet, currency, amount)
+    }
+
+    /// Creates a pending transfer under the policy that applies to it. Refused when the quorum
+    /// could never be reached because the requester is one of the approvers it needs.
+    pub fn submit_transfer(
+        &mut self,
+        requested_by: &str,
+        secret: &str,
+        recipient_wallet_id: &str,
+        currency: &str,
+        amount: f64,
+    ) -> Result<PendingTransfer, String> {
+        self.authenticate_spender(requested_by, secret)?;
+        let policy = self.find_policy(currency, amount)
+            .ok_or(format!("Transfer of {} {} does not require approval", amount, currency))?;
+        let eligible_approvers = policy.approvers.iter().filter(|a| *a != requested_by).count();
+        if eligible_approvers < policy.required_approvals {
+            return Err(format!(
+                "Transfer of {} {} needs {} approvals but only {} approvers other than {} can give them",
+                amount, currency, policy.required_approvals, eligible_approvers, requested_by
+            ));
+        }
+        let transfer_id = format!(
+            "Approval-{}",
+            rand::rng()
+                .sample_iter(&Alphanumeric)
+                .take(10)
+                .map(char::from)
+                .collect::<String>()
+        );
+        let created_at = current_timestamp();
+        let pending_transfer = PendingTransfer {
+            transfer_id,
+            requested_by: requested_by.to_string(),
+            recipient_wallet_id: recipient_wallet_id.to_string(),
+            currency: currency.to_string(),
+            amount,
+            approvers: policy.approvers.clone(),
+            required_approvals: policy.required_approvals,
+            approvals: Vec::new(),
+            rejected_by: None,
+            status: PendingTransferStatus::Pending,
+            created_at,
+            expires_at: created_at + self.approval_validity_seconds,
+            error_message: None,
+        };
+        self.pending_transfers.push(pending_transfer.clone());
+        Ok(pending_transfer)
+    }
+
+    /// Records an approval from an authenticated approver. Requesters cannot approve their own transfers.
+    pub fn approve(&mut self, transfer_id: &str, approver: &str, secret: &str) -> Result<&PendingTransfer, String> {
+        self.wallet.authenticate(approver, secret)?;
+        let pending_transfer = self.find_open_transfer(transfer_id)?;
+        if !pending_transfer.approvers.iter().any(|a| a == approver) {
+            return Err(format!("{} is not an approver for transfer {}", approver, transfer_id));
+        }
+        if pending_transfer.requested_by == approver {
+            return Err(format!("{} cannot approve their own transfer", approver));
+        }
+        if pending_transfer.approvals.iter().any(|a| a == approver) {
+            return Err(format!("{} has already approved transfer {}", approver, transfer_id));
+        }
+        pending_transfer.approvals.push(approver.to_string());
+        Ok(pending_transfer)
+    }
+
+    pub fn reject(&mut self, transfer_id: &str, approver: &str, secret: &str) -> Result<&PendingTransfer, String> {
+        self.wallet.authenticate(approver, secret)?;
+        let pending_transfer = self.find_open_transfer(transfer_id)?;
+        if !pending_transfer.approvers.iter().any(|a| a == approver) {
+            return Err(format!("{} is not an approver for transfer {}", approver, transfer_id));
+        }
+        pending_transfer.rejected_by = Some(approver.to_string());
+        pending_transfer.status = PendingTransferStatus::Rejected;
+        Ok(pending_transfer)
+    }
+
+    /// Makes an approved transfer through `Wallet::transfer`. The quorum stands in for the spending
+    /// limit, so any authenticated user who may spend can execute it.
+    pub fn execute(&mut self, transfer_id: &str, user_id: &str, secret: &str, to_wallet: &mut dyn Wallet) -> Result<TransferResponse, String> {
+        self.authenticate_spender(user_id, secret)?;
+        let index = self.find_open_transfer_index(transfer_id)?;
+        let pending_transfer = &self.pending_transfers[index];
+        if !pending_transfer.has_quorum() {
+            return Err(format!(
+                "Transfer {} has {} of {} required approvals",
+                transfer_id, pending_transfer.approvals.len(), pending_transfer.required_approvals
+            ));
+        }
+        if pending_transfer.recipient_wallet_id != to_wallet.get_wallet_id() {
+            return Err(format!(
+                "Transfer {} was approved for wallet {}, not {}",
+                transfer_id, pending_transfer.recipient_wallet_id, to_wallet.get_wallet_id()
+            ));
+        }
+
+        let response = self.wallet.wallet_mut().transfer(to_wallet, &pending_transfer.currency, pending_transfer.amount);
+        let pending_transfer = &mut self.pending_transfers[index];
+        if response.is_successful {
+            pending_transfer.status = PendingTransferStatus::Executed;
+        } else {
+            pending_transfer.status = PendingTransferStatus::Failed;
+            pending_transfer.error_message = response.error_message.clone();
+        }
+        Ok(response)
+    }
+
+    /// Marks every pending transfer past its expiry as expired, returning how many were expired
+    pub fn expire_stale_transfers(&mut self) -> usize {
+        let now = current_timestamp();
+        let mut expired = 0;
+        for pending_transfer in self.pending_transfers.iter_mut() {
+            if pending_transfer.status == PendingTransferStatus::Pending && now >= pending_transfer.expires_at {
+                pending_transfer.status = PendingTransferStatus::Expired;
+                expired += 1;
+            }
+        }
+        expired
+    }
+
+    pub fn get_pending_transfer(&self, transfer_id: &str) -> Option<&PendingTransfer> {
+        self.pending_transfers.iter().find(|t| t.transfer_id == transfer_id)
+    }
+
+    /// Returns every submitted transfer, whatever its status
+    pub fn get_pending_transfers(&self) -> &[PendingTransfer] {
+        &self.pending_transfers
+    }
+
+    fn authenticate_spender(&mut self, user_id: &str, secret: &str) -> Result<(), String> {
+        match self.wallet.authenticate(user_id, secret)? {
+            Role::Viewer => Err(format!("User {} has read-only access", user_id)),
+            _ => Ok(()),
+        }
+    }
+
+    fn check_no_approval_needed(&self, currency: &str, amount: f64) -> Result<(), String> {
+        match self.find_policy(currency, amount) {
+            Some(policy) => Err(format!(
+                "Amounts above {} {} need {} approvals; submit a pending transfer",
+                policy.threshold, currency, policy.required_approvals
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn find_open_transfer(&mut self, transfer_id: &str) -> Result<&mut PendingTransfer, String> {
+        let index = self.find_open_transfer_index(transfer_id)?;
+        Ok(&mut self.pending_transfers[index])
+    }
+
+    fn find_open_transfer_index(&mut self, transfer_id: &str) -> Result<usize, String> {
+        let index = self.pending_transfers.iter()
+            .position(|t| t.transfer_id == transfer_id)
+            .ok_or(format!("No pending transfer found with id: {}", transfer_id))?;
+        let pending_transfer = &mut self.pending_transfers[index];
+        if pending_transfer.status == PendingTransferStatus::Pending && current_timestamp() >= pending_transfer.expires_at {
+            pending_transfer.status = PendingTransferStatus::Expired;
+        }
+        if pending_transfer.status != PendingTransferStatus::Pending {
+            return Err(format!("Transfer {} is {:?}", transfer_id, pending_transfer.status));
+        }
+        Ok(index)
+    }
+}
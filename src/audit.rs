@@ -0,0 +1,263 @@
+use std::cell::RefCell;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::events::{EventBus, SubscriptionId, WalletEvent};
+use crate::transactions::current_timestamp;
+
+/// Previous hash of the first record in a log
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Record of a wallet or account operation, chained to the record before it by `previous_hash`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Position in the log, starting at 1
+    pub sequence: u64,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub operation: String,
+    pub wallet_id: Option<String>,
+    pub account_number: Option<String>,
+    pub currency: String,
+    pub amount: f64,
+    pub details: String,
+    pub previous_hash: String,
+    /// SHA-256 of every other field, hex encoded, see `compute_hash`
+    pub hash: String,
+}
+
+impl AuditRecord {
+    /// SHA-256 of the record serialized to JSON without its `hash`, so field boundaries and
+    /// missing values cannot be confused with the contents of another field
+    pub fn compute_hash(&self) -> String {
+        let mut content = serde_json::to_value(self).expect("audit records serialize to JSON");
+        if let Some(fields) = content.as_object_mut() {
+            fields.remove("hash");
+        }
+        Sha256::digest(content.to_string().as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+/// Result of a successful verification
+#[derive(Clone, Debug, PartialEq)]
+pub struct AuditVerification {
+    pub records: u64,
+    /// Hash of the last record, or `GENESIS_HASH` for an empty log. Keep it somewhere
+    /// safe to detect records removed from the end of the log.
+    pub head_hash: String,
+}
+
+/// Checks the sequence numbers, hashes and chaining of every record
+pub fn verify_records(records: &[AuditRecord]) -> Result<AuditVerification, String> {
+    let mut previous_hash = GENESIS_HASH.to_string();
+    for (index, record) in records.iter().enumerate() {
+        let expected_sequence = index as u64 + 1;
+        if record.sequence != expected_sequence {
+            return Err(format!(
+                "Record {} has sequence number {}: records were inserted or deleted",
+                expected_sequence, record.sequence
+            ));
+        }
+        if record.previous_hash != previous_hash {
+            return Err(format!("Record {} does not chain to the record before it", record.sequence));
+        }
+        if record.compute_hash() != record.hash {
+            return Err(format!("Record {} was modified: its hash does not match its content", record.sequence));
+        }
+        previous_hash = record.hash.clone();
+    }
+    Ok(AuditVerification {
+        records: records.len() as u64,
+        head_hash: previous_hash,
+    })
+}
+
+/// Verifies a log and checks that it ends at a previously recorded head hash
+pub fn verify_records_against(records: &[AuditRecord], expected_head_hash: &str) -> Result<AuditVerification, String> {
+    let verification = verify_records(records)?;
+    if verification.head_hash != expected_head_hash {
+        return Err(format!(
+            "Log ends at {} instead of the expected head {}: records were removed or appended",
+            verification.head_hash, expected_head_hash
+        ));
+    }
+    Ok(verification)
+}
+
+/// Reads a JSON-lines audit log file
+pub fn read_log_file<P: AsRef<Path>>(path: P) -> Result<Vec<AuditRecord>, String> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read audit log {}: {}", path.display(), e))?;
+    content.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line).map_err(|e| format!("Malformed audit record on line {}: {}", index + 1, e))
+        })
+        .collect()
+}
+
+pub fn verify_log_file<P: AsRef<Path>>(path: P) -> Result<AuditVerification, String> {
+    verify_records(&read_log_file(path)?)
+}
+
+/// Append-only, hash-chained audit log, kept in memory and optionally mirrored to a JSON-lines file
+pub struct AuditLog {
+    path: Option<PathBuf>,
+    records: Vec<AuditRecord>,
+    write_errors: Vec<String>,
+}
+
+impl AuditLog {
+    pub fn in_memory() -> Self {
+        AuditLog {
+            path: None,
+            records: Vec::new(),
+            write_errors: Vec::new(),
+        }
+    }
+
+    /// Opens a log file, creating it when missing. Existing records must verify.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let records = if path.exists() { read_log_file(&path)? } else { Vec::new() };
+        verify_records(&records)?;
+        Ok(AuditLog {
+            path: Some(path),
+            records,
+            write_errors: Vec::new(),
+        })
+    }
+
+    /// Subscribes a shared log to an event bus so every published event is recorded
+    pub fn subscribe_to(log: &Rc<RefCell<AuditLog>>, event_bus: &EventBus) -> SubscriptionId {
+        let log = log.clone();
+        event_bus.subscribe(move |event| {
+            let mut log = log.borrow_mut();
+            if let Err(error) = log.record_event(event) {
+                log.write_errors.push(error);
+            }
+        })
+    }
+
+    pub fn append(
+        &mut self,
+        operation: &str,
+        wallet_id: Option<&str>,
+        account_number: Option<&str>,
+        currency: &str,
+        amount: f64,
+        details: &str,
+    ) -> Result<&AuditRecord, String> {
+        // JSON has no NaN or infinity, so such a record could be written but never read back
+        if !amount.is_finite() {
+            return Err(format!("Cannot record {} with an amount that is not finite: {}", operation, amount));
+        }
+        let mut record = AuditRecord {
+            sequence: self.records.len() as u64 + 1,
+            timestamp: current_timestamp(),
+            operation: operation.to_string(),
+            wallet_id: wallet_id.map(|id| id.to_string()),
+            account_number: account_number.map(|n| n.to_string()),
+            currency: currency.to_string(),
+            amount,
+            details: details.to_string(),
+            previous_hash: self.get_head_hash().to_string(),
+            hash: "".to_string(),
+        };
+        record.hash = record.compute_hash();
+
+        if let Some(path) = &self.path {
+            let line = serde_json::to_string(&record).map_err(|e| format!("Failed to encode audit record: {}", e))?;
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| format!("Failed to open audit log {}: {}", path.display(), e))?;
+            writeln!(file, "{}", line).map_err(|e| format!("Failed to write audit log {}: {}", path.display(), e))?;
+        }
+        self.records.push(record);
+        Ok(self.records.last().unwrap())
+    }
+
+    pub fn record_event(&mut self, event: &WalletEvent) -> Result<&AuditRecord, String> {
+        let (operation, wallet_id, account_number, currency, amount, details) = match event {
+            WalletEvent::AccountCredited { account_number, currency, transaction } => (
+                "AccountCredited", None, Some(account_number), currency.as_str(), transaction.amount,
+                format!("{} balance_after={} counterparty={}", transaction.kind, transaction.balance_after,
+                        transaction.counterparty_account_number.as_deref().unwrap_or("")),
+            ),
+            WalletEvent::AccountDebited { account_number, currency, transaction } => (
+                "AccountDebited", None, Some(account_number), currency.as_str(), transaction.amount,
+                format!("{} balance_after={} counterparty={}", transaction.kind, transaction.balance_after,
+                        transaction.counterparty_account_number.as_deref().unwrap_or("")),
+            ),
+            WalletEvent::AccountDebitReversed { account_number, currency, transaction } => (
+                "AccountDebitReversed", None, Some(account_number), currency.as_str(), transaction.amount,
+                format!("{} counterparty={}", transaction.kind,
+                        transaction.counterparty_account_number.as_deref().unwrap_or("")),
+            ),
+            WalletEvent::OverdraftUsed { account_number, currency, balance, overdraft_limit, entered } => (
+                "OverdraftUsed", None, Some(account_number), currency.as_str(), *balance,
+                format!("overdraft_limit={} entered={}", overdraft_limit, entered),
+            ),
+            WalletEvent::OperationRejected { wallet_id, account_number, currency, operation, amount, reason } => (
+                "OperationRejected", wallet_id.as_ref(), account_number.as_ref(), currency.as_str(), *amount,
+                format!("{} reason={}", operation, reason),
+            ),
+            WalletEvent::TransferCompleted { sender_wallet_id, sender_account_number, recipient_wallet_id, recipient_account_number, currency, amount } => (
+                "TransferCompleted", Some(sender_wallet_id), Some(sender_account_number), currency.as_str(), *amount,
+                format!("to_wallet={} to_account={}", recipient_wallet_id, recipient_account_number),
+            ),
+            WalletEvent::TransferFailed { sender_wallet_id, sender_account_number, recipient_wallet_id, recipient_account_number, currency, amount, reason } => (
+                "TransferFailed", Some(sender_wallet_id), Some(sender_account_number), currency.as_str(), *amount,
+                format!("to_wallet={} to_account={} reason={}", recipient_wallet_id, recipient_account_number, reason),
+            ),
+            WalletEvent::TransferRolledBack { wallet_id, account_number, recipient_account_number, currency, amount, reason } => (
+                "TransferRolledBack", Some(wallet_id), Some(account_number), currency.as_str(), *amount,
+                format!("to_account={} reason={}", recipient_account_number, reason),
+            ),
+            WalletEvent::InternalTransferCompleted { wallet_id, from_account_number, to_account_number, currency, amount } => (
+                "InternalTransferCompleted", Some(wallet_id), Some(from_account_number), currency.as_str(), *amount,
+                format!("to_account={}", to_account_number),
+            ),
+            WalletEvent::AccountClosed { wallet_id, account_number, currency } => (
+                "AccountClosed", Some(wallet_id), Some(account_number), currency.as_str(), 0.0, String::new(),
+            ),
+            WalletEvent::AccountTypeChanged { wallet_id, account_number, currency, account_type, overdraft_limit } => (
+                "AccountTypeChanged", Some(wallet_id), Some(account_number), currency.as_str(), 0.0,
+                format!("account_type={} overdraft_limit={}", account_type, overdraft_limit),
+            ),
+            WalletEvent::ExchangeCompleted { wallet_id, record } => (
+                "ExchangeCompleted", Some(wallet_id), Some(&record.from_account_number), record.from_currency.as_str(), record.debited_amount,
+                format!("quote={} to_account={} to_currency={} credited={} rate={}", record.quote_id, record.to_account_number,
+                        record.to_currency, record.credited_amount, record.effective_rate),
+            ),
+            WalletEvent::ExchangeFailed { wallet_id, quote_id, from_currency, to_currency, reason } => (
+                "ExchangeFailed", Some(wallet_id), None, from_currency.as_str(), 0.0,
+                format!("quote={} to_currency={} reason={}", quote_id, to_currency, reason),
+            ),
+        };
+        self.append(operation, wallet_id.map(|id| id.as_str()), account_number.map(|n| n.as_str()), currency, amount, &details)
+    }
+
+    pub fn get_records(&self) -> &[AuditRecord] {
+        &self.records
+    }
+
+    pub fn get_head_hash(&self) -> &str {
+        self.records.last().map(|record| record.hash.as_str()).unwrap_or(GENESIS_HASH)
+    }
+
+    /// Errors raised while recording events received from an event bus
+    pub fn get_write_errors(&self) -> &[String] {
+        &self.write_errors
+    }
+
+    pub fn verify(&self) -> Result<AuditVerification, String> {
+        verify_records(&self.records)
+    }
+}
@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use rand::distr::Alphanumeric;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use crate::accounts::AccountResponse;
+use crate::transactions::current_timestamp;
+use crate::wallets::{TransferResponse, Wallet, WithdrawWalletResponse};
+
+/// Number of consecutive failed authentications after which a user is locked out
+pub const MAX_FAILED_ATTEMPTS: u32 = 3;
+
+/// Default number of seconds a locked-out user has to wait before trying again
+pub const DEFAULT_LOCKOUT_SECONDS: u64 = 15 * 60;
+
+/// Minimum length of a PIN or passphrase
+pub const MIN_SECRET_LENGTH: usize = 4;
+
+/// Number of hashing rounds used to stretch a secret
+const HASH_ROUNDS: u32 = 10_000;
+
+/// Enum defining what a user may do with a shared wallet
+#[derive(Clone, Debug, PartialEq)]
+pub enum Role {
+    /// Full access, including managing other users
+    Owner,
+    /// May deposit, and withdraw or transfer up to `limit` per operation
+    Spender { limit: f64 },
+    /// May only read balances and account details
+    Viewer,
+}
+
+/// Salted, stretched SHA-256 hash of a PIN or passphrase. The secret itself is never stored.
+#[derive(Clone, Debug)]
+pub struct Credential {
+    salt: String,
+    hash: String,
+}
+
+impl Credential {
+    pub fn new(secret: &str) -> Result<Self, String> {
+        if secret.chars().count() < MIN_SECRET_LENGTH {
+            return Err(format!("PIN or passphrase must be at least {} characters", MIN_SECRET_LENGTH));
+        }
+        let salt = rand::rng()
+            .sample_iter(&Alphanumeric)
+            .take(16)
+            .map(char::from)
+            .collect::<String>();
+        let hash = Self::hash(&salt, secret);
+        Ok(Credential { salt, hash })
+    }
+
+    /// Compares the hashes in constant time, so timing does not reveal how much of a guess matched
+    pub fn verify(&self, secret: &str) -> bool {
+        let (expected, actual) = (self.hash.as_bytes(), Self::hash(&self.salt, secret));
+        let actual = actual.as_bytes();
+        expected.len() == actual.len() && expected.iter().zip(actual).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+    }
+
+    fn hash(salt: &str, secret: &str) -> String {
+        let mut digest = Sha256::digest(format!("{}{}", salt, secret).as_bytes());
+        for _ in 1..HASH_ROUNDS {
+            digest = Sha256::digest(digest);
+        }
+        digest.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+struct WalletUser {
+    credential: Credential,
+    role: Role,
+    failed_attempts: u32,
+    /// Seconds since the Unix epoch at which the user was locked out
+    locked_at: Option<u64>,
+}
+
+impl WalletUser {
+    fn new(credential: Credential, role: Role) -> Self {
+        WalletUser { credential, role, failed_attempts: 0, locked_at: None }
+    }
+
+    fn is_locked(&self, lockout_seconds: u64) -> bool {
+        self.locked_at.is_some_and(|locked_at| current_timestamp() < locked_at + lockout_seconds)
+    }
+
+    fn clear_failures(&mut self) {
+        self.failed_attempts = 0;
+        self.locked_at = None;
+    }
+}
+
+/// Authorization layer around a wallet shared between several users.
+/// Every operation authenticates the user and checks that their role allows it.
+/// A lockout expires on its own, so a single-owner wallet cannot be locked for good.
+pub struct AuthorizedWallet {
+    wallet: Box<dyn Wallet>,
+    users: HashMap<String, WalletUser>,
+    lockout_seconds: u64,
+}
+
+impl AuthorizedWallet {
+    pub fn new(wallet: Box<dyn Wallet>, owner_id: &str, owner_secret: &str) -> Result<Self, String> {
+        let mut users = HashMap::new();
+        users.insert(owner_id.to_string(), WalletUser::new(Credential::new(owner_secret)?, Role::Owner));
+        Ok(AuthorizedWallet { wallet, users, lockout_seconds: DEFAULT_LOCKOUT_SECONDS })
+    }
+
+    pub fn get_wallet_id(&self) -> &str {
+        self.wallet.get_wallet_id()
+    }
+
+    /// Wrapped wallet without a role check, for wrappers that authorize operations themselves
+    pub(crate) fn wallet_mut(&mut self) -> &mut dyn Wallet {
+        self.wallet.as_mut()
+    }
+
+    /// Sets how long a user stays locked out after too many failed attempts
+    pub fn set_lockout_seconds(&mut self, seconds: u64) {
+        self.lockout_seconds = seconds;
+    }
+
+    /// Checks the user's PIN or passphrase, locking the user for the lockout period after repeated failures
+    pub fn authenticate(&mut self, user_id: &str, secret: &str) -> Result<Role, String> {
+        let lockout_seconds = self.lockout_seconds;
+        let user = self.users.get_mut(user_id).ok_or(format!("Unknown user: {}", user_id))?;
+        if user.is_locked(lockout_seconds) {
+            return Err(format!("User {} is locked after {} failed attempts", user_id, MAX_FAILED_ATTEMPTS));
+        }
+        if user.locked_at.is_some() {
+            // the lockout expired, so the user gets a fresh set of attempts
+            user.clear_failures();
+        }
+        if !user.credential.verify(secret) {
+            user.failed_attempts += 1;
+            if user.failed_attempts >= MAX_FAILED_ATTEMPTS {
+                user.locked_at = Some(current_timestamp());
+                return Err(format!("User {} is locked after {} failed attempts", user_id, MAX_FAILED_ATTEMPTS));
+            }
+            return Err(format!("Invalid credentials for user {}", user_id));
+        }
+        user.clear_failures();
+        Ok(user.role.clone())
+    }
+
+    pub fn is_locked(&self, user_id: &str) -> bool {
+        self.users.get(user_id).map(|user| user.is_locked(self.lockout_seconds)).unwrap_or(false)
+    }
+
+    /// Adds or replaces a user. Only owners may manage users, and the wallet always keeps an owner.
+    pub fn add_user(&mut self, owner_id: &str, owner_secret: &str, user_id: &str, user_secret: &str, role: Role) -> Result<(), String> {
+        self.require_owner(owner_id, owner_secret)?;
+        if let Role::Spender { limit } = role {
+            if limit < 0.0 {
+                return Err("Spender limit cannot be negative".to_string());
+            }
+        }
+        self.check_owner_remains(user_id, Some(&role))?;
+        self.users.insert(user_id.to_string(), WalletUser::new(Credential::new(user_secret)?, role));
+        Ok(())
+    }
+
+    pub fn remove_user(&mut self, owner_id: &str, owner_secret: &str, user_id: &str) -> Result<(), String> {
+        self.require_owner(owner_id, owner_secret)?;
+        if owner_id == user_id {
+            return Err("Owners cannot remove themselves".to_string());
+        }
+        self.check_owner_remains(user_id, None)?;
+        self.users.remove(user_id).map(|_| ()).ok_or(format!("Unknown user: {}", user_id))
+    }
+
+    /// Clears the failed attempts of a locked-out user before the lockout expires
+    pub fn unlock_user(&mut self, owner_id: &str, owner_secret: &str, user_id: &str) -> Result<(), String> {
+        self.require_owner(owner_id, owner_secret)?;
+        let user = self.users.get_mut(user_id).ok_or(format!("Unknown user: {}", user_id))?;
+        user.clear_failures();
+        Ok(())
+    }
+
+    pub fn change_secret(&mut self, user_id: &str, old_secret: &str, new_secret: &str) -> Result<(), String> {
+        self.authenticate(user_id, old_secret)?;
+        let credential = Credential::new(new_secret)?;
+        if let Some(user) = self.users.get_mut(user_id) {
+            user.credential = credential;
+        }
+        Ok(())
+    }
+
+    /// Read-only access to the wallet, available to every role
+    pub fn view(&mut self, user_id: &str, secret: &str) -> Result<&dyn Wallet, String> {
+        self.authenticate(user_id, secret)?;
+        Ok(self.wallet.as_ref())
+    }
+
+    pub fn balance(&mut self, user_id: &str, secret: &str, currency: &str) -> Result<f64, String> {
+        self.authenticate(user_id, secret)?;
+        self.wallet.balance(currency)
+    }
+
+    pub fn deposit(&mut self, user_id: &str, secret: &str, currency: &str, amount: f64) -> Result<AccountResponse, String> {
+        match self.authenticate(user_id, secret)? {
+            Role::Viewer => Err(format!("User {} has read-only access", user_id)),
+            _ => Ok(self.wallet.deposit(currency, amount)),
+        }
+    }
+
+    pub fn withdraw(&mut self, user_id: &str, secret: &str, currency: &str, amount: f64) -> Result<WithdrawWalletResponse, String> {
+        let role = self.authenticate(user_id, secret)?;
+        Self::check_spend(user_id, &role, amount)?;
+        Ok(self.wallet.withdraw(currency, amount))
+    }
+
+    pub fn transfer(
+        &mut self,
+        user_id: &str,
+        secret: &str,
+        to_wallet: &mut dyn Wallet,
+        currency: &str,
+        amount: f64,
+    ) -> Result<TransferResponse, String> {
+        let role = self.authenticate(user_id, secret)?;
+        Self::check_spend(user_id, &role, amount)?;
+        Ok(self.wallet.transfer(to_wallet, currency, amount))
+    }
+
+    fn require_owner(&mut self, user_id: &str, secret: &str) -> Result<(), String> {
+        match self.authenticate(user_id, secret)? {
+            Role::Owner => Ok(()),
+            _ => Err(format!("User {} is not an owner of the wallet", user_id)),
+        }
+    }
+
+    /// Refuses giving `user_id` the role `new_role`, or removing them when `None`, if that leaves no owner
+    fn check_owner_remains(&self, user_id: &str, new_role: Option<&Role>) -> Result<(), String> {
+        let other_owners = self.users.iter()
+            .filter(|(id, user)| id.as_str() != user_id && user.role == Role::Owner)
+            .count();
+        if other_owners == 0 && new_role != Some(&Role::Owner) {
+            return Err(format!("User {} is the last owner of the wallet", user_id));
+        }
+        Ok(())
+    }
+
+    fn check_spend(user_id: &str, role: &Role, amount: f64) -> Result<(), String> {
+        match role {
+            Role::Owner => Ok(()),
+            Role::Spender { limit } if amount <= *limit => Ok(()),
+            Role::Spender { limit } => Err(format!("Amount {} exceeds the spending limit of {} for user {}", amount, limit, user_id)),
+            Role::Viewer => Err(format!("User {} has read-only access", user_id)),
+        }
+    }
+}
@@ -1,43 +1,137 @@
-use rand::distributions::Alphanumeric;
-use rand::Rng;
+use crate::account_number::AccountNumber;
 use crate::accounts::{Account, AccountResponse, AccountTransferResponse, AccountType, DisplayAccount};
+use crate::events::{EventBus, WalletEvent};
+use crate::rendering::AccountSummary;
+use crate::transactions::{Transaction, TransactionKind};
 
 /// Basic account implementation with balance, currency, and account number
 #[derive(Debug)]
 pub struct BasicAccount {
     balance: f64,
     currency: String,
-    account_number: String,
+    account_number: AccountNumber,
     account_type: AccountType,
+    history: Vec<Transaction>,
+    event_bus: Option<EventBus>,
 }
 
 impl BasicAccount {
     pub fn new(currency: &str) -> Self {
-        let account_number = format!(
-            "{}-{}",
-            AccountType::Basic,
-            rand::thread_rng()
-                .sample_iter(&Alphanumeric)
-                .take(10)
-                .map(char::from)
-                .collect::<String>()
-        );
+        Self::restore(AccountNumber::generate(AccountType::Basic), currency, 0.0, Vec::new())
+    }
 
+    /// Creates an account with a pre-issued account number, e.g. one checked for collisions
+    pub fn with_account_number(currency: &str, account_number: AccountNumber) -> Result<Self, String> {
+        if account_number.issued_account_type() != AccountType::Basic {
+            return Err(format!("Account number {} is not a Basic account number", account_number));
+        }
+        Ok(Self::restore(account_number, currency, 0.0, Vec::new()))
+    }
+
+    /// Rebuilds an account from existing state, e.g. when converting from another account type
+    pub fn restore(account_number: AccountNumber, currency: &str, balance: f64, history: Vec<Transaction>) -> Self {
         BasicAccount {
-            balance: 0.0,
+            balance,
             currency: currency.to_string(),
             account_number,
             account_type: AccountType::Basic,
+            history,
+            event_bus: None,
+        }
+    }
+
+    fn response(&self, is_successful: bool, error_message: Option<String>) -> AccountResponse {
+        AccountResponse {
+            account_number: self.account_number.to_string(),
+            account_type: self.account_type.clone(),
+            currency: self.currency.clone(),
+            balance: self.balance,
+            is_successful,
+            error_message,
+        }
+    }
+
+    fn credit(&mut self, amount: f64, kind: TransactionKind, counterparty: Option<&str>) -> AccountResponse {
+        if amount < 0.0 {
+            return self.reject(kind, amount, "Cannot deposit a negative amount");
+        }
+        self.balance += amount;
+        let transaction = Transaction::new(kind, amount, self.balance, counterparty);
+        self.history.push(transaction.clone());
+        self.emit(WalletEvent::AccountCredited {
+            account_number: self.account_number.to_string(),
+            currency: self.currency.clone(),
+            transaction,
+        });
+        self.response(true, None)
+    }
+
+    fn debit(&mut self, amount: f64, kind: TransactionKind, counterparty: Option<&str>) -> AccountResponse {
+        if amount < 0.0 {
+            return self.reject(kind, amount, "Cannot withdraw a negative amount");
+        }
+        if self.balance >= amount {
+            self.balance -= amount;
+            let transaction = Transaction::new(kind, amount, self.balance, counterparty);
+            self.history.push(transaction.clone());
+            self.emit(WalletEvent::AccountDebited {
+                account_number: self.account_number.to_string(),
+                currency: self.currency.clone(),
+                transaction,
+            });
+            self.response(true, None)
+        } else {
+            self.reject(kind, amount, "Insufficient funds")
+        }
+    }
+
+    fn reverse_debit(&mut self, amount: f64, kind: TransactionKind, counterparty: Option<&str>) -> AccountResponse {
+        let is_latest = self.history.last().is_some_and(|transaction| {
+            transaction.kind == kind
+                && transaction.amount == amount
+                && transaction.counterparty_account_number.as_deref() == counterparty
+        });
+        if !is_latest {
+            return self.reject(kind, amount, "No matching transaction to reverse");
+        }
+        let transaction = self.history.pop().unwrap();
+        self.balance += amount;
+        self.emit(WalletEvent::AccountDebitReversed {
+            account_number: self.account_number.to_string(),
+            currency: self.currency.clone(),
+            transaction,
+        });
+        self.response(true, None)
+    }
+
+    fn reject(&self, operation: TransactionKind, amount: f64, reason: &str) -> AccountResponse {
+        self.emit(WalletEvent::OperationRejected {
+            wallet_id: None,
+            account_number: Some(self.account_number.to_string()),
+            currency: self.currency.clone(),
+            operation,
+            amount,
+            reason: reason.to_string(),
+        });
+        self.response(false, Some(reason.to_string()))
+    }
+
+    fn emit(&self, event: WalletEvent) {
+        if let Some(event_bus) = &self.event_bus {
+            event_bus.publish(event);
         }
     }
 }
 
 impl DisplayAccount for BasicAccount {
-    fn display_details(&self) {
-        println!("Account Number: {}", self.account_number);
-        println!("Account Type: {:?}", self.account_type);
-        println!("Currency: {}", self.currency);
-        println!("Balance: {}", self.balance);
+    fn summary(&self) -> AccountSummary {
+        AccountSummary {
+            account_number: self.account_number.to_string(),
+            account_type: self.account_type.clone(),
+            currency: self.currency.clone(),
+            balance: self.balance,
+            overdraft_limit: 0.0,
+        }
     }
 }
 
@@ -52,58 +146,31 @@ impl Account for BasicAccount {
     }
 
     fn deposit(&mut self, amount: f64) -> AccountResponse {
-        if amount < 0.0 {
-            return AccountResponse {
-                account_number: self.account_number.clone(),
-                account_type: self.account_type.clone(),
-                currency: self.currency.clone(),
-                balance: self.balance,
-                is_successful: false,
-                error_message: Some("Cannot deposit a negative amount".to_string()),
-            };
-        }
-        self.balance += amount;
-        AccountResponse {
-            account_number: self.account_number.clone(),
-            account_type: self.account_type.clone(),
-            currency: self.currency.clone(),
-            balance: self.balance,
-            is_successful: true,
-            error_message: None,
-        }
+        self.credit(amount, TransactionKind::Deposit, None)
     }
 
     fn withdraw(&mut self, amount: f64) -> AccountResponse {
-        if amount < 0.0 {
-            return AccountResponse {
-                account_number: self.account_number.clone(),
-                account_type: self.account_type.clone(),
-                currency: self.currency.clone(),
-                balance: self.balance,
-                is_successful: false,
-                error_message: Some("Cannot withdraw a negative amount".to_string()),
-            };
-        }
-        if self.balance >= amount {
-            self.balance -= amount;
-            AccountResponse {
-                account_number: self.account_number.clone(),
-                account_type: self.account_type.clone(),
-                currency: self.currency.clone(),
-                balance: self.balance,
-                is_successful: true,
-                error_message: None,
-            }
-        } else {
-            AccountResponse {
-                account_number: self.account_number.clone(),
-                account_type: self.account_type.clone(),
-                currency: self.currency.clone(),
-                balance: self.balance,
-                is_successful: false,
-                error_message: Some("Insufficient funds".to_string()),
-            }
-        }
+        self.debit(amount, TransactionKind::Withdrawal, None)
+    }
+
+    fn deposit_from(&mut self, amount: f64, from_account_number: &str) -> AccountResponse {
+        self.credit(amount, TransactionKind::TransferIn, Some(from_account_number))
+    }
+
+    fn withdraw_to(&mut self, amount: f64, to_account_number: &str) -> AccountResponse {
+        self.debit(amount, TransactionKind::TransferOut, Some(to_account_number))
+    }
+
+    fn reverse_transfer_out(&mut self, amount: f64, to_account_number: &str) -> AccountResponse {
+        self.reverse_debit(amount, TransactionKind::TransferOut, Some(to_account_number))
+    }
+
+    fn charge_fee(&mut self, amount: f64) -> AccountResponse {
+        self.debit(amount, TransactionKind::Fee, None)
+    }
+
+    fn pay_interest(&mut self, amount: f64) -> AccountResponse {
+        self.credit(amount, TransactionKind::Interest, None)
     }
 
     fn get_currency(&self) -> &str {
@@ -111,27 +178,32 @@ impl Account for BasicAccount {
     }
 
     fn get_account_number(&self) -> &str {
-        &self.account_number
+        self.account_number.as_str()
     }
 
     fn get_account_type(&self) -> AccountType {
         self.account_type.clone()
     }
 
+    fn get_history(&self) -> &[Transaction] {
+        &self.history
+    }
+
     fn transfer(&mut self, to_account: &mut dyn Account, amount: f64) -> AccountTransferResponse {
         let recipient_account_number = to_account.get_account_number().to_string();
         let recipient_account_type = to_account.get_account_type().clone();
         let currency = self.currency.clone();
 
+        let mut error_message = "Insufficient funds".to_string();
         if self.balance >= amount {
             // Withdraw from sender's account
-            let withdrawal_response = self.withdraw(amount);
+            let withdrawal_response = self.withdraw_to(amount, &recipient_account_number);
             if withdrawal_response.is_successful {
                 // Deposit to recipient's account
-                let deposit_response = to_account.deposit(amount);
+                let deposit_response = to_account.deposit_from(amount, self.account_number.as_str());
                 if deposit_response.is_successful {
                     return AccountTransferResponse {
-                        account_number: self.account_number.clone(),
+                        account_number: self.account_number.to_string(),
                         account_type: self.account_type.clone(),
                         currency: currency.clone(),
                         recipient_account_number,
@@ -141,22 +213,33 @@ impl Account for BasicAccount {
                         error_message: None,
                     };
                 }
+                // Deposit failed, so revert the withdrawal
+                self.reverse_transfer_out(amount, &recipient_account_number);
+                error_message = deposit_response.error_message.unwrap_or(error_message);
+            } else {
+                error_message = withdrawal_response.error_message.unwrap_or(error_message);
             }
+        } else {
+            self.reject(TransactionKind::TransferOut, amount, "Insufficient funds");
         }
 
         AccountTransferResponse {
-            account_number: self.account_number.clone(),
+            account_number: self.account_number.to_string(),
             account_type: self.account_type.clone(),
             currency: currency.clone(),
             recipient_account_number,
             recipient_account_type,
             balance: self.balance,
             is_successful: false,
-            error_message: Some("Insufficient funds".to_string()),
+            error_message: Some(error_message),
         }
     }
 
     fn get_overdraft_limit(&self) -> f64 {
         0.0
     }
+
+    fn set_event_bus(&mut self, event_bus: Option<EventBus>) {
+        self.event_bus = event_bus;
+    }
 }
@@ -1,8 +1,10 @@
-use std::any::TypeId;
-use std::borrow::Cow;
-use rand::distributions::Alphanumeric;
+use rand::distr::Alphanumeric;
 use rand::Rng;
+use crate::account_number::AccountNumber;
 use crate::accounts::{Account, AccountResponse, AccountType};
+use crate::events::{EventBus, WalletEvent};
+use crate::rendering::WalletSummary;
+use crate::transactions::TransactionKind;
 use crate::wallets::{Wallet, WalletType, TransferResponse, WithdrawWalletResponse, DisplayWallet};
 
 #[derive(Debug)]
@@ -10,6 +12,7 @@ pub struct BasicWallet<T: Account> {
     wallet_id: String,
     wallet_type: WalletType,
     account: T,
+    event_bus: Option<EventBus>,
 }
 
 impl<T: Account> BasicWallet<T> {
@@ -17,35 +20,39 @@ impl<T: Account> BasicWallet<T> {
         let wallet_id = format!(
             "{:?}-{}",
             WalletType::Basic,
-            rand::thread_rng()
+            rand::rng()
                 .sample_iter(&Alphanumeric)
                 .take(10)
                 .map(char::from)
                 .collect::<String>()
         );
 
+        Self::restore(&wallet_id, account)
+    }
+
+    /// Rebuilds a wallet with an existing wallet id, e.g. one loaded from a state file
+    pub fn restore(wallet_id: &str, account: T) -> Self {
         BasicWallet {
             account,
-            wallet_id,
+            wallet_id: wallet_id.to_string(),
             wallet_type: WalletType::Basic,
+            event_bus: None,
         }
     }
 }
 
 impl<T: Account> DisplayWallet for BasicWallet<T> {
-    fn display_details(&self) {
-        println!("-----------------------------");
-        println!("Wallet ID: {}", self.wallet_id);
-        println!("Wallet Type: {:?}", self.wallet_type);
-        println!("-----------------------------");
-        println!("Account Details: ");
-        self.account.display_details();
-        println!("-----------------------------");
+    fn summary(&self) -> WalletSummary {
+        WalletSummary {
+            wallet_id: self.wallet_id.clone(),
+            wallet_type: self.wallet_type.clone(),
+            accounts: vec![self.account.summary()],
+        }
     }
 }
 
 impl<T: Account> Wallet for BasicWallet<T> {
-    fn add_account(&mut self, account: Box<dyn Account>) -> Result<&dyn Account, String> {
+    fn add_account(&mut self, _account: Box<dyn Account>) -> Result<&dyn Account, String> {
         Err(String::from("Cannot add an account to a BasicWallet after it's created"))
     }
 
@@ -90,6 +97,20 @@ impl<T: Account> Wallet for BasicWallet<T> {
         }
     }
 
+    fn get_accounts(&self) -> Vec<&dyn Account> {
+        vec![&self.account]
+    }
+
+    fn get_accounts_closed_since(&self, _timestamp: u64) -> Vec<&dyn Account> {
+        // the single account of a basic wallet cannot be closed
+        Vec::new()
+    }
+
+    fn get_account_by_number(&self, account_number: &str) -> Result<&dyn Account, String> {
+        self.check_account_number(account_number)?;
+        Ok(&self.account)
+    }
+
     fn transfer(
         &mut self,
         to_wallet: &mut dyn Wallet,
@@ -98,85 +119,37 @@ impl<T: Account> Wallet for BasicWallet<T> {
     ) -> TransferResponse {
         let sender_account_number = self.account.get_account_number().to_string();
 
-        // if TypeId::of::<T>() == TypeId::of::<BasicWallet<T>>() {
-        //     println!("string={:?}", "BasicWallet");
-        // }
-
-        let recipient_account_number = to_wallet
-            .find_account_index_by_currency(&currency)
-            .map_or("".to_string(), |idx| {
-                to_wallet.get_account_number_by_index(idx).unwrap().to_string()
-            });
-
-        let transfer_result = if self.account.get_currency() == currency {
-            let withdraw_result = self.account.withdraw(amount);
-            if !withdraw_result.is_successful {
-                return TransferResponse {
-                    currency: currency.to_string(),
-                    amount,
-                    sender_account_number: self.account.get_account_number().to_string(),
-                    sender_wallet_id: self.wallet_id.clone(),
-                    sender_wallet_type: self.wallet_type.clone(),
-                    recipient_account_number,
-                    recipient_wallet_id: to_wallet.get_wallet_id().to_string(),
-                    recipient_wallet_type: to_wallet.get_wallet_type(),
-                    is_successful: false,
-                    error_message: withdraw_result.error_message,
-                };
-            }
-
-            let deposit_result = to_wallet.deposit(currency, amount);
-            if !deposit_result.is_successful {
-                self.account.deposit(amount); // Rollback withdrawal
-                return TransferResponse {
-                    currency: currency.to_string(),
-                    amount,
-                    sender_account_number: self.account.get_account_number().to_string(),
-                    sender_wallet_id: self.wallet_id.clone(),
-                    sender_wallet_type: self.wallet_type.clone(),
-                    recipient_account_number,
-                    recipient_wallet_id: to_wallet.get_wallet_id().to_string(),
-                    recipient_wallet_type: to_wallet.get_wallet_type(),
-                    is_successful: false,
-                    error_message: Some(deposit_result.error_message.unwrap()),
-                };
-            }
-
-            TransferResponse {
-                currency: currency.to_string(),
+        if self.account.get_currency() != currency {
+            return self.transfer_response(
+                to_wallet,
+                currency,
                 amount,
-                sender_account_number: self.account.get_account_number().to_string(),
-                sender_wallet_id: self.wallet_id.clone(),
-                sender_wallet_type: self.wallet_type.clone(),
-                recipient_account_number,
-                recipient_wallet_id: to_wallet.get_wallet_id().to_string(),
-                recipient_wallet_type: to_wallet.get_wallet_type(),
-                is_successful: true,
-                error_message: None,
+                sender_account_number,
+                "".to_string(),
+                Some(format!("Source account currency: {}, Receiver account currency: {}, mismatch", self.account.get_currency(), currency)),
+            );
+        }
+
+        match to_wallet.get_account_by_currency(currency).map(|acc| acc.get_account_number().to_string()) {
+            Some(recipient_account_number) => {
+                self.transfer_from_account(&sender_account_number, to_wallet, &recipient_account_number, amount)
             }
-        } else {
-            TransferResponse {
-                currency: currency.to_string(),
+            None => self.transfer_response(
+                to_wallet,
+                currency,
                 amount,
                 sender_account_number,
-                sender_wallet_id: self.wallet_id.clone(),
-                sender_wallet_type: self.wallet_type.clone(),
-                recipient_account_number,
-                recipient_wallet_id: to_wallet.get_wallet_id().to_string(),
-                recipient_wallet_type: to_wallet.get_wallet_type(),
-                is_successful: false,
-                error_message: Some(format!("Source account currency: {}, Receiver account currency: {}, mismatch", self.account.get_currency(), currency)),
-            }
-        };
-
-        transfer_result
+                "".to_string(),
+                Some(format!("No account found with currency: {}", currency)),
+            ),
+        }
     }
 
     fn deposit(&mut self, currency: &str, amount: f64) -> AccountResponse {
         if self.account.get_currency() == currency {
-            let deposit_result = self.account.deposit(amount);
-            deposit_result
+            self.account.deposit(amount)
         } else {
+            self.reject(None, currency, TransactionKind::Deposit, amount, "Currency mismatch");
             AccountResponse {
                 account_number: "".to_string(),
                 account_type: AccountType::Basic, // Default account type
@@ -203,6 +176,7 @@ impl<T: Account> Wallet for BasicWallet<T> {
                 error_message: withdrawal_result.error_message,
             }
         } else {
+            self.reject(None, currency, TransactionKind::Withdrawal, amount, "Currency mismatch");
             WithdrawWalletResponse {
                 wallet_id: self.wallet_id.clone(),
                 wallet_type: self.wallet_type.clone(),
@@ -216,4 +190,181 @@ impl<T: Account> Wallet for BasicWallet<T> {
             }
         }
     }
-}
\ No newline at end of file
+
+    fn transfer_from_account(
+        &mut self,
+        from_account_number: &str,
+        to_wallet: &mut dyn Wallet,
+        to_account_number: &str,
+        amount: f64,
+    ) -> TransferResponse {
+        let currency = self.account.get_currency().to_string();
+        if let Err(error) = self.check_account_number(from_account_number) {
+            return self.transfer_response(to_wallet, &currency, amount, from_account_number.to_string(), to_account_number.to_string(), Some(error));
+        }
+
+        let recipient_currency = match to_wallet.get_account_by_number(to_account_number) {
+            Ok(account) => account.get_currency().to_string(),
+            Err(error) => {
+                return self.transfer_response(to_wallet, &currency, amount, from_account_number.to_string(), to_account_number.to_string(), Some(error));
+            }
+        };
+        if recipient_currency != currency {
+            return self.transfer_response(
+                to_wallet,
+                &currency,
+                amount,
+                from_account_number.to_string(),
+                to_account_number.to_string(),
+                Some(format!("Source account currency: {}, Receiver account currency: {}, mismatch", currency, recipient_currency)),
+            );
+        }
+
+        let withdraw_result = self.account.withdraw_to(amount, to_account_number);
+        if !withdraw_result.is_successful {
+            return self.transfer_response(to_wallet, &currency, amount, from_account_number.to_string(), to_account_number.to_string(), withdraw_result.error_message);
+        }
+
+        let deposit_result = to_wallet.receive_transfer(to_account_number, amount, from_account_number);
+        if !deposit_result.is_successful {
+            self.account.reverse_transfer_out(amount, to_account_number); // Rollback withdrawal
+            self.emit(WalletEvent::TransferRolledBack {
+                wallet_id: self.wallet_id.clone(),
+                account_number: from_account_number.to_string(),
+                recipient_account_number: to_account_number.to_string(),
+                currency: currency.clone(),
+                amount,
+                reason: deposit_result.error_message.clone().unwrap_or_default(),
+            });
+            return self.transfer_response(to_wallet, &currency, amount, from_account_number.to_string(), to_account_number.to_string(), deposit_result.error_message);
+        }
+
+        self.transfer_response(to_wallet, &currency, amount, from_account_number.to_string(), to_account_number.to_string(), None)
+    }
+
+    fn deposit_to_account(&mut self, account_number: &str, amount: f64) -> AccountResponse {
+        match self.check_account_number(account_number) {
+            Ok(()) => self.account.deposit(amount),
+            Err(error) => self.account_not_found(account_number, TransactionKind::Deposit, amount, error),
+        }
+    }
+
+    fn withdraw_from_account(&mut self, account_number: &str, amount: f64) -> WithdrawWalletResponse {
+        match self.check_account_number(account_number) {
+            Ok(()) => {
+                let currency = self.account.get_currency().to_string();
+                self.withdraw(&currency, amount)
+            }
+            Err(error) => {
+                self.reject(Some(account_number), "", TransactionKind::Withdrawal, amount, &error);
+                WithdrawWalletResponse {
+                    wallet_id: self.wallet_id.clone(),
+                    wallet_type: self.wallet_type.clone(),
+                    currency: "".to_string(),
+                    amount,
+                    account_number: account_number.to_string(),
+                    account_type: AccountType::Basic, // Default account type
+                    balance: 0.0,
+                    is_successful: false,
+                    error_message: Some(error),
+                }
+            }
+        }
+    }
+
+    fn set_event_bus(&mut self, event_bus: Option<EventBus>) {
+        self.account.set_event_bus(event_bus.clone());
+        self.event_bus = event_bus;
+    }
+
+    fn receive_transfer(&mut self, account_number: &str, amount: f64, from_account_number: &str) -> AccountResponse {
+        match self.check_account_number(account_number) {
+            Ok(()) => self.account.deposit_from(amount, from_account_number),
+            Err(error) => self.account_not_found(account_number, TransactionKind::TransferIn, amount, error),
+        }
+    }
+}
+
+impl<T: Account> BasicWallet<T> {
+    /// Validates the account number and checks that it belongs to the wallet's account
+    fn check_account_number(&self, account_number: &str) -> Result<(), String> {
+        let account_number = AccountNumber::parse(account_number)?;
+        if self.account.get_account_number() == account_number.as_str() {
+            Ok(())
+        } else {
+            Err(format!("No account found with account number: {}", account_number))
+        }
+    }
+
+    fn account_not_found(&self, account_number: &str, operation: TransactionKind, amount: f64, error: String) -> AccountResponse {
+        self.reject(Some(account_number), "", operation, amount, &error);
+        AccountResponse {
+            account_number: account_number.to_string(),
+            account_type: AccountType::Basic, // Default account type
+            currency: "".to_string(),
+            balance: 0.0,
+            is_successful: false,
+            error_message: Some(error),
+        }
+    }
+
+    fn transfer_response(
+        &self,
+        to_wallet: &dyn Wallet,
+        currency: &str,
+        amount: f64,
+        sender_account_number: String,
+        recipient_account_number: String,
+        error_message: Option<String>,
+    ) -> TransferResponse {
+        self.emit(match &error_message {
+            None => WalletEvent::TransferCompleted {
+                sender_wallet_id: self.wallet_id.clone(),
+                sender_account_number: sender_account_number.clone(),
+                recipient_wallet_id: to_wallet.get_wallet_id().to_string(),
+                recipient_account_number: recipient_account_number.clone(),
+                currency: currency.to_string(),
+                amount,
+            },
+            Some(reason) => WalletEvent::TransferFailed {
+                sender_wallet_id: self.wallet_id.clone(),
+                sender_account_number: sender_account_number.clone(),
+                recipient_wallet_id: to_wallet.get_wallet_id().to_string(),
+                recipient_account_number: recipient_account_number.clone(),
+                currency: currency.to_string(),
+                amount,
+                reason: reason.clone(),
+            },
+        });
+        TransferResponse {
+            currency: currency.to_string(),
+            amount,
+            sender_account_number,
+            sender_wallet_id: self.wallet_id.clone(),
+            sender_wallet_type: self.wallet_type.clone(),
+            recipient_account_number,
+            recipient_wallet_id: to_wallet.get_wallet_id().to_string(),
+            recipient_wallet_type: to_wallet.get_wallet_type(),
+            is_successful: error_message.is_none(),
+            error_message,
+        }
+    }
+
+    /// Publishes a rejection raised by the wallet itself, before any account was touched
+    fn reject(&self, account_number: Option<&str>, currency: &str, operation: TransactionKind, amount: f64, reason: &str) {
+        self.emit(WalletEvent::OperationRejected {
+            wallet_id: Some(self.wallet_id.clone()),
+            account_number: account_number.map(|n| n.to_string()),
+            currency: currency.to_string(),
+            operation,
+            amount,
+            reason: reason.to_string(),
+        });
+    }
+
+    fn emit(&self, event: WalletEvent) {
+        if let Some(event_bus) = &self.event_bus {
+            event_bus.publish(event);
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use std::process;
+use clap::{Arg, App};
+use wallet_system::server::WalletServer;
+use wallet_system::service::WalletService;
+
+fn main() {
+    let matches = App::new("Wallet Server")
+        .version("1.0")
+        .author("kanth")
+        .about("Serves wallets and accounts as JSON over HTTP")
+        .arg(Arg::with_name("address")
+            .long("address")
+            .takes_value(true)
+            .default_value("127.0.0.1:8080")
+            .help("The address to listen on"))
+        .get_matches();
+
+    let address = matches.value_of("address").unwrap();
+    let server = match WalletServer::bind(address) {
+        Ok(server) => server,
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    };
+    if let Some(local_address) = server.local_address() {
+        println!("Listening on http://{}", local_address);
+    }
+    server.run(WalletService::new());
+}
@@ -0,0 +1,493 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::rc::Rc;
+use rand::distr::Alphanumeric;
+use rand::Rng;
+use crate::accounts::{Account, AccountResponse, AccountType};
+use crate::events::EventBus;
+use crate::rendering::WalletSummary;
+use crate::transactions::current_timestamp;
+use crate::wallets::{DisplayWallet, TransferResponse, Wallet, WalletType, WithdrawWalletResponse};
+
+/// Enum defining the know-your-customer verification tiers
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum KycTier {
+    Unverified,
+    Verified,
+    Enhanced,
+}
+
+/// Limits applied to a customer per currency, in units of that currency
+#[derive(Clone, Debug, PartialEq)]
+pub struct KycLimits {
+    /// Maximum total balance across all of the customer's wallets
+    pub max_balance: f64,
+    /// Maximum size of a single withdrawal or outgoing transfer
+    pub max_transfer: f64,
+}
+
+impl KycTier {
+    pub fn limits(&self) -> KycLimits {
+        match self {
+            KycTier::Unverified => KycLimits { max_balance: 1_000.0, max_transfer: 250.0 },
+            KycTier::Verified => KycLimits { max_balance: 50_000.0, max_transfer: 10_000.0 },
+            KycTier::Enhanced => KycLimits { max_balance: f64::INFINITY, max_transfer: f64::INFINITY },
+        }
+    }
+}
+
+impl fmt::Display for KycTier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KycTier::Unverified => write!(f, "Unverified"),
+            KycTier::Verified => write!(f, "Verified"),
+            KycTier::Enhanced => write!(f, "Enhanced"),
+        }
+    }
+}
+
+/// Struct defining how a customer can be contacted
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContactDetails {
+    pub email: String,
+    pub phone: Option<String>,
+    pub address: Option<String>,
+}
+
+/// Customer owning one or more wallets. Every wallet is wrapped on the way in, so all of its
+/// operations, including transfers it receives, are checked against the balance and transfer
+/// caps of the customer's KYC tier.
+pub struct Customer {
+    customer_id: String,
+    name: String,
+    contact: ContactDetails,
+    created_at: u64,
+    kyc: Rc<RefCell<KycState>>,
+    wallets: Vec<KycWallet>,
+}
+
+impl Customer {
+    pub fn new(name: &str, contact: ContactDetails, kyc_tier: KycTier) -> Self {
+        let customer_id = format!(
+            "Customer-{}",
+            rand::rng()
+                .sample_iter(&Alphanumeric)
+                .take(10)
+                .map(char::from)
+                .collect::<String>()
+        );
+
+        Customer {
+            customer_id,
+            name: name.to_string(),
+            contact,
+            created_at: current_timestamp(),
+            kyc: Rc::new(RefCell::new(KycState { kyc_tier, balances: BTreeMap::new() })),
+            wallets: Vec::new(),
+        }
+    }
+
+    pub fn get_customer_id(&self) -> &str {
+        &self.customer_id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_contact(&self) -> &ContactDetails {
+        &self.contact
+    }
+
+    pub fn set_contact(&mut self, contact: ContactDetails) {
+        self.contact = contact;
+    }
+
+    pub fn get_kyc_tier(&self) -> KycTier {
+        self.kyc.borrow().kyc_tier.clone()
+    }
+
+    /// Changes the KYC tier. Refused when the current balances exceed the balance cap of the new tier.
+    pub fn set_kyc_tier(&mut self, kyc_tier: KycTier) -> Result<(), String> {
+        check_balances(&self.balances(), &kyc_tier)?;
+        self.kyc.borrow_mut().kyc_tier = kyc_tier;
+        Ok(())
+    }
+
+    /// Seconds since the Unix epoch at which the customer was created
+    pub fn get_created_at(&self) -> u64 {
+        self.created_at
+    }
+
+    /// Takes ownership of a wallet. Refused when its balances would exceed the KYC balance cap.
+    pub fn add_wallet(&mut self, wallet: Box<dyn Wallet>) -> Result<&dyn Wallet, String> {
+        if self.wallets.iter().any(|w| w.get_wallet_id() == wallet.get_wallet_id()) {
+            return Err(format!("Wallet {} already belongs to customer {}", wallet.get_wallet_id(), self.customer_id));
+        }
+        let mut combined = self.balances();
+        for account in wallet.get_accounts() {
+            *combined.entry(account.get_currency().to_string()).or_insert(0.0) += account.get_balance();
+        }
+        check_balances(&combined, &self.get_kyc_tier())?;
+
+        let wallet = KycWallet { wallet, kyc: Rc::clone(&self.kyc) };
+        wallet.record_balances();
+        self.wallets.push(wallet);
+        Ok(self.wallets.last().unwrap())
+    }
+
+    /// Returns the customer's wallets, in the order they were added
+    pub fn get_wallets(&self) -> Vec<&dyn Wallet> {
+        self.wallets.iter().map(|w| w as &dyn Wallet).collect()
+    }
+
+    pub fn get_wallet(&self, wallet_id: &str) -> Option<&dyn Wallet> {
+        self.wallets.iter().find(|w| w.get_wallet_id() == wallet_id).map(|w| w as &dyn Wallet)
+    }
+
+    /// Total balance in a currency across all of the customer's wallets
+    pub fn total_balance(&self, currency: &str) -> f64 {
+        self.wallets.iter()
+            .flat_map(|w| w.get_accounts())
+            .filter(|acc| acc.get_currency() == currency)
+            .map(|acc| acc.get_balance())
+            .sum()
+    }
+
+    /// Total balance per currency across all of the customer's wallets
+    pub fn balances(&self) -> BTreeMap<String, f64> {
+        let mut balances = BTreeMap::new();
+        for account in self.wallets.iter().flat_map(|w| w.get_accounts()) {
+            *balances.entry(account.get_currency().to_string()).or_insert(0.0) += account.get_balance();
+        }
+        balances
+    }
+
+    pub fn deposit(&mut self, wallet_id: &str, currency: &str, amount: f64) -> AccountResponse {
+        match self.find_wallet_index(wallet_id) {
+            Ok(index) => self.wallets[index].deposit(currency, amount),
+            Err(error) => Self::deposit_failed(currency, error),
+        }
+    }
+
+    pub fn withdraw(&mut self, wallet_id: &str, currency: &str, amount: f64) -> WithdrawWalletResponse {
+        match self.find_wallet_index(wallet_id) {
+            Ok(index) => self.wallets[index].withdraw(currency, amount),
+            Err(error) => Self::withdraw_failed(wallet_id, WalletType::Basic, currency, amount, error),
+        }
+    }
+
+    /// Transfers from one of the customer's wallets to any other wallet
+    pub fn transfer(&mut self, wallet_id: &str, to_wallet: &mut dyn Wallet, currency: &str, amount: f64) -> TransferResponse {
+        match self.find_wallet_index(wallet_id) {
+            Ok(index) => self.wallets[index].transfer(to_wallet, currency, amount),
+            Err(error) => Self::transfer_failed(wallet_id, WalletType::Basic, to_wallet.get_wallet_id(), to_wallet.get_wallet_type(), currency, amount, error),
+        }
+    }
+
+    /// Transfers from one of the customer's wallets to a wallet of another customer, within the
+    /// caps of both customers
+    pub fn transfer_to_customer(&mut self, wallet_id: &str, recipient: &mut Customer, to_wallet_id: &str, currency: &str, amount: f64) -> TransferResponse {
+        match recipient.find_wallet_index(to_wallet_id) {
+            Ok(to_index) => self.transfer(wallet_id, &mut recipient.wallets[to_index], currency, amount),
+            Err(error) => Self::transfer_failed(wallet_id, WalletType::Basic, to_wallet_id, WalletType::Basic, currency, amount, error),
+        }
+    }
+
+    /// Transfers between two of the customer's own wallets
+    pub fn transfer_between_wallets(&mut self, from_wallet_id: &str, to_wallet_id: &str, currency: &str, amount: f64) -> Result<TransferResponse, String> {
+        self.kyc.borrow().check_transfer_limit(amount)?;
+        let from_index = self.find_wallet_index(from_wallet_id)?;
+        let to_index = self.find_wallet_index(to_wallet_id)?;
+        if from_index == to_index {
+            return Err("Cannot transfer between a wallet and itself".to_string());
+        }
+
+        let (from_wallet, to_wallet) = if from_index < to_index {
+            let (head, tail) = self.wallets.split_at_mut(to_index);
+            (&mut head[from_index], &mut tail[0])
+        } else {
+            let (head, tail) = self.wallets.split_at_mut(from_index);
+            (&mut tail[0], &mut head[to_index])
+        };
+        Ok(from_wallet.transfer(to_wallet, currency, amount))
+    }
+
+    fn find_wallet_index(&self, wallet_id: &str) -> Result<usize, String> {
+        self.wallets.iter()
+            .position(|w| w.get_wallet_id() == wallet_id)
+            .ok_or(format!("No wallet found with id: {}", wallet_id))
+    }
+
+    fn deposit_failed(currency: &str, error: String) -> AccountResponse {
+        AccountResponse {
+            account_number: "".to_string(),
+            account_type: AccountType::Basic, // Default account type
+            currency: currency.to_string(),
+            balance: 0.0,
+            is_successful: false,
+            error_message: Some(error),
+        }
+    }
+
+    fn withdraw_failed(wallet_id: &str, wallet_type: WalletType, currency: &str, amount: f64, error: String) -> WithdrawWalletResponse {
+        WithdrawWalletResponse {
+            wallet_id: wallet_id.to_string(),
+            wallet_type,
+            currency: currency.to_string(),
+            amount,
+            account_number: "".to_string(),
+            account_type: AccountType::Basic, // Default account type
+            balance: 0.0,
+            is_successful: false,
+            error_message: Some(error),
+        }
+    }
+
+    fn transfer_failed(
+        wallet_id: &str,
+        wallet_type: WalletType,
+        to_wallet_id: &str,
+        to_wallet_type: WalletType,
+        currency: &str,
+        amount: f64,
+        error: String,
+    ) -> TransferResponse {
+        TransferResponse {
+            currency: currency.to_string(),
+            amount,
+            sender_account_number: "".to_string(),
+            sender_wallet_id: wallet_id.to_string(),
+            sender_wallet_type: wallet_type,
+            recipient_account_number: "".to_string(),
+            recipient_wallet_id: to_wallet_id.to_string(),
+            recipient_wallet_type: to_wallet_type,
+            is_successful: false,
+            error_message: Some(error),
+        }
+    }
+}
+
+/// KYC tier of a customer and the balances of its wallets, shared by the wallets' wrappers
+struct KycState {
+    kyc_tier: KycTier,
+    /// Balance per wallet id and currency
+    balances: BTreeMap<(String, String), f64>,
+}
+
+impl KycState {
+    fn check_balance_limit(&self, operation: &str, currency: &str, amount: f64) -> Result<(), String> {
+        let limits = self.kyc_tier.limits();
+        let total: f64 = self.balances.iter()
+            .filter(|((_, balance_currency), _)| balance_currency == currency)
+            .map(|(_, balance)| balance)
+            .sum();
+        if total + amount > limits.max_balance {
+            return Err(format!(
+                "{} would take the {} balance above the {} KYC tier limit of {}",
+                operation, currency, self.kyc_tier, limits.max_balance
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_transfer_limit(&self, amount: f64) -> Result<(), String> {
+        let limits = self.kyc_tier.limits();
+        if amount > limits.max_transfer {
+            return Err(format!(
+                "Amount {} exceeds the {} KYC tier transfer limit of {}",
+                amount, self.kyc_tier, limits.max_transfer
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Wallet of a customer, checking every operation against the customer's KYC caps
+struct KycWallet {
+    wallet: Box<dyn Wallet>,
+    kyc: Rc<RefCell<KycState>>,
+}
+
+impl KycWallet {
+    /// Stores the balances of the wrapped wallet in the customer's KYC state
+    fn record_balances(&self) {
+        let wallet_id = self.wallet.get_wallet_id();
+        let mut kyc = self.kyc.borrow_mut();
+        kyc.balances.retain(|(balance_wallet_id, _), _| balance_wallet_id != wallet_id);
+        for account in self.wallet.get_accounts() {
+            let key = (wallet_id.to_string(), account.get_currency().to_string());
+            *kyc.balances.entry(key).or_insert(0.0) += account.get_balance();
+        }
+    }
+
+    /// Takes an outgoing transfer off the recorded balance before the recipient is credited,
+    /// so money moved between the customer's own wallets is not counted twice
+    fn record_debit(&self, currency: &str, amount: f64) {
+        let key = (self.wallet.get_wallet_id().to_string(), currency.to_string());
+        *self.kyc.borrow_mut().balances.entry(key).or_insert(0.0) -= amount;
+    }
+
+    fn currency_of(&self, account_number: &str) -> Option<String> {
+        self.wallet.get_account_by_number(account_number).ok().map(|account| account.get_currency().to_string())
+    }
+
+    fn transfer_refused(&self, to_wallet: &dyn Wallet, currency: &str, amount: f64, error: String) -> TransferResponse {
+        Customer::transfer_failed(
+            self.wallet.get_wallet_id(),
+            self.wallet.get_wallet_type(),
+            to_wallet.get_wallet_id(),
+            to_wallet.get_wallet_type(),
+            currency,
+            amount,
+            error,
+        )
+    }
+
+    fn withdraw_refused(&self, currency: &str, amount: f64, error: String) -> WithdrawWalletResponse {
+        Customer::withdraw_failed(self.wallet.get_wallet_id(), self.wallet.get_wallet_type(), currency, amount, error)
+    }
+}
+
+impl DisplayWallet for KycWallet {
+    fn summary(&self) -> WalletSummary {
+        self.wallet.summary()
+    }
+}
+
+impl Wallet for KycWallet {
+    fn add_account(&mut self, account: Box<dyn Account>) -> Result<&dyn Account, String> {
+        self.kyc.borrow().check_balance_limit("Adding the account", account.get_currency(), account.get_balance())?;
+        self.wallet.add_account(account)?;
+        self.record_balances();
+        self.wallet.get_accounts().last().copied().ok_or("Failed to add account".to_string())
+    }
+
+    fn balance(&self, currency: &str) -> Result<f64, String> {
+        self.wallet.balance(currency)
+    }
+
+    fn get_wallet_id(&self) -> &str {
+        self.wallet.get_wallet_id()
+    }
+
+    fn get_wallet_type(&self) -> WalletType {
+        self.wallet.get_wallet_type()
+    }
+
+    fn find_account_index_by_currency(&self, currency: &str) -> Option<usize> {
+        self.wallet.find_account_index_by_currency(currency)
+    }
+
+    fn get_account_number_by_index(&self, index: usize) -> Option<&str> {
+        self.wallet.get_account_number_by_index(index)
+    }
+
+    fn get_account_by_currency(&self, currency: &str) -> Option<&dyn Account> {
+        self.wallet.get_account_by_currency(currency)
+    }
+
+    fn get_accounts(&self) -> Vec<&dyn Account> {
+        self.wallet.get_accounts()
+    }
+
+    fn get_accounts_closed_since(&self, timestamp: u64) -> Vec<&dyn Account> {
+        self.wallet.get_accounts_closed_since(timestamp)
+    }
+
+    fn get_account_by_number(&self, account_number: &str) -> Result<&dyn Account, String> {
+        self.wallet.get_account_by_number(account_number)
+    }
+
+    fn transfer(&mut self, to_wallet: &mut dyn Wallet, currency: &str, amount: f64) -> TransferResponse {
+        if let Err(error) = self.kyc.borrow().check_transfer_limit(amount) {
+            return self.transfer_refused(to_wallet, currency, amount, error);
+        }
+        self.record_debit(currency, amount);
+        let response = self.wallet.transfer(to_wallet, currency, amount);
+        self.record_balances();
+        response
+    }
+
+    fn deposit(&mut self, currency: &str, amount: f64) -> AccountResponse {
+        if let Err(error) = self.kyc.borrow().check_balance_limit("Deposit", currency, amount) {
+            return Customer::deposit_failed(currency, error);
+        }
+        let response = self.wallet.deposit(currency, amount);
+        self.record_balances();
+        response
+    }
+
+    fn withdraw(&mut self, currency: &str, amount: f64) -> WithdrawWalletResponse {
+        if let Err(error) = self.kyc.borrow().check_transfer_limit(amount) {
+            return self.withdraw_refused(currency, amount, error);
+        }
+        let response = self.wallet.withdraw(currency, amount);
+        self.record_balances();
+        response
+    }
+
+    fn transfer_from_account(
+        &mut self,
+        from_account_number: &str,
+        to_wallet: &mut dyn Wallet,
+        to_account_number: &str,
+        amount: f64,
+    ) -> TransferResponse {
+        let currency = self.currency_of(from_account_number).unwrap_or_default();
+        if let Err(error) = self.kyc.borrow().check_transfer_limit(amount) {
+            return self.transfer_refused(to_wallet, &currency, amount, error);
+        }
+        self.record_debit(&currency, amount);
+        let response = self.wallet.transfer_from_account(from_account_number, to_wallet, to_account_number, amount);
+        self.record_balances();
+        response
+    }
+
+    fn deposit_to_account(&mut self, account_number: &str, amount: f64) -> AccountResponse {
+        let currency = self.currency_of(account_number).unwrap_or_default();
+        if let Err(error) = self.kyc.borrow().check_balance_limit("Deposit", &currency, amount) {
+            return Customer::deposit_failed(&currency, error);
+        }
+        let response = self.wallet.deposit_to_account(account_number, amount);
+        self.record_balances();
+        response
+    }
+
+    fn withdraw_from_account(&mut self, account_number: &str, amount: f64) -> WithdrawWalletResponse {
+        let currency = self.currency_of(account_number).unwrap_or_default();
+        if let Err(error) = self.kyc.borrow().check_transfer_limit(amount) {
+            return self.withdraw_refused(&currency, amount, error);
+        }
+        let response = self.wallet.withdraw_from_account(account_number, amount);
+        self.record_balances();
+        response
+    }
+
+    fn receive_transfer(&mut self, account_number: &str, amount: f64, from_account_number: &str) -> AccountResponse {
+        let currency = self.currency_of(account_number).unwrap_or_default();
+        if let Err(error) = self.kyc.borrow().check_balance_limit("Incoming transfer", &currency, amount) {
+            return Customer::deposit_failed(&currency, error);
+        }
+        let response = self.wallet.receive_transfer(account_number, amount, from_account_number);
+        self.record_balances();
+        response
+    }
+
+    fn set_event_bus(&mut self, event_bus: Option<EventBus>) {
+        self.wallet.set_event_bus(event_bus);
+    }
+}
+
+/// Refuses balances above the balance cap of a tier
+fn check_balances(balances: &BTreeMap<String, f64>, kyc_tier: &KycTier) -> Result<(), String> {
+    let limits = kyc_tier.limits();
+    if let Some((currency, balance)) = balances.iter().find(|(_, balance)| **balance > limits.max_balance) {
+        return Err(format!(
+            "Balance of {} {} exceeds the {} KYC tier limit of {}",
+            balance, currency, kyc_tier, limits.max_balance
+        ));
+    }
+    Ok(())
+}
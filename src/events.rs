@@ -0,0 +1,252 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::mem;
+use std::rc::Rc;
+use crate::accounts::AccountType;
+use crate::exchange::ExchangeRecord;
+use crate::transactions::{Transaction, TransactionKind};
+
+/// Typed events emitted by account and wallet operations
+#[derive(Clone, Debug, PartialEq)]
+pub enum WalletEvent {
+    /// Money added to an account by a deposit or an incoming transfer
+    AccountCredited {
+        account_number: String,
+        currency: String,
+        transaction: Transaction,
+    },
+    /// Money taken from an account by a withdrawal or an outgoing transfer
+    AccountDebited {
+        account_number: String,
+        currency: String,
+        transaction: Transaction,
+    },
+    /// A debit was undone and removed from the history. `transaction` is the removed entry.
+    AccountDebitReversed {
+        account_number: String,
+        currency: String,
+        transaction: Transaction,
+    },
+    /// A debit left the account below zero. `entered` is set when the balance was not negative before.
+    OverdraftUsed {
+        account_number: String,
+        currency: String,
+        balance: f64,
+        overdraft_limit: f64,
+        entered: bool,
+    },
+    /// An account or wallet refused an operation; nothing was posted
+    OperationRejected {
+        wallet_id: Option<String>,
+        account_number: Option<String>,
+        currency: String,
+        operation: TransactionKind,
+        amount: f64,
+        reason: String,
+    },
+    TransferCompleted {
+        sender_wallet_id: String,
+        sender_account_number: String,
+        recipient_wallet_id: String,
+        recipient_account_number: String,
+        currency: String,
+        amount: f64,
+    },
+    TransferFailed {
+        sender_wallet_id: String,
+        sender_account_number: String,
+        recipient_wallet_id: String,
+        recipient_account_number: String,
+        currency: String,
+        amount: f64,
+        reason: String,
+    },
+    /// The recipient refused a transfer after the sender was debited, so the debit was reversed
+    TransferRolledBack {
+        wallet_id: String,
+        account_number: String,
+        recipient_account_number: String,
+        currency: String,
+        amount: f64,
+        reason: String,
+    },
+    /// Money moved between two accounts of the same wallet
+    InternalTransferCompleted {
+        wallet_id: String,
+        from_account_number: String,
+        to_account_number: String,
+        currency: String,
+        amount: f64,
+    },
+    /// An account was closed; it stays queryable among the closed accounts of its wallet
+    AccountClosed {
+        wallet_id: String,
+        account_number: String,
+        currency: String,
+    },
+    /// An account was upgraded or downgraded in place, keeping its number, balance and history
+    AccountTypeChanged {
+        wallet_id: String,
+        account_number: String,
+        currency: String,
+        account_type: AccountType,
+        overdraft_limit: f64,
+    },
+    ExchangeCompleted {
+        wallet_id: String,
+        record: ExchangeRecord,
+    },
+    ExchangeFailed {
+        wallet_id: String,
+        quote_id: String,
+        from_currency: String,
+        to_currency: String,
+        reason: String,
+    },
+}
+
+/// Enum defining when published events reach the subscribers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeliveryMode {
+    /// Each event is delivered as soon as it is published
+    Synchronous,
+    /// Events are queued until `EventBus::flush` is called
+    Buffered,
+}
+
+/// Handle returned by `EventBus::subscribe`, used to unsubscribe
+pub type SubscriptionId = usize;
+
+type Subscriber = Box<dyn FnMut(&WalletEvent)>;
+
+struct EventBusState {
+    delivery_mode: DeliveryMode,
+    subscribers: Vec<(SubscriptionId, Subscriber)>,
+    unsubscribed: Vec<SubscriptionId>,
+    queue: VecDeque<WalletEvent>,
+    next_subscription_id: SubscriptionId,
+    dispatching: bool,
+}
+
+/// Shared event bus. Clones are handles to the same bus, so one bus can be attached to
+/// several accounts and wallets. Events published while subscribers are being called are
+/// delivered after the current event, in order.
+#[derive(Clone)]
+pub struct EventBus {
+    state: Rc<RefCell<EventBusState>>,
+}
+
+impl EventBus {
+    pub fn new(delivery_mode: DeliveryMode) -> Self {
+        EventBus {
+            state: Rc::new(RefCell::new(EventBusState {
+                delivery_mode,
+                subscribers: Vec::new(),
+                unsubscribed: Vec::new(),
+                queue: VecDeque::new(),
+                next_subscription_id: 0,
+                dispatching: false,
+            })),
+        }
+    }
+
+    pub fn get_delivery_mode(&self) -> DeliveryMode {
+        self.state.borrow().delivery_mode
+    }
+
+    /// Changes the delivery mode. Switching to synchronous delivery flushes queued events.
+    pub fn set_delivery_mode(&self, delivery_mode: DeliveryMode) {
+        self.state.borrow_mut().delivery_mode = delivery_mode;
+        if delivery_mode == DeliveryMode::Synchronous {
+            self.flush();
+        }
+    }
+
+    pub fn subscribe<F>(&self, subscriber: F) -> SubscriptionId
+    where
+        F: FnMut(&WalletEvent) + 'static,
+    {
+        let mut state = self.state.borrow_mut();
+        let id = state.next_subscription_id;
+        state.next_subscription_id += 1;
+        state.subscribers.push((id, Box::new(subscriber)));
+        id
+    }
+
+    pub fn unsubscribe(&self, id: SubscriptionId) {
+        let mut state = self.state.borrow_mut();
+        state.subscribers.retain(|(subscriber_id, _)| *subscriber_id != id);
+        if state.dispatching {
+            // the subscriber list is checked out while dispatching
+            state.unsubscribed.push(id);
+        }
+    }
+
+    pub fn publish(&self, event: WalletEvent) {
+        let delivery_mode = {
+            let mut state = self.state.borrow_mut();
+            state.queue.push_back(event);
+            state.delivery_mode
+        };
+        if delivery_mode == DeliveryMode::Synchronous {
+            self.dispatch();
+        }
+    }
+
+    /// Delivers all queued events, returning how many were delivered
+    pub fn flush(&self) -> usize {
+        self.dispatch()
+    }
+
+    /// Number of events waiting for delivery
+    pub fn pending_events(&self) -> usize {
+        self.state.borrow().queue.len()
+    }
+
+    fn dispatch(&self) -> usize {
+        {
+            let mut state = self.state.borrow_mut();
+            if state.dispatching {
+                return 0;
+            }
+            state.dispatching = true;
+        }
+
+        let mut delivered = 0;
+        loop {
+            let (event, mut subscribers) = {
+                let mut state = self.state.borrow_mut();
+                match state.queue.pop_front() {
+                    Some(event) => (event, mem::take(&mut state.subscribers)),
+                    None => break,
+                }
+            };
+            for (_, subscriber) in subscribers.iter_mut() {
+                subscriber(&event);
+            }
+
+            let mut state = self.state.borrow_mut();
+            // keep subscribers added by a subscriber, drop those it removed
+            subscribers.append(&mut state.subscribers);
+            let unsubscribed = mem::take(&mut state.unsubscribed);
+            subscribers.retain(|(id, _)| !unsubscribed.contains(id));
+            state.subscribers = subscribers;
+            delivered += 1;
+        }
+
+        self.state.borrow_mut().dispatching = false;
+        delivered
+    }
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let state = self.state.borrow();
+        f.debug_struct("EventBus")
+            .field("delivery_mode", &state.delivery_mode)
+            .field("subscribers", &state.subscribers.len())
+            .field("pending_events", &state.queue.len())
+            .finish()
+    }
+}
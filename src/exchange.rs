@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+/// Trait defining a source of currency exchange rates
+pub trait ExchangeRateSource {
+    /// Returns how many units of `to_currency` one unit of `from_currency` is worth
+    fn get_rate(&self, from_currency: &str, to_currency: &str) -> Option<f64>;
+}
+
+/// Exchange rate source backed by a fixed table of rates
+#[derive(Clone, Debug, Default)]
+pub struct StaticExchangeRates {
+    rates: HashMap<(String, String), f64>,
+}
+
+impl StaticExchangeRates {
+    pub fn new() -> Self {
+        StaticExchangeRates {
+            rates: HashMap::new(),
+        }
+    }
+
+    /// Sets the rate from one currency to another. The inverse rate is derived when not set explicitly.
+    pub fn set_rate(&mut self, from_currency: &str, to_currency: &str, rate: f64) -> Result<(), String> {
+        if rate <= 0.0 || !rate.is_finite() {
+            return Err(format!("Invalid exchange rate from {} to {}: {}", from_currency, to_currency, rate));
+        }
+        self.rates.insert((from_currency.to_string(), to_currency.to_string()), rate);
+        Ok(())
+    }
+}
+
+impl ExchangeRateSource for StaticExchangeRates {
+    fn get_rate(&self, from_currency: &str, to_currency: &str) -> Option<f64> {
+        if from_currency == to_currency {
+            return Some(1.0);
+        }
+        if let Some(rate) = self.rates.get(&(from_currency.to_string(), to_currency.to_string())) {
+            return Some(*rate);
+        }
+        self.rates
+            .get(&(to_currency.to_string(), from_currency.to_string()))
+            .map(|rate| 1.0 / rate)
+    }
+}
+
+/// Locked-in price for exchanging money between two accounts of a `MultiCurrencyWallet`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExchangeQuote {
+    pub quote_id: String,
+    pub wallet_id: String,
+    pub from_currency: String,
+    pub to_currency: String,
+    /// Accounts that were the defaults for the two currencies when the quote was issued
+    pub from_account_number: String,
+    pub to_account_number: String,
+    /// Amount debited in `from_currency`
+    pub amount: f64,
+    /// Rate from the exchange rate source, before the spread
+    pub market_rate: f64,
+    pub spread: f64,
+    /// Rate actually applied: `market_rate * (1 - spread)`
+    pub effective_rate: f64,
+    /// Amount credited in `to_currency`
+    pub converted_amount: f64,
+    /// Seconds since the Unix epoch
+    pub quoted_at: u64,
+    /// The quote can be executed while the current time is before this instant
+    pub expires_at: u64,
+}
+
+/// Struct defining the response for an executed exchange
+#[derive(Clone, Debug)]
+pub struct ExchangeResponse {
+    pub quote_id: String,
+    pub from_account_number: String,
+    pub to_account_number: String,
+    pub from_currency: String,
+    pub to_currency: String,
+    pub debited_amount: f64,
+    pub credited_amount: f64,
+    pub effective_rate: f64,
+    pub is_successful: bool,
+    pub error_message: Option<String>,
+}
+
+/// Completed exchange kept in the wallet's exchange history
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExchangeRecord {
+    pub quote_id: String,
+    pub from_account_number: String,
+    pub to_account_number: String,
+    pub from_currency: String,
+    pub to_currency: String,
+    pub debited_amount: f64,
+    pub credited_amount: f64,
+    pub market_rate: f64,
+    pub effective_rate: f64,
+    pub timestamp: u64,
+}
@@ -0,0 +1,325 @@
+use std::fmt::Write;
+use std::str::FromStr;
+use crate::accounts::Account;
+use crate::transactions::{date_time_from_timestamp, timestamp_from_date_time, Transaction, TransactionKind};
+
+/// Bank identifier written to OFX files, which require one for every account
+pub const OFX_BANK_ID: &str = "WALLET";
+
+/// File formats understood by personal finance tools
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    /// Open Financial Exchange 2.x (XML)
+    Ofx,
+    /// Quicken Interchange Format
+    Qif,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    /// Parses `ofx` or `qif`, ignoring case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "ofx" => Ok(ExportFormat::Ofx),
+            "qif" => Ok(ExportFormat::Qif),
+            _ => Err(format!("Unknown export format: {}", s)),
+        }
+    }
+}
+
+/// Transaction as read back from an exported file, which does not keep running balances
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActivityRecord {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub kind: TransactionKind,
+    pub amount: f64,
+    pub counterparty_account_number: Option<String>,
+}
+
+impl From<&Transaction> for ActivityRecord {
+    fn from(transaction: &Transaction) -> Self {
+        ActivityRecord {
+            timestamp: transaction.timestamp,
+            kind: transaction.kind.clone(),
+            amount: transaction.amount,
+            counterparty_account_number: transaction.counterparty_account_number.clone(),
+        }
+    }
+}
+
+/// Contents of an OFX or QIF file. QIF files carry no currency or balance.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportedActivity {
+    pub account_number: Option<String>,
+    pub currency: Option<String>,
+    pub ledger_balance: Option<f64>,
+    pub records: Vec<ActivityRecord>,
+}
+
+/// Exports the whole history of an account; `generated_at` is the OFX server time
+pub fn export_account(format: ExportFormat, account: &dyn Account, generated_at: u64) -> String {
+    export_transactions(format, account.get_account_number(), account.get_currency(), account.get_history(), generated_at)
+}
+
+pub fn export_transactions(format: ExportFormat, account_number: &str, currency: &str, transactions: &[Transaction], generated_at: u64) -> String {
+    match format {
+        ExportFormat::Ofx => to_ofx(account_number, currency, transactions, generated_at),
+        ExportFormat::Qif => to_qif(account_number, transactions),
+    }
+}
+
+/// Writes a bank statement response in OFX 2.1.1. Transfers name the counterparty account
+/// in `BANKACCTTO`; the ledger balance is the balance after the last transaction.
+pub fn to_ofx(account_number: &str, currency: &str, transactions: &[Transaction], generated_at: u64) -> String {
+    let start = transactions.first().map(|t| t.timestamp).unwrap_or(generated_at);
+    let end = transactions.last().map(|t| t.timestamp).unwrap_or(generated_at);
+    let ledger_balance = transactions.last().map(|t| t.balance_after).unwrap_or(0.0);
+
+    let mut ofx = String::new();
+    ofx.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"no\"?>\n");
+    ofx.push_str("<?OFX OFXHEADER=\"200\" VERSION=\"211\" SECURITY=\"NONE\" OLDFILEUID=\"NONE\" NEWFILEUID=\"NONE\"?>\n");
+    ofx.push_str("<OFX>\n");
+    ofx.push_str("<SIGNONMSGSRSV1>\n<SONRS>\n<STATUS>\n<CODE>0</CODE>\n<SEVERITY>INFO</SEVERITY>\n</STATUS>\n");
+    writeln!(ofx, "<DTSERVER>{}</DTSERVER>", ofx_date(generated_at)).unwrap();
+    ofx.push_str("<LANGUAGE>ENG</LANGUAGE>\n</SONRS>\n</SIGNONMSGSRSV1>\n");
+    ofx.push_str("<BANKMSGSRSV1>\n<STMTTRNRS>\n<TRNUID>1</TRNUID>\n<STATUS>\n<CODE>0</CODE>\n<SEVERITY>INFO</SEVERITY>\n</STATUS>\n");
+    ofx.push_str("<STMTRS>\n");
+    writeln!(ofx, "<CURDEF>{}</CURDEF>", xml_escape(currency)).unwrap();
+    write_ofx_account(&mut ofx, "BANKACCTFROM", account_number);
+    ofx.push_str("<BANKTRANLIST>\n");
+    writeln!(ofx, "<DTSTART>{}</DTSTART>", ofx_date(start)).unwrap();
+    writeln!(ofx, "<DTEND>{}</DTEND>", ofx_date(end)).unwrap();
+    for (index, transaction) in transactions.iter().enumerate() {
+        ofx.push_str("<STMTTRN>\n");
+        writeln!(ofx, "<TRNTYPE>{}</TRNTYPE>", ofx_transaction_type(&transaction.kind)).unwrap();
+        writeln!(ofx, "<DTPOSTED>{}</DTPOSTED>", ofx_date(transaction.timestamp)).unwrap();
+        writeln!(ofx, "<TRNAMT>{:.2}</TRNAMT>", transaction.signed_amount()).unwrap();
+        writeln!(ofx, "<FITID>{}-{}</FITID>", xml_escape(account_number), index + 1).unwrap();
+        writeln!(ofx, "<NAME>{}</NAME>", xml_escape(&transaction_name(transaction))).unwrap();
+        if let Some(counterparty) = &transaction.counterparty_account_number {
+            write_ofx_account(&mut ofx, "BANKACCTTO", counterparty);
+        }
+        writeln!(ofx, "<MEMO>{}</MEMO>", transaction.kind).unwrap();
+        ofx.push_str("</STMTTRN>\n");
+    }
+    ofx.push_str("</BANKTRANLIST>\n");
+    writeln!(ofx, "<LEDGERBAL>\n<BALAMT>{:.2}</BALAMT>\n<DTASOF>{}</DTASOF>\n</LEDGERBAL>", ledger_balance, ofx_date(end)).unwrap();
+    ofx.push_str("</STMTRS>\n</STMTTRNRS>\n</BANKMSGSRSV1>\n</OFX>\n");
+    ofx
+}
+
+/// Writes a QIF bank account. The payee of a transfer is the counterparty account
+/// and the memo keeps the transaction kind, so a wallet can read its own files back.
+pub fn to_qif(account_number: &str, transactions: &[Transaction]) -> String {
+    let mut qif = String::new();
+    writeln!(qif, "!Account\nN{}\nTBank\n^", account_number).unwrap();
+    qif.push_str("!Type:Bank\n");
+    for transaction in transactions.iter() {
+        let (year, month, day, _, _, _) = date_time_from_timestamp(transaction.timestamp);
+        writeln!(qif, "D{:02}/{:02}/{:04}", month, day, year).unwrap();
+        writeln!(qif, "T{:.2}", transaction.signed_amount()).unwrap();
+        writeln!(qif, "P{}", transaction_name(transaction)).unwrap();
+        writeln!(qif, "M{}", transaction.kind).unwrap();
+        qif.push_str("^\n");
+    }
+    qif
+}
+
+/// Reads the transactions of the first bank statement in an OFX 2.x file
+pub fn parse_ofx(ofx: &str) -> Result<ExportedActivity, String> {
+    let statement = element_block(ofx, "STMTRS").ok_or("No bank statement found in OFX file")?;
+    let account_number = element_block(statement, "BANKACCTFROM").and_then(|block| element_value(block, "ACCTID"));
+    let currency = element_value(statement, "CURDEF");
+    let ledger_balance = match element_block(statement, "LEDGERBAL").and_then(|block| element_value(block, "BALAMT")) {
+        Some(balance) => Some(parse_amount(&balance)?),
+        None => None,
+    };
+
+    let mut records = Vec::new();
+    let mut rest = statement;
+    while let Some(start) = rest.find("<STMTTRN>") {
+        let after_start = &rest[start + "<STMTTRN>".len()..];
+        let end = after_start.find("</STMTTRN>").ok_or("Unterminated STMTTRN element in OFX file")?;
+        let block = &after_start[..end];
+        rest = &after_start[end..];
+
+        let transaction_type = element_value(block, "TRNTYPE").ok_or("Transaction without TRNTYPE in OFX file")?;
+        let posted = element_value(block, "DTPOSTED").ok_or("Transaction without DTPOSTED in OFX file")?;
+        let amount = parse_amount(&element_value(block, "TRNAMT").ok_or("Transaction without TRNAMT in OFX file")?)?;
+        let counterparty = element_block(block, "BANKACCTTO").and_then(|account| element_value(account, "ACCTID"));
+        let kind = match element_value(block, "MEMO").and_then(|memo| memo.parse().ok()) {
+            Some(kind) => kind,
+            None => kind_from_ofx_type(&transaction_type, amount),
+        };
+        records.push(ActivityRecord {
+            timestamp: parse_ofx_date(&posted)?,
+            kind,
+            amount: amount.abs(),
+            counterparty_account_number: counterparty,
+        });
+    }
+
+    Ok(ExportedActivity { account_number, currency, ledger_balance, records })
+}
+
+/// Reads the first bank account of a QIF file. Dates are `MM/DD/YYYY` or `MM/DD'YY`.
+pub fn parse_qif(qif: &str) -> Result<ExportedActivity, String> {
+    let mut account_number = None;
+    let mut in_account = false;
+    let mut in_bank = false;
+    let mut records = Vec::new();
+    let mut date = None;
+    let mut amount = None;
+    let mut payee: Option<String> = None;
+    let mut memo: Option<String> = None;
+
+    for (index, line) in qif.lines().enumerate() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('!') {
+            in_account = header.eq_ignore_ascii_case("Account");
+            in_bank = header.eq_ignore_ascii_case("Type:Bank");
+            continue;
+        }
+        // the code is the first character, which need not be a single byte
+        let code_length = line.chars().next().map(char::len_utf8).unwrap_or(0);
+        let (code, value) = line.split_at(code_length);
+        if in_account {
+            if code == "N" && account_number.is_none() {
+                account_number = Some(value.to_string());
+            }
+            continue;
+        }
+        if !in_bank {
+            continue;
+        }
+        match code {
+            "D" => date = Some(parse_qif_date(value).map_err(|e| format!("Line {}: {}", index + 1, e))?),
+            "T" | "U" => amount = Some(parse_amount(value).map_err(|e| format!("Line {}: {}", index + 1, e))?),
+            "P" => payee = Some(value.to_string()),
+            "M" => memo = Some(value.to_string()),
+            "^" => {
+                let (timestamp, amount) = match (date.take(), amount.take()) {
+                    (Some(timestamp), Some(amount)) => (timestamp, amount),
+                    _ => return Err(format!("Line {}: transaction without a date or amount", index + 1)),
+                };
+                let kind = match memo.take().and_then(|memo| memo.parse().ok()) {
+                    Some(kind) => kind,
+                    None if amount >= 0.0 => TransactionKind::Deposit,
+                    None => TransactionKind::Withdrawal,
+                };
+                let payee = payee.take();
+                let counterparty_account_number = match kind {
+                    TransactionKind::TransferIn | TransactionKind::TransferOut => payee,
+                    _ => None,
+                };
+                records.push(ActivityRecord { timestamp, kind, amount: amount.abs(), counterparty_account_number });
+            }
+            _ => {}
+        }
+    }
+
+    Ok(ExportedActivity { account_number, currency: None, ledger_balance: None, records })
+}
+
+fn write_ofx_account(ofx: &mut String, element: &str, account_number: &str) {
+    writeln!(
+        ofx,
+        "<{0}>\n<BANKID>{1}</BANKID>\n<ACCTID>{2}</ACCTID>\n<ACCTTYPE>CHECKING</ACCTTYPE>\n</{0}>",
+        element, OFX_BANK_ID, xml_escape(account_number)
+    ).unwrap();
+}
+
+fn ofx_transaction_type(kind: &TransactionKind) -> &'static str {
+    match kind {
+        TransactionKind::Deposit => "DEP",
+        TransactionKind::Withdrawal => "DEBIT",
+        TransactionKind::TransferIn | TransactionKind::TransferOut => "XFER",
+        TransactionKind::Fee => "FEE",
+        TransactionKind::Interest => "INT",
+    }
+}
+
+fn kind_from_ofx_type(transaction_type: &str, amount: f64) -> TransactionKind {
+    match transaction_type {
+        "FEE" | "SRVCHG" => TransactionKind::Fee,
+        "INT" | "DIV" => TransactionKind::Interest,
+        "XFER" if amount >= 0.0 => TransactionKind::TransferIn,
+        "XFER" => TransactionKind::TransferOut,
+        _ if amount >= 0.0 => TransactionKind::Deposit,
+        _ => TransactionKind::Withdrawal,
+    }
+}
+
+/// Counterparty for transfers, the kind of movement otherwise
+fn transaction_name(transaction: &Transaction) -> String {
+    match &transaction.counterparty_account_number {
+        Some(counterparty) => counterparty.clone(),
+        None => transaction.kind.to_string(),
+    }
+}
+
+fn ofx_date(timestamp: u64) -> String {
+    let (year, month, day, hour, minute, second) = date_time_from_timestamp(timestamp);
+    format!("{:04}{:02}{:02}{:02}{:02}{:02}", year, month, day, hour, minute, second)
+}
+
+/// Accepts `YYYYMMDD` and `YYYYMMDDHHMMSS`, ignoring fractional seconds and a time zone suffix
+fn parse_ofx_date(date: &str) -> Result<u64, String> {
+    let digits: String = date.chars().take_while(|c| c.is_ascii_digit()).collect();
+    let field = |range: std::ops::Range<usize>| digits[range].parse::<u32>().unwrap_or(0);
+    match digits.len() {
+        8 => timestamp_from_date_time(field(0..4) as i64, field(4..6), field(6..8), 0, 0, 0),
+        n if n >= 14 => timestamp_from_date_time(field(0..4) as i64, field(4..6), field(6..8), field(8..10), field(10..12), field(12..14)),
+        _ => Err(format!("Invalid OFX date: {}", date)),
+    }
+}
+
+fn parse_qif_date(date: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid QIF date: {}", date);
+    let normalized = date.trim().replace('\'', "/");
+    let parts: Vec<&str> = normalized.split('/').map(str::trim).collect();
+    let [month, day, year] = parts.as_slice() else {
+        return Err(invalid());
+    };
+    let month = month.parse::<u32>().map_err(|_| invalid())?;
+    let day = day.parse::<u32>().map_err(|_| invalid())?;
+    let year = match year.parse::<i64>().map_err(|_| invalid())? {
+        year if year < 100 => 2000 + year,
+        year => year,
+    };
+    timestamp_from_date_time(year, month, day, 0, 0, 0)
+}
+
+fn parse_amount(amount: &str) -> Result<f64, String> {
+    amount.trim().replace(',', "").parse::<f64>().map_err(|_| format!("Invalid amount: {}", amount))
+}
+
+/// Text between `<name>` and `</name>`
+fn element_block<'a>(text: &'a str, name: &str) -> Option<&'a str> {
+    let open = format!("<{}>", name);
+    let close = format!("</{}>", name);
+    let start = text.find(&open)? + open.len();
+    let end = text[start..].find(&close)? + start;
+    Some(&text[start..end])
+}
+
+/// Text of a leaf element, up to its closing tag or the next tag (OFX 1.x leaves are not closed)
+fn element_value(text: &str, name: &str) -> Option<String> {
+    let open = format!("<{}>", name);
+    let start = text.find(&open)? + open.len();
+    let end = text[start..].find('<').map(|end| end + start).unwrap_or(text.len());
+    Some(xml_unescape(text[start..end].trim()))
+}
+
+fn xml_escape(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn xml_unescape(value: &str) -> String {
+    value.replace("&lt;", "<").replace("&gt;", ">").replace("&amp;", "&")
+}
@@ -0,0 +1,388 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+use crate::accounts::AccountType;
+use crate::service::{AmountRequest, TransferRequest, WalletService};
+
+/// Kinds of rows an import file can hold
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImportOperation {
+    Deposit,
+    Withdrawal,
+    /// Transfer to the wallet named in the counterparty column
+    Transfer,
+}
+
+impl FromStr for ImportOperation {
+    type Err = String;
+
+    /// Parses `deposit`, `withdrawal` (or `withdraw`) and `transfer`, ignoring case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "deposit" => Ok(ImportOperation::Deposit),
+            "withdrawal" | "withdraw" => Ok(ImportOperation::Withdrawal),
+            "transfer" => Ok(ImportOperation::Transfer),
+            _ => Err(format!("Unknown transaction type: {}", s)),
+        }
+    }
+}
+
+/// Names of the CSV columns holding each field. The counterparty column may be missing
+/// from files without transfers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnMapping {
+    pub wallet_id: String,
+    pub currency: String,
+    pub amount: String,
+    pub operation: String,
+    pub counterparty: String,
+}
+
+impl Default for ColumnMapping {
+    fn default() -> Self {
+        ColumnMapping {
+            wallet_id: "wallet_id".to_string(),
+            currency: "currency".to_string(),
+            amount: "amount".to_string(),
+            operation: "type".to_string(),
+            counterparty: "counterparty".to_string(),
+        }
+    }
+}
+
+/// Validated row of an import file
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportRow {
+    /// Line of the file the row starts on, the header being line 1
+    pub line: usize,
+    pub wallet_id: String,
+    pub currency: String,
+    pub amount: f64,
+    pub operation: ImportOperation,
+    /// Recipient wallet id of a transfer
+    pub counterparty: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RowError {
+    pub line: usize,
+    /// Column at fault, when the problem is a single field
+    pub column: Option<String>,
+    pub message: String,
+}
+
+/// Outcome of an import. Rows are only applied when every row is valid.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub rows: usize,
+    pub applied: usize,
+    pub errors: Vec<RowError>,
+}
+
+impl ImportReport {
+    pub fn is_successful(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Row-level error report with the columns `line,column,error`
+    pub fn errors_to_csv(&self) -> String {
+        let mut csv = String::from("line,column,error\n");
+        for error in self.errors.iter() {
+            csv.push_str(&format!(
+                "{},{},{}\n",
+                error.line,
+                csv_field(error.column.as_deref().unwrap_or("")),
+                csv_field(&error.message)
+            ));
+        }
+        csv
+    }
+}
+
+/// Loads deposits, withdrawals and transfers from CSV into the wallets of a `WalletService`.
+///
+/// The whole file is checked before anything is applied: every row is replayed against the
+/// current balances and overdraft limits of the accounts it touches, in file order, so a row may
+/// rely on funds brought in by an earlier one. Rows use the default account of their currency,
+/// which must already exist.
+pub struct CsvImporter {
+    mapping: ColumnMapping,
+}
+
+impl CsvImporter {
+    pub fn new(mapping: ColumnMapping) -> Self {
+        CsvImporter { mapping }
+    }
+
+    pub fn get_mapping(&self) -> &ColumnMapping {
+        &self.mapping
+    }
+
+    /// Checks a file without changing any wallet
+    pub fn dry_run(&self, service: &WalletService, csv: &str) -> ImportReport {
+        let (rows, errors) = self.validate(service, csv);
+        ImportReport { dry_run: true, rows: rows.len(), applied: 0, errors }
+    }
+
+    /// Applies every row of a file, or none of them when any row is invalid. The rows are applied
+    /// to a copy of the service rebuilt from `to_stored`, which replaces the service only when every
+    /// row went through, so a wallet refusing a row leaves the service untouched.
+    pub fn import(&self, service: &mut WalletService, csv: &str) -> ImportReport {
+        let (rows, errors) = self.validate(service, csv);
+        let mut report = ImportReport { dry_run: false, rows: rows.len(), applied: 0, errors };
+        if !report.is_successful() {
+            return report;
+        }
+        let mut staged = match WalletService::from_stored(&service.to_stored()) {
+            Ok(staged) => staged,
+            Err(error) => {
+                report.errors.push(RowError { line: 1, column: None, message: format!("Failed to stage the import: {}", error.message) });
+                return report;
+            }
+        };
+        for row in rows.iter() {
+            let result = match row.operation {
+                ImportOperation::Deposit => staged.deposit(&row.wallet_id, &amount_request(row)).map(|_| ()),
+                ImportOperation::Withdrawal => staged.withdraw(&row.wallet_id, &amount_request(row)).map(|_| ()),
+                ImportOperation::Transfer => staged.transfer(&row.wallet_id, &TransferRequest {
+                    to_wallet_id: row.counterparty.clone().unwrap_or_default(),
+                    currency: row.currency.clone(),
+                    amount: row.amount,
+                }).map(|_| ()),
+            };
+            // validation replays the same rules, so this only happens if a wallet refuses for its own reasons
+            if let Err(error) = result {
+                report.errors.push(RowError { line: row.line, column: None, message: error.message });
+                return report;
+            }
+        }
+        *service = staged;
+        report.applied = rows.len();
+        report
+    }
+
+    /// Parses and checks every row, returning the valid rows and the errors of the others
+    pub fn validate(&self, service: &WalletService, csv: &str) -> (Vec<ImportRow>, Vec<RowError>) {
+        let mut records = parse_csv(csv).into_iter();
+        let mut errors = Vec::new();
+        let header = match records.next() {
+            Some((_, Ok(header))) => header,
+            Some((line, Err(message))) => return (Vec::new(), vec![RowError { line, column: None, message }]),
+            None => return (Vec::new(), vec![RowError { line: 1, column: None, message: "The file is empty".to_string() }]),
+        };
+
+        let position = |name: &str| header.iter().position(|column| column.trim() == name);
+        let mut required = Vec::new();
+        for name in [&self.mapping.wallet_id, &self.mapping.currency, &self.mapping.amount, &self.mapping.operation] {
+            match position(name) {
+                Some(index) => required.push(index),
+                None => errors.push(RowError { line: 1, column: Some(name.clone()), message: format!("Missing column: {}", name) }),
+            }
+        }
+        if !errors.is_empty() {
+            return (Vec::new(), errors);
+        }
+        let counterparty_index = position(&self.mapping.counterparty);
+
+        let mut ledger = Ledger::default();
+        let mut rows = Vec::new();
+        for (line, record) in records {
+            let fields = match record {
+                Ok(fields) => fields,
+                Err(message) => {
+                    errors.push(RowError { line, column: None, message });
+                    continue;
+                }
+            };
+            if fields.iter().all(|field| field.trim().is_empty()) {
+                continue;
+            }
+            let field = |index: usize| fields.get(index).map(|f| f.trim()).unwrap_or("");
+            let row_errors_before = errors.len();
+            let mut fail = |column: &str, message: String| errors.push(RowError { line, column: Some(column.to_string()), message });
+
+            let wallet_id = field(required[0]).to_string();
+            let currency = field(required[1]).to_string();
+            if wallet_id.is_empty() {
+                fail(&self.mapping.wallet_id, "Wallet id is empty".to_string());
+            }
+            if currency.is_empty() {
+                fail(&self.mapping.currency, "Currency is empty".to_string());
+            }
+            let amount = match field(required[2]).parse::<f64>() {
+                Ok(amount) if amount.is_finite() && amount > 0.0 => amount,
+                Ok(_) => {
+                    fail(&self.mapping.amount, format!("Amount must be a positive number: {}", field(required[2])));
+                    0.0
+                }
+                Err(_) => {
+                    fail(&self.mapping.amount, format!("Invalid amount: {}", field(required[2])));
+                    0.0
+                }
+            };
+            let operation = match field(required[3]).parse::<ImportOperation>() {
+                Ok(operation) => Some(operation),
+                Err(message) => {
+                    fail(&self.mapping.operation, message);
+                    None
+                }
+            };
+            let counterparty = counterparty_index.map(field).filter(|c| !c.is_empty()).map(|c| c.to_string());
+            match (operation, &counterparty) {
+                (Some(ImportOperation::Transfer), None) => {
+                    fail(&self.mapping.counterparty, "A transfer needs a counterparty wallet id".to_string())
+                }
+                (Some(ImportOperation::Deposit | ImportOperation::Withdrawal), Some(_)) => {
+                    fail(&self.mapping.counterparty, "Only transfers have a counterparty".to_string())
+                }
+                _ => {}
+            }
+            if errors.len() > row_errors_before {
+                continue;
+            }
+
+            let row = ImportRow { line, wallet_id, currency, amount, operation: operation.unwrap(), counterparty };
+            match ledger.apply(service, &row) {
+                Ok(()) => rows.push(row),
+                Err(message) => errors.push(RowError { line, column: None, message }),
+            }
+        }
+        (rows, errors)
+    }
+}
+
+impl Default for CsvImporter {
+    fn default() -> Self {
+        Self::new(ColumnMapping::default())
+    }
+}
+
+struct LedgerAccount {
+    account_number: String,
+    account_type: AccountType,
+    overdraft_limit: f64,
+}
+
+/// Balances of the accounts touched so far by a validation run
+#[derive(Default)]
+struct Ledger {
+    balances: HashMap<String, f64>,
+}
+
+impl Ledger {
+    fn apply(&mut self, service: &WalletService, row: &ImportRow) -> Result<(), String> {
+        let account = Self::default_account(service, &row.wallet_id, &row.currency)?;
+        match row.operation {
+            ImportOperation::Deposit => {
+                self.credit(service, &row.wallet_id, &account.account_number, row.amount);
+                Ok(())
+            }
+            ImportOperation::Withdrawal => self.debit(service, &row.wallet_id, &account, row.amount),
+            ImportOperation::Transfer => {
+                let to_wallet_id = row.counterparty.as_deref().unwrap_or_default();
+                if to_wallet_id == row.wallet_id {
+                    return Err("Cannot transfer between a wallet and itself".to_string());
+                }
+                let recipient = Self::default_account(service, to_wallet_id, &row.currency)?;
+                self.debit(service, &row.wallet_id, &account, row.amount)?;
+                self.credit(service, to_wallet_id, &recipient.account_number, row.amount);
+                Ok(())
+            }
+        }
+    }
+
+    fn default_account(service: &WalletService, wallet_id: &str, currency: &str) -> Result<LedgerAccount, String> {
+        let wallet = service.wallet(wallet_id).ok_or(format!("No wallet found with id: {}", wallet_id))?;
+        let account = wallet.get_account_by_currency(currency)
+            .ok_or(format!("No account found with currency: {} in wallet {}", currency, wallet_id))?;
+        Ok(LedgerAccount {
+            account_number: account.get_account_number().to_string(),
+            account_type: account.get_account_type(),
+            overdraft_limit: account.get_overdraft_limit(),
+        })
+    }
+
+    fn balance(&mut self, service: &WalletService, wallet_id: &str, account_number: &str) -> &mut f64 {
+        self.balances.entry(account_number.to_string()).or_insert_with(|| {
+            service.wallet(wallet_id)
+                .and_then(|wallet| wallet.get_account_by_number(account_number).ok())
+                .map(|account| account.get_balance())
+                .unwrap_or(0.0)
+        })
+    }
+
+    fn credit(&mut self, service: &WalletService, wallet_id: &str, account_number: &str, amount: f64) {
+        *self.balance(service, wallet_id, account_number) += amount;
+    }
+
+    fn debit(&mut self, service: &WalletService, wallet_id: &str, account: &LedgerAccount, amount: f64) -> Result<(), String> {
+        let balance = self.balance(service, wallet_id, &account.account_number);
+        if *balance + account.overdraft_limit < amount {
+            return Err(match account.account_type {
+                AccountType::Premium => "Overdraft limit exceeded".to_string(),
+                AccountType::Basic => "Insufficient funds".to_string(),
+            });
+        }
+        *balance -= amount;
+        Ok(())
+    }
+}
+
+fn amount_request(row: &ImportRow) -> AmountRequest {
+    AmountRequest {
+        currency: row.currency.clone(),
+        amount: row.amount,
+        account_number: None,
+    }
+}
+
+/// Splits CSV text into records with the line each one starts on. Quoted fields may hold
+/// commas, doubled quotes and line breaks.
+pub(crate) fn parse_csv(csv: &str) -> Vec<(usize, Result<Vec<String>, String>)> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = csv.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => fields.push(std::mem::take(&mut field)),
+            ('\r', false) if chars.peek() == Some(&'\n') => {}
+            ('\n', false) => {
+                fields.push(std::mem::take(&mut field));
+                records.push((record_line, Ok(std::mem::take(&mut fields))));
+                line += 1;
+                record_line = line;
+            }
+            ('\n', true) => {
+                line += 1;
+                field.push(c);
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        records.push((record_line, Err("Unterminated quoted field".to_string())));
+    } else if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((record_line, Ok(fields)));
+    }
+    records
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -1,6 +1,29 @@
 pub mod accounts;
+pub mod account_number;
+pub mod transactions;
 pub mod wallets;
 pub mod basic_wallet;
 pub mod basic_account;
 pub mod premium_account;
-pub mod multi_currency_wallet;
\ No newline at end of file
+pub mod multi_currency_wallet;
+pub mod exchange;
+pub mod valuation;
+pub mod customer;
+pub mod auth;
+pub mod approvals;
+pub mod events;
+pub mod alerts;
+pub mod audit;
+pub mod service;
+pub mod server;
+pub mod rpc;
+pub mod statements;
+pub mod export;
+pub mod import;
+pub mod reconciliation;
+pub mod monitoring;
+pub mod sanctions;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+pub mod simulation;
+pub mod rendering;
@@ -1,6 +1,15 @@
-use clap::{Arg, App, SubCommand};
-use wallet_system::basic_wallet::BasicWallet;
-use wallet_system::multi_currency_wallet::MultiCurrencyWallet;
+use std::fs;
+use std::io;
+use std::process;
+use std::str::FromStr;
+use clap::{Arg, App, ArgMatches, SubCommand};
+use wallet_system::audit;
+use wallet_system::export::{self, ExportFormat};
+use wallet_system::rendering::{Render, RenderFormat};
+use wallet_system::rpc;
+use wallet_system::service::{AddAccountRequest, AmountRequest, CreateWalletRequest, ServiceError, StoredAccount, StoredWallet, TransferRequest, WalletService};
+use wallet_system::simulation::{self, AmountDistribution, SimulatedOperation, Simulation, SimulationConfig};
+use wallet_system::transactions;
 
 fn main() {
     let matches = App::new("Wallet CLI")
@@ -13,7 +22,28 @@ fn main() {
                 .arg(Arg::with_name("type")
                     .help("The type of wallet to create (basic or multi)")
                     .required(true)
-                    .index(1)),
+                    .index(1))
+                .arg(Arg::with_name("currency")
+                    .long("currency")
+                    .takes_value(true)
+                    .help("The currency of the account of a basic wallet"))
+                .arg(Arg::with_name("account_type")
+                    .long("account-type")
+                    .takes_value(true)
+                    .help("The type of the account of a basic wallet (basic or premium)"))
+                .arg(Arg::with_name("overdraft")
+                    .long("overdraft")
+                    .takes_value(true)
+                    .help("The overdraft limit for premium accounts"))
+                .arg(Arg::with_name("format")
+                    .long("format")
+                    .takes_value(true)
+                    .default_value("text")
+                    .help("The output format (text, json or markdown)"))
+                .arg(Arg::with_name("state")
+                    .long("state")
+                    .takes_value(true)
+                    .help("The JSON file to add the wallet to; created when missing")),
         )
         .subcommand(
             SubCommand::with_name("create_account")
@@ -33,7 +63,12 @@ fn main() {
                 .arg(Arg::with_name("overdraft")
                     .help("The overdraft limit for premium accounts")
                     .required(false)
-                    .index(4)),
+                    .index(4))
+                .arg(Arg::with_name("state")
+                    .long("state")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The JSON file holding the wallets")),
         )
         .subcommand(
             SubCommand::with_name("deposit")
@@ -49,7 +84,12 @@ fn main() {
                 .arg(Arg::with_name("currency")
                     .help("The currency of the account")
                     .required(true)
-                    .index(3)),
+                    .index(3))
+                .arg(Arg::with_name("state")
+                    .long("state")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The JSON file holding the wallets")),
         )
         .subcommand(
             SubCommand::with_name("withdraw")
@@ -65,7 +105,12 @@ fn main() {
                 .arg(Arg::with_name("currency")
                     .help("The currency of the account")
                     .required(true)
-                    .index(3)),
+                    .index(3))
+                .arg(Arg::with_name("state")
+                    .long("state")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The JSON file holding the wallets")),
         )
         .subcommand(
             SubCommand::with_name("transfer")
@@ -85,7 +130,395 @@ fn main() {
                 .arg(Arg::with_name("currency")
                     .help("The currency of the accounts")
                     .required(true)
-                    .index(4)),
+                    .index(4))
+                .arg(Arg::with_name("state")
+                    .long("state")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The JSON file holding the wallets")),
+        )
+        .subcommand(
+            SubCommand::with_name("balance")
+                .about("Shows the balance of a wallet in a currency")
+                .arg(Arg::with_name("wallet_id")
+                    .help("The ID of the wallet")
+                    .required(true)
+                    .index(1))
+                .arg(Arg::with_name("currency")
+                    .help("The currency of the accounts")
+                    .required(true)
+                    .index(2))
+                .arg(Arg::with_name("state")
+                    .long("state")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The JSON file holding the wallets")),
+        )
+        .subcommand(
+            SubCommand::with_name("serve")
+                .about("Runs the wallet engine as a long-lived JSON-RPC 2.0 server")
+                .arg(Arg::with_name("stdio")
+                    .long("stdio")
+                    .help("Reads one request per line from stdin and writes one response per line to stdout")),
+        )
+        .subcommand(
+            SubCommand::with_name("verify_audit_log")
+                .about("Verifies that an audit log file was not modified")
+                .arg(Arg::with_name("path")
+                    .help("The path of the audit log file")
+                    .required(true)
+                    .index(1))
+                .arg(Arg::with_name("head")
+                    .long("head")
+                    .takes_value(true)
+                    .help("The expected hash of the last record, to detect records removed from the end")),
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("Exports account transactions for personal finance tools")
+                .arg(Arg::with_name("wallet_id")
+                    .help("The ID of the wallet")
+                    .required(true)
+                    .index(1))
+                .arg(Arg::with_name("account")
+                    .long("account")
+                    .takes_value(true)
+                    .help("The account number; may be omitted when the wallet has a single account"))
+                .arg(Arg::with_name("state")
+                    .long("state")
+                    .takes_value(true)
+                    .required(true)
+                    .help("The JSON file holding the wallets"))
+                .arg(Arg::with_name("format")
+                    .long("format")
+                    .takes_value(true)
+                    .default_value("ofx")
+                    .help("The file format (ofx or qif)"))
+                .arg(Arg::with_name("output")
+                    .long("output")
+                    .takes_value(true)
+                    .help("The file to write; standard output when omitted")),
+        )
+        .subcommand(
+            SubCommand::with_name("simulate")
+                .about("Simulates a seeded workload of customers and prints throughput, failure and balance statistics")
+                .arg(Arg::with_name("seed")
+                    .long("seed")
+                    .takes_value(true)
+                    .default_value("0")
+                    .help("The seed of the random number generator"))
+                .arg(Arg::with_name("customers")
+                    .long("customers")
+                    .takes_value(true)
+                    .default_value("100")
+                    .help("The number of customers"))
+                .arg(Arg::with_name("days")
+                    .long("days")
+                    .takes_value(true)
+                    .default_value("30")
+                    .help("The number of simulated days"))
+                .arg(Arg::with_name("rate")
+                    .long("rate")
+                    .takes_value(true)
+                    .default_value("2")
+                    .help("The mean number of operations per customer per day"))
+                .arg(Arg::with_name("mix")
+                    .long("mix")
+                    .takes_value(true)
+                    .default_value("0.4,0.3,0.3")
+                    .help("The relative weights of deposits, withdrawals and transfers"))
+                .arg(Arg::with_name("amounts")
+                    .long("amounts")
+                    .takes_value(true)
+                    .default_value("lognormal:50:1")
+                    .help("The amount distribution (uniform:MIN:MAX or lognormal:MEDIAN:SIGMA)"))
+                .arg(Arg::with_name("currencies")
+                    .long("currencies")
+                    .takes_value(true)
+                    .default_value("USD:0.6,EUR:0.3,GBP:0.1")
+                    .help("The currencies and their relative weights"))
+                .arg(Arg::with_name("multi_currency_share")
+                    .long("multi-currency-share")
+                    .takes_value(true)
+                    .default_value("0.3")
+                    .help("The share of customers with a multi-currency wallet"))
+                .arg(Arg::with_name("premium_share")
+                    .long("premium-share")
+                    .takes_value(true)
+                    .default_value("0.2")
+                    .help("The share of premium accounts"))
+                .arg(Arg::with_name("overdraft")
+                    .long("overdraft")
+                    .takes_value(true)
+                    .default_value("500")
+                    .help("The overdraft limit of premium accounts"))
+                .arg(Arg::with_name("json")
+                    .long("json")
+                    .help("Prints the report as JSON")),
         )
         .get_matches();
+
+    if let Some(matches) = matches.subcommand_matches("create_wallet") {
+        let format: RenderFormat = match matches.value_of("format").unwrap().parse() {
+            Ok(format) => format,
+            Err(error) => {
+                eprintln!("{}", error);
+                process::exit(2);
+            }
+        };
+        let overdraft_limit = match matches.value_of("overdraft").map(|overdraft| overdraft.parse::<f64>()).transpose() {
+            Ok(overdraft_limit) => overdraft_limit,
+            Err(_) => {
+                eprintln!("Invalid value for --overdraft: {}", matches.value_of("overdraft").unwrap());
+                process::exit(2);
+            }
+        };
+        let request = CreateWalletRequest {
+            wallet_type: matches.value_of("type").unwrap().to_string(),
+            currency: matches.value_of("currency").map(|currency| currency.to_string()),
+            account_type: matches.value_of("account_type").map(|account_type| account_type.to_string()),
+            overdraft_limit,
+        };
+        let mut service = match matches.value_of("state") {
+            Some(state) => load_state(state),
+            None => WalletService::new(),
+        };
+        let wallet = service.create_wallet(&request).unwrap_or_else(|error| fail(error));
+        if let Some(state) = matches.value_of("state") {
+            save_state(&service, state);
+        }
+        print!("{}", wallet.render(format));
+    }
+
+    if let Some(matches) = matches.subcommand_matches("create_account") {
+        let state = matches.value_of("state").unwrap();
+        let mut service = load_state(state);
+        let request = AddAccountRequest {
+            account_type: matches.value_of("type").unwrap().to_string(),
+            currency: matches.value_of("currency").unwrap().to_string(),
+            overdraft_limit: matches.value_of("overdraft").map(|_| amount(matches, "overdraft")),
+        };
+        let account = service.add_account(matches.value_of("wallet_id").unwrap(), &request).unwrap_or_else(|error| fail(error));
+        save_state(&service, state);
+        print!("{}", account.render(RenderFormat::Text));
+    }
+
+    for subcommand in ["deposit", "withdraw"] {
+        if let Some(matches) = matches.subcommand_matches(subcommand) {
+            let state = matches.value_of("state").unwrap();
+            let mut service = load_state(state);
+            let wallet_id = matches.value_of("wallet_id").unwrap();
+            let request = AmountRequest {
+                currency: matches.value_of("currency").unwrap().to_string(),
+                amount: amount(matches, "amount"),
+                account_number: None,
+            };
+            let account = match subcommand {
+                "deposit" => service.deposit(wallet_id, &request),
+                _ => service.withdraw(wallet_id, &request),
+            }.unwrap_or_else(|error| fail(error));
+            save_state(&service, state);
+            print!("{}", account.render(RenderFormat::Text));
+        }
+    }
+
+    if let Some(matches) = matches.subcommand_matches("transfer") {
+        let state = matches.value_of("state").unwrap();
+        let mut service = load_state(state);
+        let request = TransferRequest {
+            to_wallet_id: matches.value_of("to_wallet_id").unwrap().to_string(),
+            currency: matches.value_of("currency").unwrap().to_string(),
+            amount: amount(matches, "amount"),
+        };
+        let transfer = service.transfer(matches.value_of("from_wallet_id").unwrap(), &request).unwrap_or_else(|error| fail(error));
+        save_state(&service, state);
+        println!(
+            "Transferred {} {} from {} to {}",
+            transfer.amount, transfer.currency, transfer.sender_account_number, transfer.recipient_account_number
+        );
+    }
+
+    if let Some(matches) = matches.subcommand_matches("balance") {
+        let service = load_state(matches.value_of("state").unwrap());
+        let balance = service
+            .balance(matches.value_of("wallet_id").unwrap(), matches.value_of("currency").unwrap())
+            .unwrap_or_else(|error| fail(error));
+        println!("{:.2} {}", balance.balance, balance.currency);
+    }
+
+    if let Some(matches) = matches.subcommand_matches("serve") {
+        if !matches.is_present("stdio") {
+            eprintln!("Only --stdio is supported");
+            process::exit(2);
+        }
+        let stdin = io::stdin();
+        let stdout = io::stdout();
+        if let Err(error) = rpc::serve(&mut WalletService::new(), stdin.lock(), stdout.lock()) {
+            eprintln!("JSON-RPC server stopped: {}", error);
+            process::exit(1);
+        }
+    }
+
+    if let Some(matches) = matches.subcommand_matches("verify_audit_log") {
+        let path = matches.value_of("path").unwrap();
+        let result = match audit::read_log_file(path) {
+            Ok(records) => match matches.value_of("head") {
+                Some(head) => audit::verify_records_against(&records, head),
+                None => audit::verify_records(&records),
+            },
+            Err(error) => Err(error),
+        };
+        match result {
+            Ok(verification) => {
+                println!("Audit log {} is intact: {} records, head {}", path, verification.records, verification.head_hash);
+            }
+            Err(error) => {
+                eprintln!("Audit log {} failed verification: {}", path, error);
+                process::exit(1);
+            }
+        }
+    }
+
+    if let Some(matches) = matches.subcommand_matches("export") {
+        let format: ExportFormat = match matches.value_of("format").unwrap().parse() {
+            Ok(format) => format,
+            Err(error) => {
+                eprintln!("{}", error);
+                process::exit(2);
+            }
+        };
+        let wallets = load_state(matches.value_of("state").unwrap()).to_stored();
+        let account = match stored_account(&wallets, matches.value_of("wallet_id").unwrap(), matches.value_of("account")) {
+            Ok(account) => account,
+            Err(error) => {
+                eprintln!("{}", error);
+                process::exit(1);
+            }
+        };
+        let exported = export::export_transactions(
+            format,
+            &account.account_number,
+            &account.currency,
+            &account.history,
+            transactions::current_timestamp(),
+        );
+        match matches.value_of("output") {
+            Some(output) => {
+                if let Err(error) = fs::write(output, exported) {
+                    eprintln!("Failed to write {}: {}", output, error);
+                    process::exit(1);
+                }
+            }
+            None => print!("{}", exported),
+        }
+    }
+
+    if let Some(matches) = matches.subcommand_matches("simulate") {
+        let config = match simulation_config(matches) {
+            Ok(config) => config,
+            Err(error) => {
+                eprintln!("{}", error);
+                process::exit(2);
+            }
+        };
+        let report = match Simulation::new(config) {
+            Ok(mut simulation) => simulation.run(),
+            Err(error) => {
+                eprintln!("{}", error);
+                process::exit(2);
+            }
+        };
+        if matches.is_present("json") {
+            match report.to_json() {
+                Ok(json) => println!("{}", json),
+                Err(error) => {
+                    eprintln!("{}", error);
+                    process::exit(1);
+                }
+            }
+        } else {
+            print!("{}", report.to_text());
+        }
+    }
+}
+
+/// Loads the wallets of the CLI state file, exiting when it cannot be read
+fn load_state(path: &str) -> WalletService {
+    WalletService::load_state(path).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        process::exit(1);
+    })
+}
+
+fn save_state(service: &WalletService, path: &str) {
+    if let Err(error) = service.save_state(path) {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+}
+
+/// Finds an account of a stored wallet, or its only account when no account number is given
+fn stored_account<'a>(wallets: &'a [StoredWallet], wallet_id: &str, account_number: Option<&str>) -> Result<&'a StoredAccount, String> {
+    let wallet = wallets.iter()
+        .find(|wallet| wallet.wallet_id == wallet_id)
+        .ok_or(format!("No wallet found with id: {}", wallet_id))?;
+    match account_number {
+        Some(account_number) => wallet.accounts.iter()
+            .find(|account| account.account_number == account_number)
+            .ok_or(format!("Account {} not found in wallet {}", account_number, wallet_id)),
+        None => match wallet.accounts.as_slice() {
+            [account] => Ok(account),
+            _ => Err(format!("Wallet {} has {} accounts; choose one with --account", wallet_id, wallet.accounts.len())),
+        },
+    }
+}
+
+/// Reports a refused request and exits with status 1
+fn fail(error: ServiceError) -> ! {
+    eprintln!("{}", error);
+    process::exit(1);
+}
+
+/// Parses a numeric argument, exiting with status 2 when it is not a number
+fn amount(matches: &ArgMatches, name: &str) -> f64 {
+    let value = matches.value_of(name).unwrap();
+    value.parse().unwrap_or_else(|_| {
+        eprintln!("Invalid value for {}: {}", name, value);
+        process::exit(2);
+    })
+}
+
+fn simulation_config(matches: &ArgMatches) -> Result<SimulationConfig, String> {
+    fn number<T: FromStr>(matches: &ArgMatches, name: &str) -> Result<T, String> {
+        let value = matches.value_of(name).unwrap();
+        value.parse().map_err(|_| format!("Invalid value for --{}: {}", name.replace('_', "-"), value))
+    }
+
+    let weights: Vec<f64> = matches.value_of("mix").unwrap()
+        .split(',')
+        .map(|weight| weight.trim().parse::<f64>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| "Invalid value for --mix".to_string())?;
+    if weights.len() != 3 {
+        return Err("--mix needs three weights: deposits, withdrawals and transfers".to_string());
+    }
+    let config = SimulationConfig {
+        seed: number(matches, "seed")?,
+        customers: number(matches, "customers")?,
+        days: number(matches, "days")?,
+        operations_per_customer_per_day: number(matches, "rate")?,
+        operation_mix: vec![
+            (SimulatedOperation::Deposit, weights[0]),
+            (SimulatedOperation::Withdrawal, weights[1]),
+            (SimulatedOperation::Transfer, weights[2]),
+        ],
+        amounts: matches.value_of("amounts").unwrap().parse::<AmountDistribution>()?,
+        currency_mix: simulation::parse_currency_mix(matches.value_of("currencies").unwrap())?,
+        multi_currency_share: number(matches, "multi_currency_share")?,
+        premium_share: number(matches, "premium_share")?,
+        overdraft_limit: number(matches, "overdraft")?,
+        ..SimulationConfig::default()
+    };
+    config.validate()?;
+    Ok(config)
 }
@@ -0,0 +1,578 @@
+use std::collections::HashSet;
+use rand::distr::Alphanumeric;
+use rand::Rng;
+use crate::accounts::{Account, AccountResponse, AccountType};
+use crate::events::EventBus;
+use crate::rendering::WalletSummary;
+use crate::transactions::{current_timestamp, TransactionKind};
+use crate::wallets::{DisplayWallet, TransferResponse, Wallet, WalletType, WithdrawWalletResponse};
+
+/// Operation of a monitored wallet, as seen by the rules
+#[derive(Clone, Debug, PartialEq)]
+pub struct MonitoredOperation {
+    pub wallet_id: String,
+    /// `Deposit`, `Withdrawal`, `TransferOut` or `TransferIn`
+    pub kind: TransactionKind,
+    pub currency: String,
+    pub amount: f64,
+    /// Recipient wallet of an outgoing transfer
+    pub counterparty_wallet_id: Option<String>,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+impl MonitoredOperation {
+    pub fn is_outgoing(&self) -> bool {
+        matches!(self.kind, TransactionKind::Withdrawal | TransactionKind::TransferOut)
+    }
+
+    /// True when the operation happened less than `window_seconds` before `now`
+    pub fn is_within(&self, now: u64, window_seconds: u64) -> bool {
+        now.saturating_sub(self.timestamp) < window_seconds
+    }
+}
+
+/// Why a rule fired: a description and the earlier operations that contributed
+#[derive(Clone, Debug, PartialEq)]
+pub struct Evidence {
+    pub description: String,
+    pub related_operations: Vec<MonitoredOperation>,
+}
+
+/// Check run on every operation before it is made. `history` holds the wallet's completed
+/// operations, oldest first.
+pub trait MonitoringRule {
+    fn name(&self) -> &str;
+    fn evaluate(&self, operation: &MonitoredOperation, history: &[MonitoredOperation]) -> Option<Evidence>;
+}
+
+/// Enum defining what happens when a rule fires
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RuleAction {
+    /// Raise an alert and let the operation through
+    Alert,
+    /// Raise an alert and refuse the operation
+    Block,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AmlAlert {
+    pub alert_id: String,
+    pub rule_name: String,
+    pub operation: MonitoredOperation,
+    pub evidence: Evidence,
+    pub blocked: bool,
+}
+
+/// Any single operation of at least `threshold` in `currency`
+pub struct LargeTransactionRule {
+    currency: String,
+    threshold: f64,
+}
+
+impl LargeTransactionRule {
+    pub fn new(currency: &str, threshold: f64) -> Result<Self, String> {
+        check_positive("Large transaction threshold", threshold)?;
+        Ok(LargeTransactionRule { currency: currency.to_string(), threshold })
+    }
+}
+
+impl MonitoringRule for LargeTransactionRule {
+    fn name(&self) -> &str {
+        "LargeTransaction"
+    }
+
+    fn evaluate(&self, operation: &MonitoredOperation, _history: &[MonitoredOperation]) -> Option<Evidence> {
+        if operation.currency != self.currency || operation.amount < self.threshold {
+            return None;
+        }
+        Some(Evidence {
+            description: format!("{} of {} {} reaches the threshold of {}", operation.kind, operation.amount, operation.currency, self.threshold),
+            related_operations: Vec::new(),
+        })
+    }
+}
+
+/// Several operations just under a reporting threshold in a short time: at least `min_operations`
+/// amounts within `margin_percent` below `threshold` during `window_seconds`
+pub struct StructuringRule {
+    currency: String,
+    threshold: f64,
+    margin_percent: f64,
+    min_operations: usize,
+    window_seconds: u64,
+}
+
+impl StructuringRule {
+    pub fn new(currency: &str, threshold: f64, margin_percent: f64, min_operations: usize, window_seconds: u64) -> Result<Self, String> {
+        check_positive("Structuring threshold", threshold)?;
+        if !(margin_percent > 0.0 && margin_percent < 100.0) {
+            return Err("Structuring margin must be between 0 and 100 percent".to_string());
+        }
+        if min_operations < 2 {
+            return Err("Structuring needs at least 2 operations".to_string());
+        }
+        Ok(StructuringRule { currency: currency.to_string(), threshold, margin_percent, min_operations, window_seconds })
+    }
+
+    fn is_just_under(&self, operation: &MonitoredOperation) -> bool {
+        operation.currency == self.currency
+            && operation.amount < self.threshold
+            && operation.amount >= self.threshold * (1.0 - self.margin_percent / 100.0)
+    }
+}
+
+impl MonitoringRule for StructuringRule {
+    fn name(&self) -> &str {
+        "Structuring"
+    }
+
+    fn evaluate(&self, operation: &MonitoredOperation, history: &[MonitoredOperation]) -> Option<Evidence> {
+        if !self.is_just_under(operation) {
+            return None;
+        }
+        let related: Vec<MonitoredOperation> = history.iter()
+            .filter(|previous| previous.is_within(operation.timestamp, self.window_seconds) && self.is_just_under(previous))
+            .cloned()
+            .collect();
+        if related.len() + 1 < self.min_operations {
+            return None;
+        }
+        Some(Evidence {
+            description: format!(
+                "{} operations between {} and {} {} within {} seconds",
+                related.len() + 1,
+                self.threshold * (1.0 - self.margin_percent / 100.0),
+                self.threshold,
+                self.currency,
+                self.window_seconds
+            ),
+            related_operations: related,
+        })
+    }
+}
+
+/// Money moved out soon after it came in: outgoing operations within `window_seconds` add up to
+/// at least `percent` of the deposits made in the same window
+pub struct RapidMovementRule {
+    percent: f64,
+    window_seconds: u64,
+}
+
+impl RapidMovementRule {
+    pub fn new(percent: f64, window_seconds: u64) -> Result<Self, String> {
+        check_positive("Rapid movement percentage", percent)?;
+        Ok(RapidMovementRule { percent, window_seconds })
+    }
+}
+
+impl MonitoringRule for RapidMovementRule {
+    fn name(&self) -> &str {
+        "RapidMovement"
+    }
+
+    fn evaluate(&self, operation: &MonitoredOperation, history: &[MonitoredOperation]) -> Option<Evidence> {
+        if !operation.is_outgoing() {
+            return None;
+        }
+        let related: Vec<MonitoredOperation> = history.iter()
+            .filter(|previous| previous.currency == operation.currency && previous.is_within(operation.timestamp, self.window_seconds))
+            .cloned()
+            .collect();
+        let incoming: f64 = related.iter().filter(|o| !o.is_outgoing()).map(|o| o.amount).sum();
+        let outgoing: f64 = related.iter().filter(|o| o.is_outgoing()).map(|o| o.amount).sum::<f64>() + operation.amount;
+        if incoming <= 0.0 || outgoing < incoming * self.percent / 100.0 {
+            return None;
+        }
+        Some(Evidence {
+            description: format!(
+                "{} {} out against {} {} in within {} seconds",
+                outgoing, operation.currency, incoming, operation.currency, self.window_seconds
+            ),
+            related_operations: related,
+        })
+    }
+}
+
+/// Transfers to more than `max_new_counterparties` wallets never paid before, within `window_seconds`
+pub struct NewCounterpartiesRule {
+    max_new_counterparties: usize,
+    window_seconds: u64,
+}
+
+impl NewCounterpartiesRule {
+    pub fn new(max_new_counterparties: usize, window_seconds: u64) -> Self {
+        NewCounterpartiesRule { max_new_counterparties, window_seconds }
+    }
+}
+
+impl MonitoringRule for NewCounterpartiesRule {
+    fn name(&self) -> &str {
+        "NewCounterparties"
+    }
+
+    fn evaluate(&self, operation: &MonitoredOperation, history: &[MonitoredOperation]) -> Option<Evidence> {
+        let counterparty = operation.counterparty_wallet_id.as_deref()?;
+        let mut seen = HashSet::new();
+        let mut related = Vec::new();
+        for previous in history.iter() {
+            if let Some(previous_counterparty) = previous.counterparty_wallet_id.as_deref() {
+                // first payment to a counterparty inside the window makes it a new one
+                if seen.insert(previous_counterparty) && previous.is_within(operation.timestamp, self.window_seconds) {
+                    related.push(previous.clone());
+                }
+            }
+        }
+        if seen.contains(counterparty) || related.len() < self.max_new_counterparties {
+            return None;
+        }
+        Some(Evidence {
+            description: format!("{} new counterparties within {} seconds", related.len() + 1, self.window_seconds),
+            related_operations: related,
+        })
+    }
+}
+
+type RuleCheck = Box<dyn Fn(&MonitoredOperation, &[MonitoredOperation]) -> Option<Evidence>>;
+
+/// Rule defined by a closure
+pub struct CustomRule {
+    name: String,
+    check: RuleCheck,
+}
+
+impl CustomRule {
+    pub fn new<F>(name: &str, check: F) -> Self
+    where
+        F: Fn(&MonitoredOperation, &[MonitoredOperation]) -> Option<Evidence> + 'static,
+    {
+        CustomRule { name: name.to_string(), check: Box::new(check) }
+    }
+}
+
+impl MonitoringRule for CustomRule {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn evaluate(&self, operation: &MonitoredOperation, history: &[MonitoredOperation]) -> Option<Evidence> {
+        (self.check)(operation, history)
+    }
+}
+
+/// Wallet whose deposits, withdrawals and incoming and outgoing transfers are screened by
+/// anti-money-laundering rules. Every rule that fires raises an alert; an operation is refused when
+/// any of them blocks. The wrapped wallet is only reachable through the screened `Wallet` calls.
+pub struct MonitoredWallet {
+    wallet: Box<dyn Wallet>,
+    rules: Vec<(Box<dyn MonitoringRule>, RuleAction)>,
+    history: Vec<MonitoredOperation>,
+    alerts: Vec<AmlAlert>,
+}
+
+impl MonitoredWallet {
+    pub fn new(wallet: Box<dyn Wallet>) -> Self {
+        MonitoredWallet {
+            wallet,
+            rules: Vec::new(),
+            history: Vec::new(),
+            alerts: Vec::new(),
+        }
+    }
+
+    pub fn get_wallet(&self) -> &dyn Wallet {
+        self.wallet.as_ref()
+    }
+
+    pub fn add_rule(&mut self, rule: Box<dyn MonitoringRule>, action: RuleAction) {
+        self.rules.push((rule, action));
+    }
+
+    /// Names of the configured rules, in evaluation order
+    pub fn get_rule_names(&self) -> Vec<&str> {
+        self.rules.iter().map(|(rule, _)| rule.name()).collect()
+    }
+
+    /// Completed operations, oldest first
+    pub fn get_history(&self) -> &[MonitoredOperation] {
+        &self.history
+    }
+
+    pub fn get_alerts(&self) -> &[AmlAlert] {
+        &self.alerts
+    }
+
+    /// Currency of an account of the wrapped wallet, `None` when the wallet has no such account
+    fn currency_of(&self, account_number: &str) -> Option<String> {
+        self.wallet.get_account_by_number(account_number).ok().map(|account| account.get_currency().to_string())
+    }
+
+    /// Runs every rule on an operation, raising alerts, and refuses it when a blocking rule fired
+    fn screen(&mut self, kind: TransactionKind, currency: &str, amount: f64, counterparty_wallet_id: Option<&str>) -> Result<MonitoredOperation, String> {
+        let operation = MonitoredOperation {
+            wallet_id: self.wallet.get_wallet_id().to_string(),
+            kind,
+            currency: currency.to_string(),
+            amount,
+            counterparty_wallet_id: counterparty_wallet_id.map(|id| id.to_string()),
+            timestamp: current_timestamp(),
+        };
+        let fired: Vec<(String, RuleAction, Evidence)> = self.rules.iter()
+            .filter_map(|(rule, action)| {
+                rule.evaluate(&operation, &self.history).map(|evidence| (rule.name().to_string(), *action, evidence))
+            })
+            .collect();
+        let blocking_rules: Vec<String> = fired.iter()
+            .filter(|(_, action, _)| *action == RuleAction::Block)
+            .map(|(name, _, _)| name.clone())
+            .collect();
+
+        for (rule_name, _, evidence) in fired {
+            self.alerts.push(AmlAlert {
+                alert_id: format!(
+                    "Aml-{}",
+                    rand::rng()
+                        .sample_iter(&Alphanumeric)
+                        .take(10)
+                        .map(char::from)
+                        .collect::<String>()
+                ),
+                rule_name,
+                operation: operation.clone(),
+                evidence,
+                blocked: !blocking_rules.is_empty(),
+            });
+        }
+        if !blocking_rules.is_empty() {
+            return Err(format!("{} blocked by AML rules: {}", operation.kind, blocking_rules.join(", ")));
+        }
+        Ok(operation)
+    }
+
+    fn record(&mut self, operation: MonitoredOperation, is_successful: bool) {
+        // refused operations moved no money, so they say nothing about later ones
+        if is_successful {
+            self.history.push(operation);
+        }
+    }
+
+    fn account_blocked(&self, account: Option<&dyn Account>, currency: &str, error: String) -> AccountResponse {
+        AccountResponse {
+            account_number: account.map(|a| a.get_account_number().to_string()).unwrap_or_default(),
+            account_type: account.map(|a| a.get_account_type()).unwrap_or(AccountType::Basic),
+            currency: currency.to_string(),
+            balance: account.map(|a| a.get_balance()).unwrap_or(0.0),
+            is_successful: false,
+            error_message: Some(error),
+        }
+    }
+
+    fn withdraw_blocked(&self, account: Option<&dyn Account>, currency: &str, amount: f64, error: String) -> WithdrawWalletResponse {
+        let response = self.account_blocked(account, currency, error);
+        WithdrawWalletResponse {
+            wallet_id: self.wallet.get_wallet_id().to_string(),
+            wallet_type: self.wallet.get_wallet_type(),
+            currency: response.currency,
+            amount,
+            account_number: response.account_number,
+            account_type: response.account_type,
+            balance: response.balance,
+            is_successful: false,
+            error_message: response.error_message,
+        }
+    }
+
+    fn transfer_blocked(
+        &self,
+        to_wallet: &dyn Wallet,
+        sender_account_number: String,
+        recipient_account_number: String,
+        currency: &str,
+        amount: f64,
+        error: String,
+    ) -> TransferResponse {
+        TransferResponse {
+            currency: currency.to_string(),
+            amount,
+            sender_account_number,
+            sender_wallet_id: self.wallet.get_wallet_id().to_string(),
+            sender_wallet_type: self.wallet.get_wallet_type(),
+            recipient_account_number,
+            recipient_wallet_id: to_wallet.get_wallet_id().to_string(),
+            recipient_wallet_type: to_wallet.get_wallet_type(),
+            is_successful: false,
+            error_message: Some(error),
+        }
+    }
+}
+
+impl DisplayWallet for MonitoredWallet {
+    fn summary(&self) -> WalletSummary {
+        self.wallet.summary()
+    }
+}
+
+impl Wallet for MonitoredWallet {
+    fn add_account(&mut self, account: Box<dyn Account>) -> Result<&dyn Account, String> {
+        self.wallet.add_account(account)
+    }
+
+    fn balance(&self, currency: &str) -> Result<f64, String> {
+        self.wallet.balance(currency)
+    }
+
+    fn get_wallet_id(&self) -> &str {
+        self.wallet.get_wallet_id()
+    }
+
+    fn get_wallet_type(&self) -> WalletType {
+        self.wallet.get_wallet_type()
+    }
+
+    fn find_account_index_by_currency(&self, currency: &str) -> Option<usize> {
+        self.wallet.find_account_index_by_currency(currency)
+    }
+
+    fn get_account_number_by_index(&self, index: usize) -> Option<&str> {
+        self.wallet.get_account_number_by_index(index)
+    }
+
+    fn get_account_by_currency(&self, currency: &str) -> Option<&dyn Account> {
+        self.wallet.get_account_by_currency(currency)
+    }
+
+    fn get_accounts(&self) -> Vec<&dyn Account> {
+        self.wallet.get_accounts()
+    }
+
+    fn get_accounts_closed_since(&self, timestamp: u64) -> Vec<&dyn Account> {
+        self.wallet.get_accounts_closed_since(timestamp)
+    }
+
+    fn get_account_by_number(&self, account_number: &str) -> Result<&dyn Account, String> {
+        self.wallet.get_account_by_number(account_number)
+    }
+
+    fn transfer(&mut self, to_wallet: &mut dyn Wallet, currency: &str, amount: f64) -> TransferResponse {
+        match self.screen(TransactionKind::TransferOut, currency, amount, Some(to_wallet.get_wallet_id())) {
+            Ok(operation) => {
+                let response = self.wallet.transfer(to_wallet, currency, amount);
+                self.record(operation, response.is_successful);
+                response
+            }
+            Err(error) => {
+                let sender_account_number = self.wallet.get_account_by_currency(currency)
+                    .map(|account| account.get_account_number().to_string())
+                    .unwrap_or_default();
+                let recipient_account_number = to_wallet.get_account_by_currency(currency)
+                    .map(|account| account.get_account_number().to_string())
+                    .unwrap_or_default();
+                self.transfer_blocked(to_wallet, sender_account_number, recipient_account_number, currency, amount, error)
+            }
+        }
+    }
+
+    fn deposit(&mut self, currency: &str, amount: f64) -> AccountResponse {
+        match self.screen(TransactionKind::Deposit, currency, amount, None) {
+            Ok(operation) => {
+                let response = self.wallet.deposit(currency, amount);
+                self.record(operation, response.is_successful);
+                response
+            }
+            Err(error) => self.account_blocked(self.wallet.get_account_by_currency(currency), currency, error),
+        }
+    }
+
+    fn withdraw(&mut self, currency: &str, amount: f64) -> WithdrawWalletResponse {
+        match self.screen(TransactionKind::Withdrawal, currency, amount, None) {
+            Ok(operation) => {
+                let response = self.wallet.withdraw(currency, amount);
+                self.record(operation, response.is_successful);
+                response
+            }
+            Err(error) => self.withdraw_blocked(self.wallet.get_account_by_currency(currency), currency, amount, error),
+        }
+    }
+
+    fn transfer_from_account(
+        &mut self,
+        from_account_number: &str,
+        to_wallet: &mut dyn Wallet,
+        to_account_number: &str,
+        amount: f64,
+    ) -> TransferResponse {
+        // an unknown account moves no money, so the wallet's own refusal is enough
+        let currency = match self.currency_of(from_account_number) {
+            Some(currency) => currency,
+            None => return self.wallet.transfer_from_account(from_account_number, to_wallet, to_account_number, amount),
+        };
+        match self.screen(TransactionKind::TransferOut, &currency, amount, Some(to_wallet.get_wallet_id())) {
+            Ok(operation) => {
+                let response = self.wallet.transfer_from_account(from_account_number, to_wallet, to_account_number, amount);
+                self.record(operation, response.is_successful);
+                response
+            }
+            Err(error) => {
+                self.transfer_blocked(to_wallet, from_account_number.to_string(), to_account_number.to_string(), &currency, amount, error)
+            }
+        }
+    }
+
+    fn deposit_to_account(&mut self, account_number: &str, amount: f64) -> AccountResponse {
+        let currency = match self.currency_of(account_number) {
+            Some(currency) => currency,
+            None => return self.wallet.deposit_to_account(account_number, amount),
+        };
+        match self.screen(TransactionKind::Deposit, &currency, amount, None) {
+            Ok(operation) => {
+                let response = self.wallet.deposit_to_account(account_number, amount);
+                self.record(operation, response.is_successful);
+                response
+            }
+            Err(error) => self.account_blocked(self.wallet.get_account_by_number(account_number).ok(), &currency, error),
+        }
+    }
+
+    fn withdraw_from_account(&mut self, account_number: &str, amount: f64) -> WithdrawWalletResponse {
+        let currency = match self.currency_of(account_number) {
+            Some(currency) => currency,
+            None => return self.wallet.withdraw_from_account(account_number, amount),
+        };
+        match self.screen(TransactionKind::Withdrawal, &currency, amount, None) {
+            Ok(operation) => {
+                let response = self.wallet.withdraw_from_account(account_number, amount);
+                self.record(operation, response.is_successful);
+                response
+            }
+            Err(error) => self.withdraw_blocked(self.wallet.get_account_by_number(account_number).ok(), &currency, amount, error),
+        }
+    }
+
+    /// Screens money coming in; a blocked credit makes the sending wallet roll its debit back
+    fn receive_transfer(&mut self, account_number: &str, amount: f64, from_account_number: &str) -> AccountResponse {
+        let currency = match self.currency_of(account_number) {
+            Some(currency) => currency,
+            None => return self.wallet.receive_transfer(account_number, amount, from_account_number),
+        };
+        match self.screen(TransactionKind::TransferIn, &currency, amount, None) {
+            Ok(operation) => {
+                let response = self.wallet.receive_transfer(account_number, amount, from_account_number);
+                self.record(operation, response.is_successful);
+                response
+            }
+            Err(error) => self.account_blocked(self.wallet.get_account_by_number(account_number).ok(), &currency, error),
+        }
+    }
+
+    fn set_event_bus(&mut self, event_bus: Option<EventBus>) {
+        self.wallet.set_event_bus(event_bus);
+    }
+}
+
+fn check_positive(what: &str, value: f64) -> Result<(), String> {
+    if value > 0.0 && value.is_finite() {
+        Ok(())
+    } else {
+        Err(format!("{} must be a positive number", what))
+    }
+}
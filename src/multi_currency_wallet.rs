@@ -1,13 +1,40 @@
-use rand::distributions::Alphanumeric;
+use std::collections::HashMap;
+use rand::distr::Alphanumeric;
 use rand::Rng;
-use crate::accounts::{Account, AccountResponse};
+use crate::account_number::AccountNumber;
+use crate::accounts::{Account, AccountResponse, AccountTransferResponse, AccountType};
+use crate::basic_account::BasicAccount;
+use crate::events::{EventBus, WalletEvent};
+use crate::exchange::{ExchangeQuote, ExchangeRateSource, ExchangeRecord, ExchangeResponse};
+use crate::premium_account::PremiumAccount;
+use crate::rendering::WalletSummary;
+use crate::transactions::{current_timestamp, TransactionKind};
+use crate::valuation::{value_accounts, WalletValuation};
 use crate::wallets::{DisplayWallet, TransferResponse, Wallet, WalletType, WithdrawWalletResponse};
 
-/// Multi-currency wallet managing multiple accounts
+/// Reporting currency of a new `MultiCurrencyWallet`
+pub const DEFAULT_BASE_CURRENCY: &str = "USD";
+
+/// Number of seconds an exchange quote stays executable by default
+pub const DEFAULT_QUOTE_VALIDITY_SECONDS: u64 = 30;
+
+/// Multi-currency wallet managing multiple accounts, possibly several per currency.
+/// Currency-based operations use the designated default account of that currency.
 pub struct MultiCurrencyWallet {
     accounts: Vec<Box<dyn Account>>,
+    /// Closed accounts with the time each was closed
+    closed_accounts: Vec<(Box<dyn Account>, u64)>,
+    default_accounts: HashMap<String, String>,
+    auto_open_account_type: Option<AccountType>,
+    auto_open_overdraft_limit: f64,
+    base_currency: String,
+    exchange_spread: f64,
+    quote_validity_seconds: u64,
+    pending_quotes: HashMap<String, ExchangeQuote>,
+    exchange_history: Vec<ExchangeRecord>,
     wallet_id: String,
     wallet_type: WalletType,
+    event_bus: Option<EventBus>,
 }
 
 impl MultiCurrencyWallet {
@@ -15,31 +42,484 @@ impl MultiCurrencyWallet {
         let wallet_id = format!(
             "{:?}-{}",
             WalletType::MultiCurrency,
-            rand::thread_rng()
+            rand::rng()
                 .sample_iter(&Alphanumeric)
                 .take(10)
                 .map(char::from)
                 .collect::<String>()
         );
 
+        Self::restore(&wallet_id)
+    }
+
+    /// Rebuilds an empty wallet with an existing wallet id, e.g. one loaded from a state file
+    pub fn restore(wallet_id: &str) -> Self {
         MultiCurrencyWallet {
             accounts: Vec::new(),
-            wallet_id,
+            closed_accounts: Vec::new(),
+            default_accounts: HashMap::new(),
+            auto_open_account_type: None,
+            auto_open_overdraft_limit: 0.0,
+            base_currency: DEFAULT_BASE_CURRENCY.to_string(),
+            exchange_spread: 0.0,
+            quote_validity_seconds: DEFAULT_QUOTE_VALIDITY_SECONDS,
+            pending_quotes: HashMap::new(),
+            exchange_history: Vec::new(),
+            wallet_id: wallet_id.to_string(),
             wallet_type: WalletType::MultiCurrency,
+            event_bus: None,
+        }
+    }
+
+    /// Issues an account number that does not collide with any open or closed account in the wallet
+    pub fn issue_account_number(&self, account_type: AccountType) -> Result<AccountNumber, String> {
+        AccountNumber::generate_unique(account_type, |candidate| self.is_account_number_taken(candidate))
+    }
+
+    /// Sets the reporting currency used by `valuation`
+    pub fn set_base_currency(&mut self, currency: &str) {
+        self.base_currency = currency.to_string();
+    }
+
+    pub fn get_base_currency(&self) -> &str {
+        &self.base_currency
+    }
+
+    /// Values every open account in the base currency, including overdrawn balances,
+    /// and returns the per-account breakdown, the rates used and the total
+    pub fn valuation(&self, rates: &dyn ExchangeRateSource) -> Result<WalletValuation, String> {
+        value_accounts(&self.wallet_id, &self.get_accounts(), &self.base_currency, rates)
+    }
+
+    /// Sets the markup taken on exchanges as a fraction of the market rate, e.g. `0.01` for 1%
+    pub fn set_exchange_spread(&mut self, spread: f64) -> Result<(), String> {
+        if !(0.0..1.0).contains(&spread) {
+            return Err(format!("Exchange spread must be between 0 and 1, got {}", spread));
+        }
+        self.exchange_spread = spread;
+        Ok(())
+    }
+
+    pub fn get_exchange_spread(&self) -> f64 {
+        self.exchange_spread
+    }
+
+    /// Sets how long new exchange quotes stay executable
+    pub fn set_quote_validity_seconds(&mut self, seconds: u64) {
+        self.quote_validity_seconds = seconds;
+    }
+
+    /// Quotes an exchange between the default accounts of two currencies. The rate and the
+    /// accounts are locked until the quote expires, and it can be executed once with
+    /// `execute_exchange`. Expired quotes are dropped whenever a new one is issued.
+    pub fn quote_exchange(
+        &mut self,
+        from_currency: &str,
+        to_currency: &str,
+        amount: f64,
+        rates: &dyn ExchangeRateSource,
+    ) -> Result<ExchangeQuote, String> {
+        if amount <= 0.0 {
+            return Err("Exchange amount must be positive".to_string());
+        }
+        if from_currency == to_currency {
+            return Err("Cannot exchange a currency into itself".to_string());
+        }
+        let from_index = self.find_default_index_by_currency(from_currency)
+            .ok_or(format!("No account found with currency: {}", from_currency))?;
+        let to_index = self.find_default_index_by_currency(to_currency)
+            .ok_or(format!("No account found with currency: {}", to_currency))?;
+        let market_rate = rates.get_rate(from_currency, to_currency).ok_or(format!(
+            "No exchange rate available from {} to {}",
+            from_currency, to_currency
+        ))?;
+
+        let effective_rate = market_rate * (1.0 - self.exchange_spread);
+        let quoted_at = current_timestamp();
+        let quote = ExchangeQuote {
+            quote_id: format!(
+                "Quote-{}",
+                rand::rng()
+                    .sample_iter(&Alphanumeric)
+                    .take(10)
+                    .map(char::from)
+                    .collect::<String>()
+            ),
+            wallet_id: self.wallet_id.clone(),
+            from_currency: from_currency.to_string(),
+            to_currency: to_currency.to_string(),
+            from_account_number: self.accounts[from_index].get_account_number().to_string(),
+            to_account_number: self.accounts[to_index].get_account_number().to_string(),
+            amount,
+            market_rate,
+            spread: self.exchange_spread,
+            effective_rate,
+            converted_amount: amount * effective_rate,
+            quoted_at,
+            expires_at: quoted_at + self.quote_validity_seconds,
+        };
+        self.pending_quotes.retain(|_, pending| quoted_at < pending.expires_at);
+        self.pending_quotes.insert(quote.quote_id.clone(), quote.clone());
+        Ok(quote)
+    }
+
+    /// Executes a previously issued quote between the accounts it was issued for. Both legs are
+    /// posted, or neither is; a quote that fails stays pending until it expires.
+    pub fn execute_exchange(&mut self, quote_id: &str) -> ExchangeResponse {
+        let quote = match self.pending_quotes.get(quote_id) {
+            Some(quote) => quote.clone(),
+            None => {
+                return self.exchange_failed(quote_id, "", "", format!("No pending quote found with id: {}", quote_id));
+            }
+        };
+        if current_timestamp() >= quote.expires_at {
+            return self.exchange_failed(quote_id, &quote.from_currency, &quote.to_currency, format!("Quote {} has expired", quote_id));
+        }
+
+        let (from_index, to_index) = match (
+            self.find_account_index_by_number(&quote.from_account_number),
+            self.find_account_index_by_number(&quote.to_account_number),
+        ) {
+            (Ok(from_index), Ok(to_index)) => (from_index, to_index),
+            _ => {
+                return self.exchange_failed(quote_id, &quote.from_currency, &quote.to_currency, "Exchange accounts are no longer available".to_string());
+            }
+        };
+        let from_account_number = quote.from_account_number.clone();
+        let to_account_number = quote.to_account_number.clone();
+
+        let withdraw_response = self.accounts[from_index].withdraw_to(quote.amount, &to_account_number);
+        if !withdraw_response.is_successful {
+            return self.exchange_failed(quote_id, &quote.from_currency, &quote.to_currency, withdraw_response.error_message.unwrap_or_default());
+        }
+        let deposit_response = self.accounts[to_index].deposit_from(quote.converted_amount, &from_account_number);
+        if !deposit_response.is_successful {
+            // Deposit failed, so revert the withdrawal
+            self.accounts[from_index].reverse_transfer_out(quote.amount, &to_account_number);
+            return self.exchange_failed(quote_id, &quote.from_currency, &quote.to_currency, deposit_response.error_message.unwrap_or_default());
+        }
+
+        let record = ExchangeRecord {
+            quote_id: quote.quote_id.clone(),
+            from_account_number: from_account_number.clone(),
+            to_account_number: to_account_number.clone(),
+            from_currency: quote.from_currency.clone(),
+            to_currency: quote.to_currency.clone(),
+            debited_amount: quote.amount,
+            credited_amount: quote.converted_amount,
+            market_rate: quote.market_rate,
+            effective_rate: quote.effective_rate,
+            timestamp: current_timestamp(),
+        };
+        self.pending_quotes.remove(quote_id);
+        self.exchange_history.push(record.clone());
+        self.emit(WalletEvent::ExchangeCompleted {
+            wallet_id: self.wallet_id.clone(),
+            record,
+        });
+
+        ExchangeResponse {
+            quote_id: quote.quote_id,
+            from_account_number,
+            to_account_number,
+            from_currency: quote.from_currency,
+            to_currency: quote.to_currency,
+            debited_amount: quote.amount,
+            credited_amount: quote.converted_amount,
+            effective_rate: quote.effective_rate,
+            is_successful: true,
+            error_message: None,
+        }
+    }
+
+    /// Quotes and immediately executes an exchange between the default accounts of two currencies
+    pub fn exchange(
+        &mut self,
+        from_currency: &str,
+        to_currency: &str,
+        amount: f64,
+        rates: &dyn ExchangeRateSource,
+    ) -> ExchangeResponse {
+        match self.quote_exchange(from_currency, to_currency, amount, rates) {
+            Ok(quote) => {
+                let response = self.execute_exchange(&quote.quote_id);
+                // nobody else holds the quote, so it is not left pending after a failure
+                self.pending_quotes.remove(&quote.quote_id);
+                response
+            }
+            Err(error) => self.exchange_failed("", from_currency, to_currency, error),
+        }
+    }
+
+    /// Completed exchanges with the effective rates applied, oldest first
+    pub fn get_exchange_history(&self) -> &[ExchangeRecord] {
+        &self.exchange_history
+    }
+
+    /// Configures the type of account opened automatically on the first deposit in a currency
+    /// the wallet holds no account for. `None` (the default) rejects such deposits instead.
+    pub fn set_auto_open_account_type(&mut self, account_type: Option<AccountType>) {
+        self.auto_open_account_type = account_type;
+    }
+
+    /// Overdraft limit given to automatically opened Premium accounts
+    pub fn set_auto_open_overdraft_limit(&mut self, overdraft_limit: f64) -> Result<(), String> {
+        if overdraft_limit < 0.0 || !overdraft_limit.is_finite() {
+            return Err("Overdraft limit must be a finite, non-negative number".to_string());
+        }
+        self.auto_open_overdraft_limit = overdraft_limit;
+        Ok(())
+    }
+
+    pub fn get_auto_open_account_type(&self) -> Option<AccountType> {
+        self.auto_open_account_type.clone()
+    }
+
+    /// Closes an account with a zero balance. The closed account and its history stay queryable.
+    pub fn close_account(&mut self, account_number: &str) -> Result<&dyn Account, String> {
+        let index = self.find_account_index_by_number(account_number)?;
+        let balance = self.accounts[index].get_balance();
+        if balance < 0.0 {
+            return Err(format!("Cannot close account {} while an overdraft of {} is outstanding", account_number, -balance));
         }
+        if balance > 0.0 {
+            return Err(format!("Cannot close account {} with a remaining balance of {}", account_number, balance));
+        }
+
+        let account = self.accounts.remove(index);
+        let currency = account.get_currency().to_string();
+        if self.default_accounts.get(&currency).map(|n| n.as_str()) == Some(account.get_account_number()) {
+            // hand the default over to the next account in the same currency, if any
+            match self.accounts.iter().find(|acc| acc.get_currency() == currency) {
+                Some(next) => {
+                    self.default_accounts.insert(currency, next.get_account_number().to_string());
+                }
+                None => {
+                    self.default_accounts.remove(&currency);
+                }
+            }
+        }
+        self.emit(WalletEvent::AccountClosed {
+            wallet_id: self.wallet_id.clone(),
+            account_number: account.get_account_number().to_string(),
+            currency: account.get_currency().to_string(),
+        });
+        self.closed_accounts.push((account, current_timestamp()));
+        Ok(self.closed_accounts.last().unwrap().0.as_ref())
+    }
+
+    /// Sweeps the remaining balance to another account in this wallet, then closes the account
+    pub fn close_account_sweeping_to(&mut self, account_number: &str, to_account_number: &str) -> Result<&dyn Account, String> {
+        let index = self.find_account_index_by_number(account_number)?;
+        let balance = self.accounts[index].get_balance();
+        if balance < 0.0 {
+            return Err(format!("Cannot close account {} while an overdraft of {} is outstanding", account_number, -balance));
+        }
+        if balance > 0.0 {
+            let response = self.internal_transfer(account_number, to_account_number, balance)?;
+            if !response.is_successful {
+                return Err(response.error_message.unwrap_or("Failed to sweep remaining balance".to_string()));
+            }
+        }
+        self.close_account(account_number)
+    }
+
+    /// Sweeps the remaining balance to an account in another wallet, then closes the account
+    pub fn close_account_sweeping_to_wallet(
+        &mut self,
+        account_number: &str,
+        to_wallet: &mut dyn Wallet,
+        to_account_number: &str,
+    ) -> Result<&dyn Account, String> {
+        let index = self.find_account_index_by_number(account_number)?;
+        let balance = self.accounts[index].get_balance();
+        if balance < 0.0 {
+            return Err(format!("Cannot close account {} while an overdraft of {} is outstanding", account_number, -balance));
+        }
+        if balance > 0.0 {
+            let response = self.transfer_from_account(account_number, to_wallet, to_account_number, balance);
+            if !response.is_successful {
+                return Err(response.error_message.unwrap_or("Failed to sweep remaining balance".to_string()));
+            }
+        }
+        self.close_account(account_number)
+    }
+
+    /// Returns the closed accounts, in the order they were closed
+    pub fn get_closed_accounts(&self) -> Vec<&dyn Account> {
+        self.closed_accounts.iter().map(|(acc, _)| acc.as_ref()).collect()
+    }
+
+    pub fn get_closed_account_by_number(&self, account_number: &str) -> Result<&dyn Account, String> {
+        let account_number = AccountNumber::parse(account_number)?;
+        self.closed_accounts.iter()
+            .find(|(acc, _)| acc.get_account_number() == account_number.as_str())
+            .map(|(acc, _)| acc.as_ref())
+            .ok_or(format!("No closed account found with account number: {}", account_number))
+    }
+
+    /// Opens the default account for a currency when auto-open is configured
+    fn auto_open_account(&mut self, currency: &str) -> Result<(), String> {
+        let account_type = match self.auto_open_account_type.clone() {
+            Some(account_type) => account_type,
+            None => return Ok(()),
+        };
+        let account_number = self.issue_account_number(account_type.clone())?;
+        let account: Box<dyn Account> = match account_type {
+            AccountType::Basic => Box::new(BasicAccount::with_account_number(currency, account_number)?),
+            AccountType::Premium => Box::new(PremiumAccount::with_account_number(currency, self.auto_open_overdraft_limit, account_number)?),
+        };
+        self.add_account(account)?;
+        Ok(())
+    }
+
+    fn is_account_number_taken(&self, account_number: &str) -> bool {
+        self.accounts.iter()
+            .chain(self.closed_accounts.iter().map(|(acc, _)| acc))
+            .any(|acc| acc.get_account_number() == account_number)
+    }
+
+    /// Converts a `BasicAccount` into a `PremiumAccount` with the given overdraft limit,
+    /// keeping its account number, balance and history. The number keeps its `Basic` prefix.
+    pub fn upgrade_account(&mut self, account_number: &str, overdraft_limit: f64) -> Result<&dyn Account, String> {
+        if overdraft_limit < 0.0 || !overdraft_limit.is_finite() {
+            return Err("Overdraft limit must be a finite, non-negative number".to_string());
+        }
+        let index = self.find_account_index_by_number(account_number)?;
+        let account = &self.accounts[index];
+        if account.get_account_type() == AccountType::Premium {
+            return Err(format!("Account {} is already a Premium account", account_number));
+        }
+
+        let mut upgraded = PremiumAccount::restore(
+            AccountNumber::parse(account.get_account_number())?,
+            account.get_currency(),
+            account.get_balance(),
+            overdraft_limit,
+            account.get_history().to_vec(),
+        );
+        upgraded.set_event_bus(self.event_bus.clone());
+        self.accounts[index] = Box::new(upgraded);
+        self.emit_type_changed(index);
+        Ok(self.accounts[index].as_ref())
+    }
+
+    /// Converts a `PremiumAccount` back into a `BasicAccount`, keeping its account number,
+    /// balance and history. The number keeps its `Premium` prefix. Refused while the account is overdrawn.
+    pub fn downgrade_account(&mut self, account_number: &str) -> Result<&dyn Account, String> {
+        let index = self.find_account_index_by_number(account_number)?;
+        let account = &self.accounts[index];
+        if account.get_account_type() == AccountType::Basic {
+            return Err(format!("Account {} is already a Basic account", account_number));
+        }
+        if account.get_balance() < 0.0 {
+            return Err(format!("Cannot downgrade account {} while its balance is negative", account_number));
+        }
+
+        let mut downgraded = BasicAccount::restore(
+            AccountNumber::parse(account.get_account_number())?,
+            account.get_currency(),
+            account.get_balance(),
+            account.get_history().to_vec(),
+        );
+        downgraded.set_event_bus(self.event_bus.clone());
+        self.accounts[index] = Box::new(downgraded);
+        self.emit_type_changed(index);
+        Ok(self.accounts[index].as_ref())
+    }
+
+    /// Makes the account the default for currency-based operations in its currency
+    pub fn set_default_account(&mut self, account_number: &str) -> Result<(), String> {
+        let index = self.find_account_index_by_number(account_number)?;
+        let account = &self.accounts[index];
+        self.default_accounts.insert(account.get_currency().to_string(), account.get_account_number().to_string());
+        Ok(())
+    }
+
+    /// Returns all accounts held in the given currency, in the order they were added
+    pub fn get_accounts_by_currency(&self, currency: &str) -> Vec<&dyn Account> {
+        self.accounts.iter()
+            .filter(|acc| acc.get_currency() == currency)
+            .map(|acc| acc.as_ref())
+            .collect()
+    }
+
+    /// Moves money between two accounts of the same currency held in this wallet
+    pub fn internal_transfer(
+        &mut self,
+        from_account_number: &str,
+        to_account_number: &str,
+        amount: f64,
+    ) -> Result<AccountTransferResponse, String> {
+        let from_index = self.find_account_index_by_number(from_account_number)?;
+        let to_index = self.find_account_index_by_number(to_account_number)?;
+        if from_index == to_index {
+            return Err("Cannot transfer between an account and itself".to_string());
+        }
+        if self.accounts[from_index].get_currency() != self.accounts[to_index].get_currency() {
+            return Err(format!(
+                "Source account currency: {}, Receiver account currency: {}, mismatch",
+                self.accounts[from_index].get_currency(),
+                self.accounts[to_index].get_currency()
+            ));
+        }
+
+        let (from_account, to_account) = if from_index < to_index {
+            let (head, tail) = self.accounts.split_at_mut(to_index);
+            (&mut head[from_index], &mut tail[0])
+        } else {
+            let (head, tail) = self.accounts.split_at_mut(from_index);
+            (&mut tail[0], &mut head[to_index])
+        };
+        let response = from_account.transfer(to_account.as_mut(), amount);
+        if response.is_successful {
+            self.emit(WalletEvent::InternalTransferCompleted {
+                wallet_id: self.wallet_id.clone(),
+                from_account_number: response.account_number.clone(),
+                to_account_number: response.recipient_account_number.clone(),
+                currency: response.currency.clone(),
+                amount,
+            });
+        }
+        Ok(response)
+    }
+
+    fn emit_type_changed(&self, index: usize) {
+        let account = &self.accounts[index];
+        self.emit(WalletEvent::AccountTypeChanged {
+            wallet_id: self.wallet_id.clone(),
+            account_number: account.get_account_number().to_string(),
+            currency: account.get_currency().to_string(),
+            account_type: account.get_account_type(),
+            overdraft_limit: account.get_overdraft_limit(),
+        });
+    }
+
+    fn find_default_index_by_currency(&self, currency: &str) -> Option<usize> {
+        let default_account_number = self.default_accounts.get(currency)?;
+        self.accounts.iter().position(|acc| acc.get_account_number() == default_account_number)
+    }
+
+    fn find_account_index_by_number(&self, account_number: &str) -> Result<usize, String> {
+        let account_number = AccountNumber::parse(account_number)?;
+        self.accounts.iter()
+            .position(|acc| acc.get_account_number() == account_number.as_str())
+            .ok_or(format!("No account found with account number: {}", account_number))
+    }
+}
+
+impl Default for MultiCurrencyWallet {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl DisplayWallet for MultiCurrencyWallet {
-    fn display_details(&self) {
-        println!("Wallet ID: {}", self.wallet_id);
-        println!("Wallet Type: {:?}", self.wallet_type);
-        println!("-----------------------------");
-        for (i, account) in self.accounts.iter().enumerate() {
-            println!("Account {} Details: ", i+1);
-            println!("-----------------------------");
-            account.display_details();
-            println!("-----------------------------");
+    fn summary(&self) -> WalletSummary {
+        WalletSummary {
+            wallet_id: self.wallet_id.clone(),
+            wallet_type: self.wallet_type.clone(),
+            accounts: self.accounts.iter().map(|account| account.summary()).collect(),
         }
     }
 }
@@ -47,30 +527,48 @@ impl DisplayWallet for MultiCurrencyWallet {
 impl Wallet for MultiCurrencyWallet {
 
     fn get_account_by_currency(&self, currency: &str) -> Option<&dyn Account> {
-       // self.accounts.iter().position(|acc| acc.get_currency() == currency)
-
-        // iterate the accounts in self and return the matching account for the currency
+        // return the default account for the currency
         // if no account is found, return None
-        self.accounts.iter()
-            .find(|acc| acc.get_currency() == currency)
-            .map(|acc| &**acc)
+        self.find_default_index_by_currency(currency)
+            .map(|index| self.accounts[index].as_ref())
+    }
+
+    fn get_accounts(&self) -> Vec<&dyn Account> {
+        // open accounts, in the order they were added
+        self.accounts.iter().map(|acc| acc.as_ref()).collect()
     }
 
-    fn add_account(&mut self, account: Box<dyn Account>) -> Result<&dyn Account, String> {
-        if self.get_account_by_currency(account.get_currency()).is_some() {
-            return Err(String::from("Account with this currency already exists"));
+    fn get_accounts_closed_since(&self, timestamp: u64) -> Vec<&dyn Account> {
+        self.closed_accounts.iter()
+            .filter(|(_, closed_at)| *closed_at >= timestamp)
+            .map(|(acc, _)| acc.as_ref())
+            .collect()
+    }
+
+    fn add_account(&mut self, mut account: Box<dyn Account>) -> Result<&dyn Account, String> {
+        AccountNumber::parse(account.get_account_number())?;
+        if self.is_account_number_taken(account.get_account_number()) {
+            return Err(format!("Account number {} already exists", account.get_account_number()));
+        }
+        // the first account of a currency becomes its default
+        if !self.default_accounts.contains_key(account.get_currency()) {
+            self.default_accounts.insert(account.get_currency().to_string(), account.get_account_number().to_string());
         }
 
+        if self.event_bus.is_some() {
+            account.set_event_bus(self.event_bus.clone());
+        }
         self.accounts.push(account);
         Ok(self.accounts.last().unwrap().as_ref())
     }
 
     fn balance(&self, currency: &str) -> Result<f64, String> {
         // iterate the accounts in self and sum the balances of the accounts with the matching currency
-        match self.accounts.iter().find(|acc| acc.get_currency() == currency) {
-            Some(account) => Ok(account.get_balance()),
-            None => Err(format!("No account found with currency: {}", currency)),
+        let accounts = self.get_accounts_by_currency(currency);
+        if accounts.is_empty() {
+            return Err(format!("No account found with currency: {}", currency));
         }
+        Ok(accounts.iter().map(|acc| acc.get_balance()).sum())
     }
 
     fn get_wallet_id(&self) -> &str {
@@ -82,123 +580,97 @@ impl Wallet for MultiCurrencyWallet {
     }
 
     fn find_account_index_by_currency(&self, currency: &str) -> Option<usize> {
-        // iterate the accounts in self and return the index of the account with the matching currency
+        // return the index of the default account for the currency
         // if no account is found, return None
-        match self.accounts.iter().position(|acc| acc.get_currency() == currency) {
-            Some(index) => Some(index),
-            None => None,
-        }
+        self.find_default_index_by_currency(currency)
     }
 
     fn get_account_number_by_index(&self, index: usize) -> Option<&str> {
         // return the account number of the account at the given index
         // if the index is out of bounds, return None
-        match self.accounts.get(index) {
-            Some(account) => Some(account.get_account_number()),
-            None => None,
-        }
+        self.accounts.get(index).map(|account| account.get_account_number())
+    }
+
+    fn get_account_by_number(&self, account_number: &str) -> Result<&dyn Account, String> {
+        let index = self.find_account_index_by_number(account_number)?;
+        Ok(self.accounts[index].as_ref())
     }
 
 
     fn transfer(&mut self, to_wallet: &mut dyn Wallet, currency: &str, amount: f64) -> TransferResponse {
-    match self.accounts.iter_mut().find(|account| account.get_currency() == currency) {
-        Some(account) => {
-            let withdraw_response = account.withdraw(amount);
-            if withdraw_response.is_successful {
-                let deposit_response = to_wallet.deposit(currency, amount);
-                if deposit_response.is_successful {
-                    TransferResponse {
-                        currency: currency.to_string(),
-                        amount,
-                        sender_account_number: withdraw_response.account_number,
-                        sender_wallet_id: self.wallet_id.clone(),
-                        sender_wallet_type: self.wallet_type.clone(),
-                        recipient_account_number: deposit_response.account_number,
-                        recipient_wallet_id: to_wallet.get_wallet_id().to_string(),
-                        recipient_wallet_type: to_wallet.get_wallet_type(),
-                        is_successful: true,
-                        error_message: None,
-                    }
-                } else {
-                    // Deposit failed, so revert the withdrawal
-                    account.deposit(amount);
-                    TransferResponse {
-                        currency: currency.to_string(),
-                        amount,
-                        sender_account_number: withdraw_response.account_number,
-                        sender_wallet_id: self.wallet_id.clone(),
-                        sender_wallet_type: self.wallet_type.clone(),
-                        recipient_account_number: "".to_string(),
-                        recipient_wallet_id: to_wallet.get_wallet_id().to_string(),
-                        recipient_wallet_type: to_wallet.get_wallet_type(),
-                        is_successful: false,
-                        error_message: deposit_response.error_message,
-                    }
-                }
-            } else {
-                TransferResponse {
-                    currency: currency.to_string(),
+        let sender_account_number = match self.find_default_index_by_currency(currency) {
+            Some(index) => self.accounts[index].get_account_number().to_string(),
+            None => {
+                return self.transfer_response(
+                    to_wallet,
+                    currency,
                     amount,
-                    sender_account_number: withdraw_response.account_number,
-                    sender_wallet_id: self.wallet_id.clone(),
-                    sender_wallet_type: self.wallet_type.clone(),
-                    recipient_account_number: "".to_string(),
-                    recipient_wallet_id: to_wallet.get_wallet_id().to_string(),
-                    recipient_wallet_type: to_wallet.get_wallet_type(),
-                    is_successful: false,
-                    error_message: withdraw_response.error_message,
-                }
+                    "".to_string(),
+                    "".to_string(),
+                    Some(format!("No account found with currency: {}", currency)),
+                );
             }
-        }
+        };
 
-        _ => {
-            TransferResponse {
-                currency: currency.to_string(),
-                amount,
-                sender_account_number: "".to_string(),
-                sender_wallet_id: self.wallet_id.clone(),
-                sender_wallet_type: self.wallet_type.clone(),
-                recipient_account_number: "".to_string(),
-                recipient_wallet_id: to_wallet.get_wallet_id().to_string(),
-                recipient_wallet_type: to_wallet.get_wallet_type(),
-                is_successful: false,
-                error_message: Some(format!("No account found with currency: {}", currency)),
+        match to_wallet.get_account_by_currency(currency).map(|acc| acc.get_account_number().to_string()) {
+            Some(recipient_account_number) => {
+                self.transfer_from_account(&sender_account_number, to_wallet, &recipient_account_number, amount)
             }
+            None => self.transfer_response(
+                to_wallet,
+                currency,
+                amount,
+                sender_account_number,
+                "".to_string(),
+                Some(format!("No account found with currency: {}", currency)),
+            ),
         }
     }
-}
-
 
     fn deposit(&mut self, currency: &str, amount: f64) -> AccountResponse {
-        // find the account with the matching currency
+        // find the default account for the currency
+        // if the account is not found and auto-open is configured, open one first
         // if the account is found, deposit the amount and return the response
         // if the account is not found, return an error response
-        match self.accounts.iter_mut().find (| account|  account.get_currency() == currency) {
-            Some(account) => {
-                let account_response = account.deposit(amount);
-                account_response
+        if self.find_default_index_by_currency(currency).is_none() && amount >= 0.0 {
+            if let Err(error) = self.auto_open_account(currency) {
+                self.reject(None, currency, TransactionKind::Deposit, amount, &error);
+                return AccountResponse {
+                    account_number: "".to_string(),
+                    account_type: AccountType::Basic, // Default account type
+                    currency: currency.to_string(),
+                    balance: 0.0,
+                    is_successful: false,
+                    error_message: Some(error),
+                };
             }
+        }
+
+        match self.find_default_index_by_currency(currency) {
+            Some(index) => self.accounts[index].deposit(amount),
 
             _ => {
+                let error = format!("No account found with currency: {}", currency);
+                self.reject(None, currency, TransactionKind::Deposit, amount, &error);
                 AccountResponse {
                     account_number: "".to_string(),
-                    account_type: self.accounts[0].get_account_type().clone(),
+                    account_type: AccountType::Basic, // Default account type
                     currency: currency.to_string(),
                     balance: 0.0,
                     is_successful: false,
-                    error_message: Some(format!("No account found with currency: {}", currency)),
+                    error_message: Some(error),
                 }
             }
         }
     }
 
     fn withdraw(&mut self, currency: &str, amount: f64) -> WithdrawWalletResponse {
-        // find the account with the matching currency
+        // find the default account for the currency
         // if the account is found, withdraw the amount and return the response
         // if the account is not found, return an error response
-        match self.accounts.iter_mut().find (| account|  account.get_currency() == currency) {
-            Some(account) => {
-                let account_response = account.withdraw(amount);
+        match self.find_default_index_by_currency(currency) {
+            Some(index) => {
+                let account_response = self.accounts[index].withdraw(amount);
                 WithdrawWalletResponse {
                     wallet_id: self.wallet_id.clone(),
                     wallet_type: self.wallet_type.clone(),
@@ -213,18 +685,225 @@ impl Wallet for MultiCurrencyWallet {
             }
 
             _ => {
+                let error = format!("No account found with currency: {}", currency);
+                self.reject(None, currency, TransactionKind::Withdrawal, amount, &error);
                 WithdrawWalletResponse {
                     wallet_id: self.wallet_id.clone(),
                     wallet_type: self.wallet_type.clone(),
                     currency: currency.to_string(),
                     amount,
                     account_number: "".to_string(),
-                    account_type: self.accounts[0].get_account_type().clone(),
+                    account_type: AccountType::Basic, // Default account type
                     balance: 0.0,
                     is_successful: false,
-                    error_message: Some(format!("No account found with currency: {}", currency)),
+                    error_message: Some(error),
                 }
             }
         }
     }
+
+    fn transfer_from_account(
+        &mut self,
+        from_account_number: &str,
+        to_wallet: &mut dyn Wallet,
+        to_account_number: &str,
+        amount: f64,
+    ) -> TransferResponse {
+        let index = match self.find_account_index_by_number(from_account_number) {
+            Ok(index) => index,
+            Err(error) => {
+                return self.transfer_response(to_wallet, "", amount, from_account_number.to_string(), to_account_number.to_string(), Some(error));
+            }
+        };
+        let currency = self.accounts[index].get_currency().to_string();
+
+        let recipient_currency = match to_wallet.get_account_by_number(to_account_number) {
+            Ok(account) => account.get_currency().to_string(),
+            Err(error) => {
+                return self.transfer_response(to_wallet, &currency, amount, from_account_number.to_string(), to_account_number.to_string(), Some(error));
+            }
+        };
+        if recipient_currency != currency {
+            return self.transfer_response(
+                to_wallet,
+                &currency,
+                amount,
+                from_account_number.to_string(),
+                to_account_number.to_string(),
+                Some(format!("Source account currency: {}, Receiver account currency: {}, mismatch", currency, recipient_currency)),
+            );
+        }
+
+        let withdraw_response = self.accounts[index].withdraw_to(amount, to_account_number);
+        if !withdraw_response.is_successful {
+            return self.transfer_response(to_wallet, &currency, amount, from_account_number.to_string(), to_account_number.to_string(), withdraw_response.error_message);
+        }
+
+        let deposit_response = to_wallet.receive_transfer(to_account_number, amount, from_account_number);
+        if !deposit_response.is_successful {
+            // Deposit failed, so revert the withdrawal
+            self.accounts[index].reverse_transfer_out(amount, to_account_number);
+            self.emit(WalletEvent::TransferRolledBack {
+                wallet_id: self.wallet_id.clone(),
+                account_number: from_account_number.to_string(),
+                recipient_account_number: to_account_number.to_string(),
+                currency: currency.clone(),
+                amount,
+                reason: deposit_response.error_message.clone().unwrap_or_default(),
+            });
+            return self.transfer_response(to_wallet, &currency, amount, from_account_number.to_string(), to_account_number.to_string(), deposit_response.error_message);
+        }
+
+        self.transfer_response(to_wallet, &currency, amount, from_account_number.to_string(), to_account_number.to_string(), None)
+    }
+
+    fn deposit_to_account(&mut self, account_number: &str, amount: f64) -> AccountResponse {
+        match self.find_account_index_by_number(account_number) {
+            Ok(index) => self.accounts[index].deposit(amount),
+            Err(error) => self.account_not_found(account_number, TransactionKind::Deposit, amount, error),
+        }
+    }
+
+    fn withdraw_from_account(&mut self, account_number: &str, amount: f64) -> WithdrawWalletResponse {
+        match self.find_account_index_by_number(account_number) {
+            Ok(index) => {
+                let account_response = self.accounts[index].withdraw(amount);
+                WithdrawWalletResponse {
+                    wallet_id: self.wallet_id.clone(),
+                    wallet_type: self.wallet_type.clone(),
+                    currency: account_response.currency,
+                    amount,
+                    account_number: account_response.account_number,
+                    account_type: account_response.account_type,
+                    balance: account_response.balance,
+                    is_successful: account_response.is_successful,
+                    error_message: account_response.error_message,
+                }
+            }
+            Err(error) => {
+                self.reject(Some(account_number), "", TransactionKind::Withdrawal, amount, &error);
+                WithdrawWalletResponse {
+                    wallet_id: self.wallet_id.clone(),
+                    wallet_type: self.wallet_type.clone(),
+                    currency: "".to_string(),
+                    amount,
+                    account_number: account_number.to_string(),
+                    account_type: AccountType::Basic, // Default account type
+                    balance: 0.0,
+                    is_successful: false,
+                    error_message: Some(error),
+                }
+            }
+        }
+    }
+
+    fn receive_transfer(&mut self, account_number: &str, amount: f64, from_account_number: &str) -> AccountResponse {
+        match self.find_account_index_by_number(account_number) {
+            Ok(index) => self.accounts[index].deposit_from(amount, from_account_number),
+            Err(error) => self.account_not_found(account_number, TransactionKind::TransferIn, amount, error),
+        }
+    }
+
+    fn set_event_bus(&mut self, event_bus: Option<EventBus>) {
+        for account in self.accounts.iter_mut() {
+            account.set_event_bus(event_bus.clone());
+        }
+        self.event_bus = event_bus;
+    }
+}
+
+impl MultiCurrencyWallet {
+    fn exchange_failed(&self, quote_id: &str, from_currency: &str, to_currency: &str, error: String) -> ExchangeResponse {
+        self.emit(WalletEvent::ExchangeFailed {
+            wallet_id: self.wallet_id.clone(),
+            quote_id: quote_id.to_string(),
+            from_currency: from_currency.to_string(),
+            to_currency: to_currency.to_string(),
+            reason: error.clone(),
+        });
+        ExchangeResponse {
+            quote_id: quote_id.to_string(),
+            from_account_number: "".to_string(),
+            to_account_number: "".to_string(),
+            from_currency: from_currency.to_string(),
+            to_currency: to_currency.to_string(),
+            debited_amount: 0.0,
+            credited_amount: 0.0,
+            effective_rate: 0.0,
+            is_successful: false,
+            error_message: Some(error),
+        }
+    }
+
+    fn account_not_found(&self, account_number: &str, operation: TransactionKind, amount: f64, error: String) -> AccountResponse {
+        self.reject(Some(account_number), "", operation, amount, &error);
+        AccountResponse {
+            account_number: account_number.to_string(),
+            account_type: AccountType::Basic, // Default account type
+            currency: "".to_string(),
+            balance: 0.0,
+            is_successful: false,
+            error_message: Some(error),
+        }
+    }
+
+    fn transfer_response(
+        &self,
+        to_wallet: &dyn Wallet,
+        currency: &str,
+        amount: f64,
+        sender_account_number: String,
+        recipient_account_number: String,
+        error_message: Option<String>,
+    ) -> TransferResponse {
+        self.emit(match &error_message {
+            None => WalletEvent::TransferCompleted {
+                sender_wallet_id: self.wallet_id.clone(),
+                sender_account_number: sender_account_number.clone(),
+                recipient_wallet_id: to_wallet.get_wallet_id().to_string(),
+                recipient_account_number: recipient_account_number.clone(),
+                currency: currency.to_string(),
+                amount,
+            },
+            Some(reason) => WalletEvent::TransferFailed {
+                sender_wallet_id: self.wallet_id.clone(),
+                sender_account_number: sender_account_number.clone(),
+                recipient_wallet_id: to_wallet.get_wallet_id().to_string(),
+                recipient_account_number: recipient_account_number.clone(),
+                currency: currency.to_string(),
+                amount,
+                reason: reason.clone(),
+            },
+        });
+        TransferResponse {
+            currency: currency.to_string(),
+            amount,
+            sender_account_number,
+            sender_wallet_id: self.wallet_id.clone(),
+            sender_wallet_type: self.wallet_type.clone(),
+            recipient_account_number,
+            recipient_wallet_id: to_wallet.get_wallet_id().to_string(),
+            recipient_wallet_type: to_wallet.get_wallet_type(),
+            is_successful: error_message.is_none(),
+            error_message,
+        }
+    }
+
+    /// Publishes a rejection raised by the wallet itself, before any account was touched
+    fn reject(&self, account_number: Option<&str>, currency: &str, operation: TransactionKind, amount: f64, reason: &str) {
+        self.emit(WalletEvent::OperationRejected {
+            wallet_id: Some(self.wallet_id.clone()),
+            account_number: account_number.map(|n| n.to_string()),
+            currency: currency.to_string(),
+            operation,
+            amount,
+            reason: reason.to_string(),
+        });
+    }
+
+    fn emit(&self, event: WalletEvent) {
+        if let Some(event_bus) = &self.event_bus {
+            event_bus.publish(event);
+        }
+    }
 }
@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+use crate::accounts::{Account, AccountType};
+use crate::service::WalletService;
+use crate::transactions::{current_timestamp, TransactionKind};
+use crate::wallets::Wallet;
+
+/// Largest difference between two amounts still treated as equal, absorbing floating point drift
+pub const RECONCILIATION_TOLERANCE: f64 = 1e-6;
+
+/// Balance of one account recomputed from its history
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AccountReconciliation {
+    pub account_number: String,
+    pub account_type: AccountType,
+    pub currency: String,
+    pub transactions: usize,
+    pub reported_balance: f64,
+    pub recomputed_balance: f64,
+}
+
+/// Totals of every reconciled account holding a currency
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CurrencyTotals {
+    pub accounts: usize,
+    pub reported_balance: f64,
+    pub recomputed_balance: f64,
+    pub deposits: f64,
+    pub withdrawals: f64,
+    pub transfers_in: f64,
+    pub transfers_out: f64,
+    pub fees: f64,
+    pub interest: f64,
+}
+
+/// Problem found while reconciling
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum Discrepancy {
+    /// The current balance differs from the sum of the history
+    BalanceMismatch {
+        account_number: String,
+        currency: String,
+        reported_balance: f64,
+        recomputed_balance: f64,
+    },
+    /// First transaction whose recorded balance does not follow from the ones before it
+    RunningBalanceMismatch {
+        account_number: String,
+        currency: String,
+        index: usize,
+        timestamp: u64,
+        expected_balance: f64,
+        recorded_balance: f64,
+    },
+    /// Negative or non-finite transaction amount
+    InvalidAmount {
+        account_number: String,
+        currency: String,
+        index: usize,
+        amount: f64,
+    },
+    /// Money sent between two reconciled accounts of the same currency that did not arrive, or the reverse
+    UnmatchedTransfer {
+        account_number: String,
+        counterparty_account_number: String,
+        currency: String,
+        sent: f64,
+        received: f64,
+    },
+    /// The balances of a currency do not add up to the totals of its histories
+    CurrencyTotalMismatch {
+        currency: String,
+        reported_balance: f64,
+        recomputed_balance: f64,
+    },
+}
+
+/// Machine-readable outcome of a reconciliation run
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReconciliationReport {
+    /// Seconds since the Unix epoch
+    pub generated_at: u64,
+    pub accounts: Vec<AccountReconciliation>,
+    pub currencies: BTreeMap<String, CurrencyTotals>,
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+impl ReconciliationReport {
+    pub fn is_balanced(&self) -> bool {
+        self.discrepancies.is_empty()
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to encode reconciliation report: {}", e))
+    }
+}
+
+/// Reconciles a set of accounts. Each balance is recomputed from an empty account through its
+/// history, so accounts must have been opened empty or restored with their full history.
+/// Transfers are matched between accounts of the set; transfers to accounts outside it and
+/// exchanges between currencies are not.
+pub fn reconcile(accounts: &[&dyn Account]) -> ReconciliationReport {
+    let mut report = ReconciliationReport {
+        generated_at: current_timestamp(),
+        accounts: Vec::new(),
+        currencies: BTreeMap::new(),
+        discrepancies: Vec::new(),
+    };
+    // (currency, sender, recipient) -> amount sent, amount received
+    let mut transfers: BTreeMap<(String, String, String), (f64, f64)> = BTreeMap::new();
+    let currency_of: BTreeMap<&str, &str> = accounts.iter()
+        .map(|account| (account.get_account_number(), account.get_currency()))
+        .collect();
+
+    for account in accounts.iter() {
+        let account_number = account.get_account_number().to_string();
+        let currency = account.get_currency().to_string();
+        let totals = report.currencies.entry(currency.clone()).or_default();
+        let mut running_balance = 0.0;
+        let mut running_balance_reported = false;
+
+        for (index, transaction) in account.get_history().iter().enumerate() {
+            if !transaction.amount.is_finite() || transaction.amount < 0.0 {
+                report.discrepancies.push(Discrepancy::InvalidAmount {
+                    account_number: account_number.clone(),
+                    currency: currency.clone(),
+                    index,
+                    amount: transaction.amount,
+                });
+            }
+            running_balance += transaction.signed_amount();
+            if !running_balance_reported && !amounts_match(running_balance, transaction.balance_after) {
+                running_balance_reported = true;
+                report.discrepancies.push(Discrepancy::RunningBalanceMismatch {
+                    account_number: account_number.clone(),
+                    currency: currency.clone(),
+                    index,
+                    timestamp: transaction.timestamp,
+                    expected_balance: running_balance,
+                    recorded_balance: transaction.balance_after,
+                });
+            }
+
+            match transaction.kind {
+                TransactionKind::Deposit => totals.deposits += transaction.amount,
+                TransactionKind::Withdrawal => totals.withdrawals += transaction.amount,
+                TransactionKind::TransferIn => totals.transfers_in += transaction.amount,
+                TransactionKind::TransferOut => totals.transfers_out += transaction.amount,
+                TransactionKind::Fee => totals.fees += transaction.amount,
+                TransactionKind::Interest => totals.interest += transaction.amount,
+            }
+
+            let counterparty = match &transaction.counterparty_account_number {
+                Some(counterparty) if currency_of.get(counterparty.as_str()) == Some(&currency.as_str()) => counterparty.clone(),
+                _ => continue,
+            };
+            // keyed by the unordered pair, so a transfer and its reversal cancel out
+            let (first, second) = if account_number <= counterparty {
+                (account_number.clone(), counterparty)
+            } else {
+                (counterparty, account_number.clone())
+            };
+            let entry = transfers.entry((currency.clone(), first, second)).or_insert((0.0, 0.0));
+            match transaction.kind {
+                TransactionKind::TransferOut => entry.0 += transaction.amount,
+                TransactionKind::TransferIn => entry.1 += transaction.amount,
+                _ => {}
+            }
+        }
+
+        let reported_balance = account.get_balance();
+        if !amounts_match(running_balance, reported_balance) {
+            report.discrepancies.push(Discrepancy::BalanceMismatch {
+                account_number: account_number.clone(),
+                currency: currency.clone(),
+                reported_balance,
+                recomputed_balance: running_balance,
+            });
+        }
+        totals.accounts += 1;
+        totals.reported_balance += reported_balance;
+        totals.recomputed_balance += running_balance;
+        report.accounts.push(AccountReconciliation {
+            account_number,
+            account_type: account.get_account_type(),
+            currency,
+            transactions: account.get_history().len(),
+            reported_balance,
+            recomputed_balance: running_balance,
+        });
+    }
+
+    for ((currency, account_number, counterparty_account_number), (sent, received)) in transfers {
+        if !amounts_match(sent, received) {
+            report.discrepancies.push(Discrepancy::UnmatchedTransfer {
+                account_number,
+                counterparty_account_number,
+                currency,
+                sent,
+                received,
+            });
+        }
+    }
+    for (currency, totals) in report.currencies.iter() {
+        if !amounts_match(totals.reported_balance, totals.recomputed_balance) {
+            report.discrepancies.push(Discrepancy::CurrencyTotalMismatch {
+                currency: currency.clone(),
+                reported_balance: totals.reported_balance,
+                recomputed_balance: totals.recomputed_balance,
+            });
+        }
+    }
+    report
+}
+
+/// Reconciles the open accounts of every wallet
+pub fn reconcile_wallets(wallets: &[&dyn Wallet]) -> ReconciliationReport {
+    let accounts: Vec<&dyn Account> = wallets.iter().flat_map(|wallet| wallet.get_accounts()).collect();
+    reconcile(&accounts)
+}
+
+/// Reconciles every wallet registered with a service
+pub fn reconcile_service(service: &WalletService) -> ReconciliationReport {
+    let wallets: Vec<&dyn Wallet> = service.list_wallets()
+        .iter()
+        .filter_map(|summary| service.wallet(&summary.wallet_id))
+        .collect();
+    reconcile_wallets(&wallets)
+}
+
+fn amounts_match(a: f64, b: f64) -> bool {
+    (a - b).abs() <= RECONCILIATION_TOLERANCE
+}
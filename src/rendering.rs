@@ -0,0 +1,207 @@
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use crate::accounts::{Account, AccountType};
+use crate::wallets::{Wallet, WalletType};
+
+/// Output format of a rendered summary
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderFormat {
+    /// Aligned plain-text table
+    Text,
+    Json,
+    Markdown,
+}
+
+impl FromStr for RenderFormat {
+    type Err = String;
+
+    /// Parses `text`, `json` or `markdown` (or `md`), ignoring case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(RenderFormat::Text),
+            "json" => Ok(RenderFormat::Json),
+            "markdown" | "md" => Ok(RenderFormat::Markdown),
+            _ => Err(format!("Unknown render format: {}", s)),
+        }
+    }
+}
+
+/// Formats a value as text, JSON or Markdown
+pub trait Render {
+    fn render(&self, format: RenderFormat) -> String;
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AccountSummary {
+    pub account_number: String,
+    pub account_type: AccountType,
+    pub currency: String,
+    pub balance: f64,
+    pub overdraft_limit: f64,
+}
+
+impl AccountSummary {
+    pub fn from_account(account: &dyn Account) -> Self {
+        AccountSummary {
+            account_number: account.get_account_number().to_string(),
+            account_type: account.get_account_type(),
+            currency: account.get_currency().to_string(),
+            balance: account.get_balance(),
+            overdraft_limit: account.get_overdraft_limit(),
+        }
+    }
+
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("Account Number", self.account_number.clone()),
+            ("Account Type", self.account_type.to_string()),
+            ("Currency", self.currency.clone()),
+            ("Balance", amount(self.balance)),
+            ("Overdraft Limit", amount(self.overdraft_limit)),
+        ]
+    }
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.account_number.clone(),
+            self.account_type.to_string(),
+            self.currency.clone(),
+            amount(self.balance),
+            amount(self.overdraft_limit),
+        ]
+    }
+}
+
+impl Render for AccountSummary {
+    fn render(&self, format: RenderFormat) -> String {
+        match format {
+            RenderFormat::Text => text_fields(&self.fields()),
+            RenderFormat::Json => to_json(self),
+            RenderFormat::Markdown => {
+                let rows: Vec<Vec<String>> = self.fields().into_iter().map(|(name, value)| vec![name.to_string(), value]).collect();
+                format!("### Account {}\n\n{}", self.account_number, markdown_table(&["Field", "Value"], &[false, false], &rows))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WalletSummary {
+    pub wallet_id: String,
+    pub wallet_type: WalletType,
+    pub accounts: Vec<AccountSummary>,
+}
+
+impl WalletSummary {
+    pub fn from_wallet(wallet: &dyn Wallet) -> Self {
+        WalletSummary {
+            wallet_id: wallet.get_wallet_id().to_string(),
+            wallet_type: wallet.get_wallet_type(),
+            accounts: wallet.get_accounts().into_iter().map(AccountSummary::from_account).collect(),
+        }
+    }
+}
+
+const ACCOUNT_COLUMNS: [&str; 5] = ["Account Number", "Type", "Currency", "Balance", "Overdraft Limit"];
+const ACCOUNT_COLUMNS_RIGHT_ALIGNED: [bool; 5] = [false, false, false, true, true];
+
+impl Render for WalletSummary {
+    fn render(&self, format: RenderFormat) -> String {
+        let rows: Vec<Vec<String>> = self.accounts.iter().map(|account| account.row()).collect();
+        match format {
+            RenderFormat::Text => {
+                let mut text = text_fields(&[
+                    ("Wallet ID", self.wallet_id.clone()),
+                    ("Wallet Type", self.wallet_type.to_string()),
+                ]);
+                text.push('\n');
+                if rows.is_empty() {
+                    text.push_str("No accounts\n");
+                } else {
+                    text.push_str(&text_table(&ACCOUNT_COLUMNS, &ACCOUNT_COLUMNS_RIGHT_ALIGNED, &rows));
+                }
+                text
+            }
+            RenderFormat::Json => to_json(self),
+            RenderFormat::Markdown => {
+                let mut markdown = format!("### Wallet {} ({})\n\n", self.wallet_id, self.wallet_type);
+                if rows.is_empty() {
+                    markdown.push_str("No accounts\n");
+                } else {
+                    markdown.push_str(&markdown_table(&ACCOUNT_COLUMNS, &ACCOUNT_COLUMNS_RIGHT_ALIGNED, &rows));
+                }
+                markdown
+            }
+        }
+    }
+}
+
+/// Several wallets, one after the other; a JSON array
+impl Render for [WalletSummary] {
+    fn render(&self, format: RenderFormat) -> String {
+        match format {
+            RenderFormat::Json => to_json(&self),
+            RenderFormat::Text | RenderFormat::Markdown => self.iter()
+                .map(|wallet| wallet.render(format))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+fn amount(value: f64) -> String {
+    format!("{:.2}", value)
+}
+
+fn to_json<T: Serialize + ?Sized>(value: &T) -> String {
+    match serde_json::to_string_pretty(value) {
+        Ok(json) => json + "\n",
+        Err(e) => json!({ "error": format!("Failed to encode summary: {}", e) }).to_string() + "\n",
+    }
+}
+
+/// Name and value pairs, with the values lined up
+fn text_fields(fields: &[(&str, String)]) -> String {
+    let width = fields.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    fields.iter()
+        .map(|(name, value)| format!("{:<width$}  {}\n", name, value, width = width))
+        .collect()
+}
+
+/// Columns padded to their widest cell and separated by two spaces, under a dashed rule
+fn text_table(headers: &[&str], right_aligned: &[bool], rows: &[Vec<String>]) -> String {
+    let widths: Vec<usize> = headers.iter()
+        .enumerate()
+        .map(|(column, header)| rows.iter().map(|row| row[column].chars().count()).fold(header.len(), usize::max))
+        .collect();
+    let line = |cells: Vec<String>| -> String {
+        let padded: Vec<String> = cells.iter()
+            .enumerate()
+            .map(|(column, cell)| {
+                if right_aligned[column] {
+                    format!("{:>width$}", cell, width = widths[column])
+                } else {
+                    format!("{:<width$}", cell, width = widths[column])
+                }
+            })
+            .collect();
+        padded.join("  ").trim_end().to_string() + "\n"
+    };
+    let mut text = line(headers.iter().map(|header| header.to_string()).collect());
+    text.push_str(&line(widths.iter().map(|width| "-".repeat(*width)).collect()));
+    for row in rows {
+        text.push_str(&line(row.clone()));
+    }
+    text
+}
+
+fn markdown_table(headers: &[&str], right_aligned: &[bool], rows: &[Vec<String>]) -> String {
+    let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+    let mut markdown = line(headers.iter().map(|header| header.to_string()).collect());
+    markdown.push_str(&line(right_aligned.iter().map(|right| if *right { "---:" } else { "---" }.to_string()).collect()));
+    for row in rows {
+        markdown.push_str(&line(row.iter().map(|cell| cell.replace('|', "\\|")).collect()));
+    }
+    markdown
+}
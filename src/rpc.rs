@@ -0,0 +1,145 @@
+use std::io::{self, BufRead, Write};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use crate::service::{AddAccountRequest, AmountRequest, CreateWalletRequest, ServiceError, ServiceErrorKind, TransferRequest, WalletService};
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+/// Maps the kind of a service failure to an application error code
+pub fn error_code_for(kind: ServiceErrorKind) -> i64 {
+    match kind {
+        ServiceErrorKind::InvalidRequest => -32000,
+        ServiceErrorKind::NotFound => -32001,
+        ServiceErrorKind::Conflict => -32002,
+        ServiceErrorKind::InsufficientFunds => -32003,
+    }
+}
+
+#[derive(Deserialize)]
+struct CreateAccountParams {
+    wallet_id: String,
+    #[serde(flatten)]
+    request: AddAccountRequest,
+}
+
+#[derive(Deserialize)]
+struct AmountParams {
+    wallet_id: String,
+    #[serde(flatten)]
+    request: AmountRequest,
+}
+
+#[derive(Deserialize)]
+struct TransferParams {
+    from_wallet_id: String,
+    #[serde(flatten)]
+    request: TransferRequest,
+}
+
+#[derive(Deserialize)]
+struct WalletParams {
+    wallet_id: String,
+}
+
+#[derive(Deserialize)]
+struct BalanceParams {
+    wallet_id: String,
+    currency: String,
+}
+
+#[derive(Deserialize)]
+struct HistoryParams {
+    wallet_id: String,
+    account_number: String,
+}
+
+/// Handles one line of input, which holds a JSON-RPC 2.0 request or batch.
+/// Returns the encoded response, or `None` when there is nothing to answer (notifications).
+///
+/// Methods: `create_wallet`, `create_account`, `deposit`, `withdraw`, `transfer`, `balance`,
+/// `get_wallet`, `list_wallets` and `history`. Parameters are named like the fields of the
+/// matching `WalletService` requests, plus `wallet_id` (`from_wallet_id` for transfers).
+pub fn handle_line(service: &mut WalletService, line: &str) -> Option<String> {
+    let message: Value = match serde_json::from_str(line) {
+        Ok(message) => message,
+        Err(e) => return Some(error_response(Value::Null, PARSE_ERROR, &format!("Parse error: {}", e)).to_string()),
+    };
+    match message {
+        Value::Array(requests) if requests.is_empty() => {
+            Some(error_response(Value::Null, INVALID_REQUEST, "Empty batch").to_string())
+        }
+        Value::Array(requests) => {
+            let responses: Vec<Value> = requests.into_iter().filter_map(|request| handle_request(service, request)).collect();
+            if responses.is_empty() {
+                None
+            } else {
+                Some(Value::Array(responses).to_string())
+            }
+        }
+        request => handle_request(service, request).map(|response| response.to_string()),
+    }
+}
+
+/// Reads one request per line until end of input, writing and flushing one response per line
+pub fn serve<R: BufRead, W: Write>(service: &mut WalletService, reader: R, mut writer: W) -> io::Result<()> {
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = handle_line(service, &line) {
+            writeln!(writer, "{}", response)?;
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
+
+fn handle_request(service: &mut WalletService, request: Value) -> Option<Value> {
+    let id = request.get("id").cloned();
+    let method = match (request.get("jsonrpc").and_then(Value::as_str), request.get("method").and_then(Value::as_str)) {
+        (Some("2.0"), Some(method)) => method,
+        _ => return Some(error_response(id.unwrap_or(Value::Null), INVALID_REQUEST, "Invalid request")),
+    };
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match method {
+        "create_wallet" => call(params, |p: CreateWalletRequest| service.create_wallet(&p)),
+        "create_account" => call(params, |p: CreateAccountParams| service.add_account(&p.wallet_id, &p.request)),
+        "deposit" => call(params, |p: AmountParams| service.deposit(&p.wallet_id, &p.request)),
+        "withdraw" => call(params, |p: AmountParams| service.withdraw(&p.wallet_id, &p.request)),
+        "transfer" => call(params, |p: TransferParams| service.transfer(&p.from_wallet_id, &p.request)),
+        "balance" => call(params, |p: BalanceParams| service.balance(&p.wallet_id, &p.currency)),
+        "get_wallet" => call(params, |p: WalletParams| service.get_wallet(&p.wallet_id)),
+        "list_wallets" => Ok(json!(service.list_wallets())),
+        "history" => call(params, |p: HistoryParams| service.history(&p.wallet_id, &p.account_number)),
+        _ => Err((METHOD_NOT_FOUND, format!("Method not found: {}", method))),
+    };
+
+    // requests without an id are notifications and get no response
+    let id = id?;
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "result": result, "id": id }),
+        Err((code, message)) => error_response(id, code, &message),
+    })
+}
+
+fn call<P, T, F>(params: Value, f: F) -> Result<Value, (i64, String)>
+where
+    P: DeserializeOwned,
+    T: Serialize,
+    F: FnOnce(P) -> Result<T, ServiceError>,
+{
+    let params = serde_json::from_value(params).map_err(|e| (INVALID_PARAMS, format!("Invalid params: {}", e)))?;
+    let result = f(params).map_err(|e| (error_code_for(e.kind), e.message))?;
+    serde_json::to_value(result).map_err(|e| (INTERNAL_ERROR, format!("Failed to encode result: {}", e)))
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "error": { "code": code, "message": message }, "id": id })
+}
@@ -0,0 +1,466 @@
+use std::fs;
+use std::path::Path;
+use rand::distr::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::customer::Customer;
+use crate::import::parse_csv;
+use crate::transactions::current_timestamp;
+use crate::wallets::TransferResponse;
+
+/// Default similarity from which a name is treated as a match
+pub const DEFAULT_NAME_MATCH_THRESHOLD: f64 = 0.9;
+
+/// Default similarity from which a name is treated as matching an alias
+pub const DEFAULT_ALIAS_MATCH_THRESHOLD: f64 = 0.95;
+
+/// Sanctioned person or organisation
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WatchListEntry {
+    pub entry_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    #[serde(default)]
+    pub account_numbers: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WatchList {
+    entries: Vec<WatchListEntry>,
+}
+
+impl WatchList {
+    pub fn new() -> Self {
+        WatchList { entries: Vec::new() }
+    }
+
+    /// Loads a `.json` or `.csv` file, chosen by extension
+    pub fn load(path: &str) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read watch list {}: {}", path, e))?;
+        match Path::new(path).extension().and_then(|extension| extension.to_str()).map(|e| e.to_ascii_lowercase()) {
+            Some(extension) if extension == "json" => Self::from_json(&contents),
+            Some(extension) if extension == "csv" => Self::from_csv(&contents),
+            _ => Err(format!("Unsupported watch list format: {}", path)),
+        }
+    }
+
+    /// Parses a JSON array of entries
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let entries = serde_json::from_str(json).map_err(|e| format!("Invalid watch list: {}", e))?;
+        Ok(WatchList { entries })
+    }
+
+    /// Parses CSV with the header `entry_id,name,aliases,account_numbers`, where
+    /// aliases and account numbers are separated by `;`
+    pub fn from_csv(csv: &str) -> Result<Self, String> {
+        let mut records = parse_csv(csv).into_iter();
+        let header = match records.next() {
+            Some((_, Ok(header))) => header,
+            Some((line, Err(error))) => return Err(format!("Invalid watch list at line {}: {}", line, error)),
+            None => return Ok(WatchList::new()),
+        };
+        let column = |name: &str| {
+            header.iter()
+                .position(|column| column.trim() == name)
+                .ok_or(format!("Watch list is missing the {} column", name))
+        };
+        let (id_index, name_index) = (column("entry_id")?, column("name")?);
+        let (aliases_index, account_numbers_index) = (column("aliases").ok(), column("account_numbers").ok());
+
+        let mut watch_list = WatchList::new();
+        for (line, record) in records {
+            let fields = record.map_err(|error| format!("Invalid watch list at line {}: {}", line, error))?;
+            let field = |index: usize| fields.get(index).map(|f| f.trim()).unwrap_or("");
+            let list = |index: Option<usize>| -> Vec<String> {
+                index.map(field).unwrap_or("")
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            };
+            if fields.iter().all(|f| f.trim().is_empty()) {
+                continue;
+            }
+            if field(name_index).is_empty() {
+                return Err(format!("Watch list entry at line {} has no name", line));
+            }
+            watch_list.add_entry(WatchListEntry {
+                entry_id: field(id_index).to_string(),
+                name: field(name_index).to_string(),
+                aliases: list(aliases_index),
+                account_numbers: list(account_numbers_index),
+            });
+        }
+        Ok(watch_list)
+    }
+
+    pub fn add_entry(&mut self, entry: WatchListEntry) {
+        self.entries.push(entry);
+    }
+
+    pub fn get_entries(&self) -> &[WatchListEntry] {
+        &self.entries
+    }
+}
+
+/// Enum defining the side of a transfer a match was found on
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Party {
+    Sender,
+    Recipient,
+}
+
+/// Enum defining what a screened value matched
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MatchField {
+    Name,
+    Alias,
+    AccountNumber,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScreeningMatch {
+    pub entry_id: String,
+    pub entry_name: String,
+    pub party: Party,
+    pub field: MatchField,
+    pub screened_value: String,
+    pub matched_value: String,
+    /// Similarity between 0 and 1; account numbers only match exactly
+    pub score: f64,
+}
+
+/// Enum defining the lifecycle of a held transfer
+#[derive(Clone, Debug, PartialEq)]
+pub enum HoldStatus {
+    Held,
+    Released,
+    Rejected,
+    /// Released by a reviewer but refused by the wallet
+    Failed,
+}
+
+/// Transfer stopped by screening until someone reviews it
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeldTransfer {
+    pub hold_id: String,
+    pub sender_name: String,
+    pub sender_wallet_id: String,
+    pub sender_account_number: Option<String>,
+    pub recipient_name: String,
+    pub recipient_wallet_id: String,
+    pub recipient_account_number: Option<String>,
+    pub currency: String,
+    pub amount: f64,
+    pub matches: Vec<ScreeningMatch>,
+    pub status: HoldStatus,
+    pub reviewed_by: Option<String>,
+    /// Seconds since the Unix epoch
+    pub created_at: u64,
+    pub error_message: Option<String>,
+}
+
+/// Enum defining the result of a screened transfer
+pub enum ScreenedTransfer {
+    /// Nothing matched and the transfer went to the wallet, successfully or not
+    Executed(TransferResponse),
+    Held(HeldTransfer),
+}
+
+/// Screens transfers against a watch list before making them. Names are compared after
+/// lower-casing and removing punctuation, using the best Jaro-Winkler similarity of the names
+/// as written and with their words sorted, so "Doe, John" matches "John Doe".
+pub struct SanctionsScreener {
+    watch_list: WatchList,
+    name_match_threshold: f64,
+    alias_match_threshold: f64,
+    held_transfers: Vec<HeldTransfer>,
+}
+
+impl SanctionsScreener {
+    pub fn new(watch_list: WatchList) -> Self {
+        SanctionsScreener {
+            watch_list,
+            name_match_threshold: DEFAULT_NAME_MATCH_THRESHOLD,
+            alias_match_threshold: DEFAULT_ALIAS_MATCH_THRESHOLD,
+            held_transfers: Vec::new(),
+        }
+    }
+
+    pub fn get_watch_list(&self) -> &WatchList {
+        &self.watch_list
+    }
+
+    pub fn set_watch_list(&mut self, watch_list: WatchList) {
+        self.watch_list = watch_list;
+    }
+
+    pub fn set_name_match_threshold(&mut self, threshold: f64) -> Result<(), String> {
+        self.name_match_threshold = check_threshold(threshold)?;
+        Ok(())
+    }
+
+    pub fn set_alias_match_threshold(&mut self, threshold: f64) -> Result<(), String> {
+        self.alias_match_threshold = check_threshold(threshold)?;
+        Ok(())
+    }
+
+    /// Matches of one party's name and account numbers against the watch list
+    pub fn screen(&self, party: Party, name: &str, account_numbers: &[&str]) -> Vec<ScreeningMatch> {
+        let mut matches = Vec::new();
+        for entry in self.watch_list.get_entries() {
+            let candidates = std::iter::once((MatchField::Name, &entry.name, self.name_match_threshold))
+                .chain(entry.aliases.iter().map(|alias| (MatchField::Alias, alias, self.alias_match_threshold)));
+            let best = candidates
+                .map(|(field, candidate, threshold)| (field, candidate, threshold, name_similarity(name, candidate)))
+                .filter(|(_, _, threshold, score)| score >= threshold)
+                .max_by(|a, b| a.3.total_cmp(&b.3));
+            if let Some((field, candidate, _, score)) = best {
+                matches.push(ScreeningMatch {
+                    entry_id: entry.entry_id.clone(),
+                    entry_name: entry.name.clone(),
+                    party,
+                    field,
+                    screened_value: name.to_string(),
+                    matched_value: candidate.clone(),
+                    score,
+                });
+            }
+            matches.extend(Self::screen_entry_accounts(entry, party, account_numbers));
+        }
+        matches
+    }
+
+    /// Matches of one party's account numbers against the watch list
+    fn screen_account_numbers(&self, party: Party, account_numbers: &[&str]) -> Vec<ScreeningMatch> {
+        self.watch_list.get_entries().iter()
+            .flat_map(|entry| Self::screen_entry_accounts(entry, party, account_numbers))
+            .collect()
+    }
+
+    fn screen_entry_accounts(entry: &WatchListEntry, party: Party, account_numbers: &[&str]) -> Vec<ScreeningMatch> {
+        account_numbers.iter()
+            .filter(|account_number| entry.account_numbers.iter().any(|listed| listed.eq_ignore_ascii_case(account_number)))
+            .map(|account_number| ScreeningMatch {
+                entry_id: entry.entry_id.clone(),
+                entry_name: entry.name.clone(),
+                party,
+                field: MatchField::AccountNumber,
+                screened_value: account_number.to_string(),
+                matched_value: account_number.to_string(),
+                score: 1.0,
+            })
+            .collect()
+    }
+
+    /// Screens both customers and makes the transfer from the sender's wallet to the recipient's
+    /// wallet when nothing matches; otherwise holds it for review. The accounts screened are the
+    /// ones the wallets would use for the currency.
+    pub fn transfer(
+        &mut self,
+        sender: &mut Customer,
+        from_wallet_id: &str,
+        recipient: &mut Customer,
+        to_wallet_id: &str,
+        currency: &str,
+        amount: f64,
+    ) -> Result<ScreenedTransfer, String> {
+        let (sender_account_number, recipient_account_number) = Self::resolve_accounts(sender, from_wallet_id, recipient, to_wallet_id, currency)?;
+        let matches = self.screen_parties(sender, sender_account_number.as_deref(), recipient, recipient_account_number.as_deref());
+        if matches.is_empty() {
+            return Ok(ScreenedTransfer::Executed(sender.transfer_to_customer(from_wallet_id, recipient, to_wallet_id, currency, amount)));
+        }
+
+        let held_transfer = HeldTransfer {
+            hold_id: format!(
+                "Hold-{}",
+                rand::rng()
+                    .sample_iter(&Alphanumeric)
+                    .take(10)
+                    .map(char::from)
+                    .collect::<String>()
+            ),
+            sender_name: sender.get_name().to_string(),
+            sender_wallet_id: from_wallet_id.to_string(),
+            sender_account_number,
+            recipient_name: recipient.get_name().to_string(),
+            recipient_wallet_id: to_wallet_id.to_string(),
+            recipient_account_number,
+            currency: currency.to_string(),
+            amount,
+            matches,
+            status: HoldStatus::Held,
+            reviewed_by: None,
+            created_at: current_timestamp(),
+            error_message: None,
+        };
+        self.held_transfers.push(held_transfer.clone());
+        Ok(ScreenedTransfer::Held(held_transfer))
+    }
+
+    /// Makes a held transfer after review, between the wallets it was held for. The accounts the
+    /// wallets use by then are screened again: one that matches the watch list and was not part of
+    /// the review adds its matches to the hold and keeps the transfer held.
+    pub fn release(&mut self, hold_id: &str, reviewer: &str, sender: &mut Customer, recipient: &mut Customer) -> Result<TransferResponse, String> {
+        let index = self.find_held_index(hold_id)?;
+        let held_transfer = &self.held_transfers[index];
+        let (from_wallet_id, to_wallet_id) = (held_transfer.sender_wallet_id.clone(), held_transfer.recipient_wallet_id.clone());
+        let (currency, amount) = (held_transfer.currency.clone(), held_transfer.amount);
+        let (sender_account_number, recipient_account_number) = Self::resolve_accounts(sender, &from_wallet_id, recipient, &to_wallet_id, &currency)?;
+
+        let reviewed: Vec<(Party, String)> = held_transfer.matches.iter()
+            .map(|m| (m.party, m.screened_value.clone()))
+            .collect();
+        let mut new_matches = self.screen_account_numbers(Party::Sender, &sender_account_number.as_deref().into_iter().collect::<Vec<_>>());
+        new_matches.extend(self.screen_account_numbers(Party::Recipient, &recipient_account_number.as_deref().into_iter().collect::<Vec<_>>()));
+        new_matches.retain(|m| !reviewed.contains(&(m.party, m.screened_value.clone())));
+        if !new_matches.is_empty() {
+            let accounts: Vec<String> = new_matches.iter().map(|m| m.screened_value.clone()).collect();
+            self.held_transfers[index].matches.extend(new_matches);
+            return Err(format!("Transfer {} now uses accounts on the watch list: {}", hold_id, accounts.join(", ")));
+        }
+
+        let response = sender.transfer_to_customer(&from_wallet_id, recipient, &to_wallet_id, &currency, amount);
+        let held_transfer = &mut self.held_transfers[index];
+        held_transfer.reviewed_by = Some(reviewer.to_string());
+        held_transfer.sender_account_number = sender_account_number;
+        held_transfer.recipient_account_number = recipient_account_number;
+        if response.is_successful {
+            held_transfer.status = HoldStatus::Released;
+        } else {
+            held_transfer.status = HoldStatus::Failed;
+            held_transfer.error_message = response.error_message.clone();
+        }
+        Ok(response)
+    }
+
+    /// Refuses a held transfer for good
+    pub fn reject(&mut self, hold_id: &str, reviewer: &str) -> Result<&HeldTransfer, String> {
+        let index = self.find_held_index(hold_id)?;
+        let held_transfer = &mut self.held_transfers[index];
+        held_transfer.status = HoldStatus::Rejected;
+        held_transfer.reviewed_by = Some(reviewer.to_string());
+        Ok(held_transfer)
+    }
+
+    pub fn get_held_transfer(&self, hold_id: &str) -> Option<&HeldTransfer> {
+        self.held_transfers.iter().find(|t| t.hold_id == hold_id)
+    }
+
+    /// Transfers still waiting for review, oldest first
+    pub fn get_review_queue(&self) -> Vec<&HeldTransfer> {
+        self.held_transfers.iter().filter(|t| t.status == HoldStatus::Held).collect()
+    }
+
+    /// Returns every held transfer, whatever its status
+    pub fn get_held_transfers(&self) -> &[HeldTransfer] {
+        &self.held_transfers
+    }
+
+    /// Numbers of the accounts the two wallets would use for the currency
+    fn resolve_accounts(
+        sender: &Customer,
+        from_wallet_id: &str,
+        recipient: &Customer,
+        to_wallet_id: &str,
+        currency: &str,
+    ) -> Result<(Option<String>, Option<String>), String> {
+        let account_number = |customer: &Customer, wallet_id: &str| {
+            customer.get_wallet(wallet_id)
+                .map(|wallet| wallet.get_account_by_currency(currency).map(|acc| acc.get_account_number().to_string()))
+                .ok_or(format!("Customer {} has no wallet with id: {}", customer.get_customer_id(), wallet_id))
+        };
+        Ok((account_number(sender, from_wallet_id)?, account_number(recipient, to_wallet_id)?))
+    }
+
+    fn screen_parties(
+        &self,
+        sender: &Customer,
+        sender_account_number: Option<&str>,
+        recipient: &Customer,
+        recipient_account_number: Option<&str>,
+    ) -> Vec<ScreeningMatch> {
+        let mut matches = self.screen(Party::Sender, sender.get_name(), &sender_account_number.into_iter().collect::<Vec<_>>());
+        matches.extend(self.screen(Party::Recipient, recipient.get_name(), &recipient_account_number.into_iter().collect::<Vec<_>>()));
+        matches
+    }
+
+    fn find_held_index(&self, hold_id: &str) -> Result<usize, String> {
+        let index = self.held_transfers.iter()
+            .position(|t| t.hold_id == hold_id)
+            .ok_or(format!("No held transfer found with id: {}", hold_id))?;
+        if self.held_transfers[index].status != HoldStatus::Held {
+            return Err(format!("Transfer {} was already reviewed: {:?}", hold_id, self.held_transfers[index].status));
+        }
+        Ok(index)
+    }
+}
+
+/// Similarity between 0 and 1 of two names, ignoring case, punctuation and word order
+pub fn name_similarity(a: &str, b: &str) -> f64 {
+    let (a, b) = (normalize_name(a), normalize_name(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let sorted = |name: &str| {
+        let mut words: Vec<&str> = name.split(' ').collect();
+        words.sort_unstable();
+        words.join(" ")
+    };
+    jaro_winkler(&a, &b).max(jaro_winkler(&sorted(&a), &sorted(&b)))
+}
+
+fn normalize_name(name: &str) -> String {
+    name.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    if a == b {
+        return 1.0;
+    }
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut a_matched = vec![false; a.len()];
+    let mut b_matched = vec![false; b.len()];
+    let mut matches = 0;
+    for (i, c) in a.iter().enumerate() {
+        let start = i.saturating_sub(window);
+        let end = (i + window + 1).min(b.len());
+        for j in start..end {
+            if !b_matched[j] && b[j] == *c {
+                a_matched[i] = true;
+                b_matched[j] = true;
+                matches += 1;
+                break;
+            }
+        }
+    }
+    if matches == 0 {
+        return 0.0;
+    }
+
+    let a_sequence = a.iter().zip(a_matched.iter()).filter(|(_, matched)| **matched).map(|(c, _)| c);
+    let b_sequence = b.iter().zip(b_matched.iter()).filter(|(_, matched)| **matched).map(|(c, _)| c);
+    let transpositions = a_sequence.zip(b_sequence).filter(|(x, y)| x != y).count() / 2;
+
+    let m = matches as f64;
+    let jaro = (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64) / m) / 3.0;
+    let prefix = a.iter().zip(b.iter()).take(4).take_while(|(x, y)| x == y).count();
+    jaro + prefix as f64 * 0.1 * (1.0 - jaro)
+}
+
+fn check_threshold(threshold: f64) -> Result<f64, String> {
+    if threshold > 0.0 && threshold <= 1.0 {
+        Ok(threshold)
+    } else {
+        Err("Match threshold must be above 0 and at most 1".to_string())
+    }
+}
@@ -0,0 +1,144 @@
+use std::net::SocketAddr;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::json;
+use tiny_http::{Header, Response, Server};
+use crate::service::{ServiceError, ServiceErrorKind, WalletService};
+
+/// Status code and JSON body produced for a request
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// Maps the kind of a service failure to an HTTP status code
+pub fn status_for(kind: ServiceErrorKind) -> u16 {
+    match kind {
+        ServiceErrorKind::InvalidRequest => 400,
+        ServiceErrorKind::NotFound => 404,
+        ServiceErrorKind::Conflict => 409,
+        ServiceErrorKind::InsufficientFunds => 422,
+    }
+}
+
+/// Routes a request to the wallet service.
+///
+/// | Method | Path                                      | Body                                   |
+/// |--------|-------------------------------------------|----------------------------------------|
+/// | GET    | /wallets                                  |                                        |
+/// | POST   | /wallets                                  | `CreateWalletRequest`                  |
+/// | GET    | /wallets/{id}                             |                                        |
+/// | POST   | /wallets/{id}/accounts                    | `AddAccountRequest`                    |
+/// | POST   | /wallets/{id}/deposit                     | `AmountRequest`                        |
+/// | POST   | /wallets/{id}/withdraw                    | `AmountRequest`                        |
+/// | POST   | /wallets/{id}/transfer                    | `TransferRequest`                      |
+/// | GET    | /wallets/{id}/balance?currency=USD        |                                        |
+/// | GET    | /wallets/{id}/accounts/{number}/history   |                                        |
+pub fn handle_request(service: &mut WalletService, method: &str, url: &str, body: &str) -> HttpResponse {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    match (method, segments.as_slice()) {
+        ("GET", ["wallets"]) => ok(200, &service.list_wallets()),
+        ("POST", ["wallets"]) => match parse_body(body) {
+            Ok(request) => reply(201, service.create_wallet(&request)),
+            Err(response) => response,
+        },
+        ("GET", ["wallets", wallet_id]) => reply(200, service.get_wallet(wallet_id)),
+        ("POST", ["wallets", wallet_id, "accounts"]) => match parse_body(body) {
+            Ok(request) => reply(201, service.add_account(wallet_id, &request)),
+            Err(response) => response,
+        },
+        ("POST", ["wallets", wallet_id, "deposit"]) => match parse_body(body) {
+            Ok(request) => reply(200, service.deposit(wallet_id, &request)),
+            Err(response) => response,
+        },
+        ("POST", ["wallets", wallet_id, "withdraw"]) => match parse_body(body) {
+            Ok(request) => reply(200, service.withdraw(wallet_id, &request)),
+            Err(response) => response,
+        },
+        ("POST", ["wallets", wallet_id, "transfer"]) => match parse_body(body) {
+            Ok(request) => reply(200, service.transfer(wallet_id, &request)),
+            Err(response) => response,
+        },
+        ("GET", ["wallets", wallet_id, "balance"]) => match query_param(query, "currency") {
+            Some(currency) => reply(200, service.balance(wallet_id, currency)),
+            None => error(400, "Missing query parameter: currency"),
+        },
+        ("GET", ["wallets", wallet_id, "accounts", account_number, "history"]) => {
+            reply(200, service.history(wallet_id, account_number))
+        }
+        (_, ["wallets"])
+        | (_, ["wallets", _])
+        | (_, ["wallets", _, "accounts" | "deposit" | "withdraw" | "transfer" | "balance"])
+        | (_, ["wallets", _, "accounts", _, "history"]) => error(405, &format!("Method {} not allowed on {}", method, path)),
+        _ => error(404, &format!("No route for {}", path)),
+    }
+}
+
+/// HTTP server exposing a `WalletService` as JSON endpoints
+pub struct WalletServer {
+    server: Server,
+}
+
+impl WalletServer {
+    /// Binds to an address such as `127.0.0.1:8080`; port 0 picks a free port
+    pub fn bind(address: &str) -> Result<Self, String> {
+        let server = Server::http(address).map_err(|e| format!("Failed to bind {}: {}", address, e))?;
+        Ok(WalletServer { server })
+    }
+
+    pub fn local_address(&self) -> Option<SocketAddr> {
+        self.server.server_addr().to_ip()
+    }
+
+    /// Serves requests one at a time until the process exits
+    pub fn run(self, mut service: WalletService) {
+        let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]).unwrap();
+        for mut request in self.server.incoming_requests() {
+            let mut body = String::new();
+            let response = match request.as_reader().read_to_string(&mut body) {
+                Ok(_) => handle_request(&mut service, request.method().as_str(), request.url(), &body),
+                Err(e) => error(400, &format!("Failed to read request body: {}", e)),
+            };
+            let http_response = Response::from_string(response.body)
+                .with_status_code(response.status)
+                .with_header(content_type.clone());
+            // the client may have gone away; there is nobody left to tell
+            let _ = request.respond(http_response);
+        }
+    }
+}
+
+fn parse_body<T: DeserializeOwned>(body: &str) -> Result<T, HttpResponse> {
+    serde_json::from_str(body).map_err(|e| error(400, &format!("Invalid JSON body: {}", e)))
+}
+
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+fn reply<T: Serialize>(status: u16, result: Result<T, ServiceError>) -> HttpResponse {
+    match result {
+        Ok(value) => ok(status, &value),
+        Err(service_error) => error(status_for(service_error.kind), &service_error.message),
+    }
+}
+
+fn ok<T: Serialize>(status: u16, value: &T) -> HttpResponse {
+    match serde_json::to_string(value) {
+        Ok(body) => HttpResponse { status, body },
+        Err(e) => error(500, &format!("Failed to encode response: {}", e)),
+    }
+}
+
+fn error(status: u16, message: &str) -> HttpResponse {
+    HttpResponse {
+        status,
+        body: json!({ "error": message }).to_string(),
+    }
+}
@@ -0,0 +1,492 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use crate::account_number::AccountNumber;
+use crate::accounts::{Account, AccountType};
+use crate::basic_account::BasicAccount;
+use crate::basic_wallet::BasicWallet;
+use crate::multi_currency_wallet::MultiCurrencyWallet;
+use crate::premium_account::PremiumAccount;
+pub use crate::rendering::{AccountSummary, WalletSummary};
+use crate::transactions::Transaction;
+use crate::wallets::{Wallet, WalletType};
+
+/// Enum defining the broad reasons a service request can fail
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ServiceErrorKind {
+    InvalidRequest,
+    NotFound,
+    Conflict,
+    InsufficientFunds,
+}
+
+/// Failure of a service request, classified so front ends can map it to a status code
+#[derive(Clone, Debug, PartialEq)]
+pub struct ServiceError {
+    pub kind: ServiceErrorKind,
+    pub message: String,
+}
+
+impl ServiceError {
+    pub fn new(kind: ServiceErrorKind, message: &str) -> Self {
+        ServiceError {
+            kind,
+            message: message.to_string(),
+        }
+    }
+
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BalanceSummary {
+    pub wallet_id: String,
+    pub currency: String,
+    pub balance: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransferSummary {
+    pub sender_wallet_id: String,
+    pub sender_account_number: String,
+    pub recipient_wallet_id: String,
+    pub recipient_account_number: String,
+    pub currency: String,
+    pub amount: f64,
+}
+
+/// Request to create a wallet. Basic wallets are created with their single account.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CreateWalletRequest {
+    /// `basic` or `multi`
+    #[serde(rename = "type")]
+    pub wallet_type: String,
+    pub currency: Option<String>,
+    pub account_type: Option<String>,
+    pub overdraft_limit: Option<f64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AddAccountRequest {
+    /// `basic` or `premium`
+    pub account_type: String,
+    pub currency: String,
+    pub overdraft_limit: Option<f64>,
+}
+
+/// Deposit or withdrawal, against the default account of the currency unless an account number is given
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AmountRequest {
+    pub currency: String,
+    pub amount: f64,
+    pub account_number: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TransferRequest {
+    pub to_wallet_id: String,
+    pub currency: String,
+    pub amount: f64,
+}
+
+/// Account as kept in a state file
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StoredAccount {
+    pub account_number: String,
+    pub account_type: AccountType,
+    pub currency: String,
+    pub balance: f64,
+    pub overdraft_limit: f64,
+    /// Whether the account is the default of its currency in its wallet
+    pub is_default: bool,
+    pub history: Vec<Transaction>,
+}
+
+/// Wallet as kept in a state file. Closed accounts, quotes and exchange history are not kept.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StoredWallet {
+    pub wallet_id: String,
+    pub wallet_type: WalletType,
+    pub accounts: Vec<StoredAccount>,
+}
+
+/// Registry of wallets addressed by wallet id, shared by the HTTP server and the CLI
+pub struct WalletService {
+    wallets: Vec<Box<dyn Wallet>>,
+}
+
+impl WalletService {
+    pub fn new() -> Self {
+        WalletService {
+            wallets: Vec::new(),
+        }
+    }
+
+    /// Registers an existing wallet
+    pub fn add_wallet(&mut self, wallet: Box<dyn Wallet>) -> Result<WalletSummary, ServiceError> {
+        if self.find_wallet_index(wallet.get_wallet_id()).is_ok() {
+            return Err(ServiceError::new(
+                ServiceErrorKind::Conflict,
+                &format!("Wallet {} already exists", wallet.get_wallet_id()),
+            ));
+        }
+        if let Some(account) = wallet.get_accounts().into_iter().find(|acc| self.is_account_number_taken(acc.get_account_number())) {
+            return Err(ServiceError::new(
+                ServiceErrorKind::Conflict,
+                &format!("Account number {} already exists", account.get_account_number()),
+            ));
+        }
+        self.wallets.push(wallet);
+        Ok(WalletSummary::from_wallet(self.wallets.last().unwrap().as_ref()))
+    }
+
+    pub fn create_wallet(&mut self, request: &CreateWalletRequest) -> Result<WalletSummary, ServiceError> {
+        let wallet_type = request.wallet_type.parse::<WalletType>().map_err(|e| ServiceError::new(ServiceErrorKind::InvalidRequest, &e))?;
+        let wallet: Box<dyn Wallet> = match wallet_type {
+            WalletType::MultiCurrency => Box::new(MultiCurrencyWallet::new()),
+            WalletType::Basic => {
+                let currency = request.currency.as_deref()
+                    .ok_or(ServiceError::new(ServiceErrorKind::InvalidRequest, "A basic wallet needs a currency"))?;
+                let account_type = request.account_type.as_deref().unwrap_or("basic");
+                match self.new_account(account_type, currency, request.overdraft_limit)? {
+                    NewAccount::Basic(account) => Box::new(BasicWallet::new(account)),
+                    NewAccount::Premium(account) => Box::new(BasicWallet::new(account)),
+                }
+            }
+        };
+        self.add_wallet(wallet)
+    }
+
+    pub fn add_account(&mut self, wallet_id: &str, request: &AddAccountRequest) -> Result<AccountSummary, ServiceError> {
+        let index = self.find_wallet_index(wallet_id)?;
+        let account: Box<dyn Account> = match self.new_account(&request.account_type, &request.currency, request.overdraft_limit)? {
+            NewAccount::Basic(account) => Box::new(account),
+            NewAccount::Premium(account) => Box::new(account),
+        };
+        // the number is unique across the service, so the wallet refuses because of the accounts it already holds
+        let account = self.wallets[index].add_account(account).map_err(|e| ServiceError::new(ServiceErrorKind::Conflict, &e))?;
+        Ok(AccountSummary::from_account(account))
+    }
+
+    /// Returns every wallet, in the order they were added
+    pub fn list_wallets(&self) -> Vec<WalletSummary> {
+        self.wallets.iter().map(|wallet| WalletSummary::from_wallet(wallet.as_ref())).collect()
+    }
+
+    pub fn get_wallet(&self, wallet_id: &str) -> Result<WalletSummary, ServiceError> {
+        let index = self.find_wallet_index(wallet_id)?;
+        Ok(WalletSummary::from_wallet(self.wallets[index].as_ref()))
+    }
+
+    pub fn wallet(&self, wallet_id: &str) -> Option<&dyn Wallet> {
+        self.wallets.iter().find(|w| w.get_wallet_id() == wallet_id).map(|w| w.as_ref())
+    }
+
+    pub fn deposit(&mut self, wallet_id: &str, request: &AmountRequest) -> Result<AccountSummary, ServiceError> {
+        Self::check_amount(request.amount)?;
+        let index = self.find_wallet_index(wallet_id)?;
+        let wallet = &mut self.wallets[index];
+        Self::check_resulting_balance(wallet.as_ref(), request, request.amount)?;
+        let response = match &request.account_number {
+            Some(account_number) => {
+                Self::check_account_currency(wallet.as_ref(), account_number, &request.currency)?;
+                wallet.deposit_to_account(account_number, request.amount)
+            }
+            None => wallet.deposit(&request.currency, request.amount),
+        };
+        if !response.is_successful {
+            return Err(Self::refused(wallet.as_ref(), &request.currency, request.amount, response.error_message, false));
+        }
+        Self::account_summary(wallet.as_ref(), &response.account_number)
+    }
+
+    pub fn withdraw(&mut self, wallet_id: &str, request: &AmountRequest) -> Result<AccountSummary, ServiceError> {
+        Self::check_amount(request.amount)?;
+        let index = self.find_wallet_index(wallet_id)?;
+        let wallet = &mut self.wallets[index];
+        Self::check_resulting_balance(wallet.as_ref(), request, -request.amount)?;
+        let response = match &request.account_number {
+            Some(account_number) => {
+                Self::check_account_currency(wallet.as_ref(), account_number, &request.currency)?;
+                wallet.withdraw_from_account(account_number, request.amount)
+            }
+            None => wallet.withdraw(&request.currency, request.amount),
+        };
+        if !response.is_successful {
+            return Err(match &request.account_number {
+                Some(account_number) => {
+                    let account = Self::find_account(wallet.as_ref(), account_number)?;
+                    Self::refused_by_account(account, request.amount, response.error_message, true)
+                }
+                None => Self::refused(wallet.as_ref(), &request.currency, request.amount, response.error_message, true),
+            });
+        }
+        Self::account_summary(wallet.as_ref(), &response.account_number)
+    }
+
+    pub fn transfer(&mut self, from_wallet_id: &str, request: &TransferRequest) -> Result<TransferSummary, ServiceError> {
+        Self::check_amount(request.amount)?;
+        let from_index = self.find_wallet_index(from_wallet_id)?;
+        let to_index = self.find_wallet_index(&request.to_wallet_id)?;
+        if from_index == to_index {
+            return Err(ServiceError::new(ServiceErrorKind::InvalidRequest, "Cannot transfer between a wallet and itself"));
+        }
+
+        let (from_wallet, to_wallet) = if from_index < to_index {
+            let (head, tail) = self.wallets.split_at_mut(to_index);
+            (&mut head[from_index], &mut tail[0])
+        } else {
+            let (head, tail) = self.wallets.split_at_mut(from_index);
+            (&mut tail[0], &mut head[to_index])
+        };
+        if let Some(recipient) = to_wallet.get_account_by_currency(&request.currency) {
+            Self::check_finite_balance(recipient, request.amount)?;
+        }
+        let response = from_wallet.transfer(to_wallet.as_mut(), &request.currency, request.amount);
+        if !response.is_successful {
+            if to_wallet.get_account_by_currency(&request.currency).is_none() {
+                return Err(ServiceError::new(
+                    ServiceErrorKind::NotFound,
+                    &format!("No account found with currency: {} in wallet {}", request.currency, request.to_wallet_id),
+                ));
+            }
+            return Err(Self::refused(from_wallet.as_ref(), &request.currency, request.amount, response.error_message, true));
+        }
+        Ok(TransferSummary {
+            sender_wallet_id: response.sender_wallet_id,
+            sender_account_number: response.sender_account_number,
+            recipient_wallet_id: response.recipient_wallet_id,
+            recipient_account_number: response.recipient_account_number,
+            currency: response.currency,
+            amount: response.amount,
+        })
+    }
+
+    pub fn balance(&self, wallet_id: &str, currency: &str) -> Result<BalanceSummary, ServiceError> {
+        let index = self.find_wallet_index(wallet_id)?;
+        let balance = self.wallets[index].balance(currency).map_err(|e| ServiceError::new(ServiceErrorKind::NotFound, &e))?;
+        Ok(BalanceSummary {
+            wallet_id: wallet_id.to_string(),
+            currency: currency.to_string(),
+            balance,
+        })
+    }
+
+    pub fn history(&self, wallet_id: &str, account_number: &str) -> Result<Vec<Transaction>, ServiceError> {
+        let index = self.find_wallet_index(wallet_id)?;
+        let account = Self::find_account(self.wallets[index].as_ref(), account_number)?;
+        Ok(account.get_history().to_vec())
+    }
+
+    /// Every wallet with its open accounts and their histories
+    pub fn to_stored(&self) -> Vec<StoredWallet> {
+        self.wallets.iter()
+            .map(|wallet| StoredWallet {
+                wallet_id: wallet.get_wallet_id().to_string(),
+                wallet_type: wallet.get_wallet_type(),
+                accounts: wallet.get_accounts().into_iter()
+                    .map(|account| StoredAccount {
+                        account_number: account.get_account_number().to_string(),
+                        account_type: account.get_account_type(),
+                        currency: account.get_currency().to_string(),
+                        balance: account.get_balance(),
+                        overdraft_limit: account.get_overdraft_limit(),
+                        is_default: wallet.get_account_by_currency(account.get_currency())
+                            .is_some_and(|default| default.get_account_number() == account.get_account_number()),
+                        history: account.get_history().to_vec(),
+                    })
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Rebuilds a service from stored wallets
+    pub fn from_stored(wallets: &[StoredWallet]) -> Result<Self, ServiceError> {
+        let invalid = |message: String| ServiceError::new(ServiceErrorKind::InvalidRequest, &message);
+        let mut service = WalletService::new();
+        for stored in wallets {
+            let mut accounts = Vec::new();
+            for account in stored.accounts.iter() {
+                let account_number = AccountNumber::parse(&account.account_number).map_err(invalid)?;
+                accounts.push(match account.account_type {
+                    AccountType::Basic => NewAccount::Basic(BasicAccount::restore(
+                        account_number, &account.currency, account.balance, account.history.clone(),
+                    )),
+                    AccountType::Premium => NewAccount::Premium(PremiumAccount::restore(
+                        account_number, &account.currency, account.balance, account.overdraft_limit, account.history.clone(),
+                    )),
+                });
+            }
+            let wallet: Box<dyn Wallet> = match stored.wallet_type {
+                WalletType::Basic => match (accounts.pop(), accounts.is_empty()) {
+                    (Some(NewAccount::Basic(account)), true) => Box::new(BasicWallet::restore(&stored.wallet_id, account)),
+                    (Some(NewAccount::Premium(account)), true) => Box::new(BasicWallet::restore(&stored.wallet_id, account)),
+                    _ => return Err(invalid(format!("Basic wallet {} must hold exactly one account", stored.wallet_id))),
+                },
+                WalletType::MultiCurrency => {
+                    let mut wallet = MultiCurrencyWallet::restore(&stored.wallet_id);
+                    for account in accounts {
+                        let account: Box<dyn Account> = match account {
+                            NewAccount::Basic(account) => Box::new(account),
+                            NewAccount::Premium(account) => Box::new(account),
+                        };
+                        wallet.add_account(account).map_err(|e| ServiceError::new(ServiceErrorKind::Conflict, &e))?;
+                    }
+                    for account in stored.accounts.iter().filter(|account| account.is_default) {
+                        wallet.set_default_account(&account.account_number).map_err(invalid)?;
+                    }
+                    Box::new(wallet)
+                }
+            };
+            service.add_wallet(wallet)?;
+        }
+        Ok(service)
+    }
+
+    /// Loads the wallets saved by `save_state`; a missing file holds no wallets
+    pub fn load_state(path: &str) -> Result<Self, String> {
+        if !Path::new(path).exists() {
+            return Ok(WalletService::new());
+        }
+        let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read state {}: {}", path, e))?;
+        let wallets: Vec<StoredWallet> = serde_json::from_str(&contents).map_err(|e| format!("Failed to parse state {}: {}", path, e))?;
+        Self::from_stored(&wallets).map_err(|e| format!("Invalid state {}: {}", path, e))
+    }
+
+    /// Writes every wallet to a state file. Refuses non-finite balances, which JSON cannot hold.
+    pub fn save_state(&self, path: &str) -> Result<(), String> {
+        let stored = self.to_stored();
+        for account in stored.iter().flat_map(|wallet| wallet.accounts.iter()) {
+            let mut amounts = std::iter::once(account.balance)
+                .chain(account.history.iter().flat_map(|transaction| [transaction.amount, transaction.balance_after]));
+            if amounts.any(|amount| !amount.is_finite()) {
+                return Err(format!("Account {} holds an amount that is not finite", account.account_number));
+            }
+        }
+        let json = serde_json::to_string_pretty(&stored).map_err(|e| format!("Failed to encode state: {}", e))?;
+        fs::write(path, json + "\n").map_err(|e| format!("Failed to write state {}: {}", path, e))
+    }
+
+    fn find_wallet_index(&self, wallet_id: &str) -> Result<usize, ServiceError> {
+        self.wallets.iter()
+            .position(|w| w.get_wallet_id() == wallet_id)
+            .ok_or(ServiceError::new(ServiceErrorKind::NotFound, &format!("No wallet found with id: {}", wallet_id)))
+    }
+
+    fn is_account_number_taken(&self, account_number: &str) -> bool {
+        self.wallets.iter()
+            .flat_map(|w| w.get_accounts())
+            .any(|acc| acc.get_account_number() == account_number)
+    }
+
+    /// Creates an account whose number is unique across every wallet of the service
+    fn new_account(&self, account_type: &str, currency: &str, overdraft_limit: Option<f64>) -> Result<NewAccount, ServiceError> {
+        let account_type = account_type.parse::<AccountType>().map_err(|e| ServiceError::new(ServiceErrorKind::InvalidRequest, &e))?;
+        if currency.trim().is_empty() {
+            return Err(ServiceError::new(ServiceErrorKind::InvalidRequest, "Currency cannot be empty"));
+        }
+        let account_number = AccountNumber::generate_unique(account_type.clone(), |n| self.is_account_number_taken(n))
+            .map_err(|e| ServiceError::new(ServiceErrorKind::Conflict, &e))?;
+        match (account_type, overdraft_limit) {
+            (AccountType::Basic, None) => BasicAccount::with_account_number(currency, account_number)
+                .map(NewAccount::Basic)
+                .map_err(|e| ServiceError::new(ServiceErrorKind::InvalidRequest, &e)),
+            (AccountType::Basic, Some(_)) => Err(ServiceError::new(ServiceErrorKind::InvalidRequest, "Basic accounts have no overdraft limit")),
+            (AccountType::Premium, Some(limit)) if limit < 0.0 || !limit.is_finite() => {
+                Err(ServiceError::new(ServiceErrorKind::InvalidRequest, "Overdraft limit cannot be negative"))
+            }
+            (AccountType::Premium, limit) => PremiumAccount::with_account_number(currency, limit.unwrap_or(0.0), account_number)
+                .map(NewAccount::Premium)
+                .map_err(|e| ServiceError::new(ServiceErrorKind::InvalidRequest, &e)),
+        }
+    }
+
+    fn check_amount(amount: f64) -> Result<(), ServiceError> {
+        if !amount.is_finite() {
+            return Err(ServiceError::new(ServiceErrorKind::InvalidRequest, "Amount must be a finite number"));
+        }
+        Ok(())
+    }
+
+    /// Refuses a deposit or withdrawal that would leave the account with a balance that cannot be stored
+    fn check_resulting_balance(wallet: &dyn Wallet, request: &AmountRequest, change: f64) -> Result<(), ServiceError> {
+        let account = match &request.account_number {
+            Some(account_number) => wallet.get_account_by_number(account_number).ok(),
+            None => wallet.get_account_by_currency(&request.currency),
+        };
+        match account {
+            Some(account) => Self::check_finite_balance(account, change),
+            None => Ok(()),
+        }
+    }
+
+    fn check_finite_balance(account: &dyn Account, change: f64) -> Result<(), ServiceError> {
+        if !(account.get_balance() + change).is_finite() {
+            return Err(ServiceError::new(
+                ServiceErrorKind::InvalidRequest,
+                &format!("The balance of account {} would overflow", account.get_account_number()),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Looks up an account, telling a malformed account number from an unknown one
+    fn find_account<'a>(wallet: &'a dyn Wallet, account_number: &str) -> Result<&'a dyn Account, ServiceError> {
+        AccountNumber::parse(account_number).map_err(|e| ServiceError::new(ServiceErrorKind::InvalidRequest, &e))?;
+        wallet.get_account_by_number(account_number).map_err(|e| ServiceError::new(ServiceErrorKind::NotFound, &e))
+    }
+
+    /// Classifies an operation the wallet refused on the default account of a currency
+    fn refused(wallet: &dyn Wallet, currency: &str, amount: f64, error_message: Option<String>, is_debit: bool) -> ServiceError {
+        match wallet.get_account_by_currency(currency) {
+            Some(account) => Self::refused_by_account(account, amount, error_message, is_debit),
+            None => {
+                let message = error_message.unwrap_or(format!("No account found with currency: {}", currency));
+                ServiceError::new(ServiceErrorKind::NotFound, &message)
+            }
+        }
+    }
+
+    /// A refused debit beyond the balance and overdraft limit lacks funds; anything else is an invalid request
+    fn refused_by_account(account: &dyn Account, amount: f64, error_message: Option<String>, is_debit: bool) -> ServiceError {
+        let kind = if is_debit && amount > 0.0 && account.get_balance() + account.get_overdraft_limit() < amount {
+            ServiceErrorKind::InsufficientFunds
+        } else {
+            ServiceErrorKind::InvalidRequest
+        };
+        ServiceError::new(kind, &error_message.unwrap_or_default())
+    }
+
+    fn check_account_currency(wallet: &dyn Wallet, account_number: &str, currency: &str) -> Result<(), ServiceError> {
+        let account = Self::find_account(wallet, account_number)?;
+        if account.get_currency() != currency {
+            return Err(ServiceError::new(
+                ServiceErrorKind::InvalidRequest,
+                &format!("Account {} holds {}, not {}", account_number, account.get_currency(), currency),
+            ));
+        }
+        Ok(())
+    }
+
+    fn account_summary(wallet: &dyn Wallet, account_number: &str) -> Result<AccountSummary, ServiceError> {
+        let account = Self::find_account(wallet, account_number)?;
+        Ok(AccountSummary::from_account(account))
+    }
+}
+
+impl Default for WalletService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+enum NewAccount {
+    Basic(BasicAccount),
+    Premium(PremiumAccount),
+}
@@ -0,0 +1,555 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use crate::accounts::{Account, AccountType};
+use crate::basic_account::BasicAccount;
+use crate::basic_wallet::BasicWallet;
+use crate::customer::{ContactDetails, Customer, KycTier};
+use crate::multi_currency_wallet::MultiCurrencyWallet;
+use crate::premium_account::PremiumAccount;
+use crate::wallets::{Wallet, WalletType};
+
+/// Distribution the amount of each simulated operation is drawn from
+#[derive(Clone, Debug, PartialEq)]
+pub enum AmountDistribution {
+    Uniform { min: f64, max: f64 },
+    /// Right-skewed: most operations are small, a few are large
+    LogNormal { median: f64, sigma: f64 },
+}
+
+impl AmountDistribution {
+    /// Draws an amount, rounded to cents
+    pub fn sample(&self, rng: &mut StdRng) -> f64 {
+        let amount = match self {
+            AmountDistribution::Uniform { min, max } => {
+                if min == max { *min } else { rng.random_range(*min..*max) }
+            }
+            AmountDistribution::LogNormal { median, sigma } => median * (sigma * standard_normal(rng)).exp(),
+        };
+        (amount * 100.0).round() / 100.0
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            AmountDistribution::Uniform { min, max } if !(min.is_finite() && max.is_finite() && *min > 0.0 && min <= max) => {
+                Err("Uniform amounts need 0 < min <= max".to_string())
+            }
+            AmountDistribution::LogNormal { median, sigma } if !(median.is_finite() && sigma.is_finite() && *median > 0.0 && *sigma >= 0.0) => {
+                Err("Log-normal amounts need a positive median and a non-negative sigma".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Parses `uniform:MIN:MAX` or `lognormal:MEDIAN:SIGMA`
+impl FromStr for AmountDistribution {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(':').collect();
+        let numbers: Vec<f64> = parts.iter()
+            .skip(1)
+            .map(|part| part.trim().parse::<f64>())
+            .collect::<Result<_, _>>()
+            .map_err(|_| format!("Invalid amount distribution: {}", s))?;
+        let distribution = match (parts[0].trim().to_lowercase().as_str(), numbers.as_slice()) {
+            ("uniform", [min, max]) => AmountDistribution::Uniform { min: *min, max: *max },
+            ("lognormal", [median, sigma]) => AmountDistribution::LogNormal { median: *median, sigma: *sigma },
+            _ => return Err(format!("Invalid amount distribution: {}", s)),
+        };
+        distribution.validate()?;
+        Ok(distribution)
+    }
+}
+
+/// Kind of operation a simulated customer makes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SimulatedOperation {
+    Deposit,
+    Withdrawal,
+    Transfer,
+}
+
+impl fmt::Display for SimulatedOperation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SimulatedOperation::Deposit => write!(f, "Deposit"),
+            SimulatedOperation::Withdrawal => write!(f, "Withdrawal"),
+            SimulatedOperation::Transfer => write!(f, "Transfer"),
+        }
+    }
+}
+
+/// Population and workload of a simulation run
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulationConfig {
+    pub seed: u64,
+    pub customers: usize,
+    pub days: u32,
+    /// Mean number of operations each customer makes per day; arrivals are Poisson distributed
+    pub operations_per_customer_per_day: f64,
+    /// Relative weights of deposits, withdrawals and transfers
+    pub operation_mix: Vec<(SimulatedOperation, f64)>,
+    pub amounts: AmountDistribution,
+    /// Currencies in use with their relative weights. Accounts are opened in these currencies and
+    /// each operation uses one the customer holds.
+    pub currency_mix: Vec<(String, f64)>,
+    /// Share of customers holding a `MultiCurrencyWallet` with an account per currency
+    /// rather than a `BasicWallet`
+    pub multi_currency_share: f64,
+    /// Share of accounts opened as `PremiumAccount`s rather than `BasicAccount`s
+    pub premium_share: f64,
+    pub overdraft_limit: f64,
+    pub kyc_tier: KycTier,
+}
+
+impl Default for SimulationConfig {
+    fn default() -> Self {
+        SimulationConfig {
+            seed: 0,
+            customers: 100,
+            days: 30,
+            operations_per_customer_per_day: 2.0,
+            operation_mix: vec![
+                (SimulatedOperation::Deposit, 0.4),
+                (SimulatedOperation::Withdrawal, 0.3),
+                (SimulatedOperation::Transfer, 0.3),
+            ],
+            amounts: AmountDistribution::LogNormal { median: 50.0, sigma: 1.0 },
+            currency_mix: vec![("USD".to_string(), 0.6), ("EUR".to_string(), 0.3), ("GBP".to_string(), 0.1)],
+            multi_currency_share: 0.3,
+            premium_share: 0.2,
+            overdraft_limit: 500.0,
+            kyc_tier: KycTier::Verified,
+        }
+    }
+}
+
+impl SimulationConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.customers < 2 {
+            return Err("The simulation needs at least two customers".to_string());
+        }
+        if self.days == 0 {
+            return Err("The simulation needs at least one day".to_string());
+        }
+        if !(self.operations_per_customer_per_day.is_finite() && self.operations_per_customer_per_day >= 0.0) {
+            return Err("The arrival rate must be a non-negative number".to_string());
+        }
+        validate_weights("operation", self.operation_mix.iter().map(|(_, weight)| *weight))?;
+        validate_weights("currency", self.currency_mix.iter().map(|(_, weight)| *weight))?;
+        for (name, share) in [("Multi-currency", self.multi_currency_share), ("Premium", self.premium_share)] {
+            if !(0.0..=1.0).contains(&share) {
+                return Err(format!("{} share must be between 0 and 1", name));
+            }
+        }
+        if !(self.overdraft_limit.is_finite() && self.overdraft_limit >= 0.0) {
+            return Err("The overdraft limit must be a non-negative number".to_string());
+        }
+        self.amounts.validate()
+    }
+}
+
+/// Parses a currency mix such as `USD:0.6,EUR:0.3,GBP:0.1`; a currency without a weight weighs 1
+pub fn parse_currency_mix(s: &str) -> Result<Vec<(String, f64)>, String> {
+    let mix = s.split(',')
+        .map(|item| {
+            let mut parts = item.splitn(2, ':');
+            let currency = parts.next().unwrap().trim().to_uppercase();
+            if currency.is_empty() {
+                return Err(format!("Invalid currency mix: {}", s));
+            }
+            let weight = match parts.next() {
+                Some(weight) => weight.trim().parse::<f64>().map_err(|_| format!("Invalid currency weight: {}", item))?,
+                None => 1.0,
+            };
+            Ok((currency, weight))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    validate_weights("currency", mix.iter().map(|(_, weight)| *weight))?;
+    Ok(mix)
+}
+
+/// Operations attempted on one simulated day
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DayStatistics {
+    pub day: u32,
+    pub attempted: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+/// Outcome of every operation of one kind
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct OperationStatistics {
+    pub attempted: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub failure_rate: f64,
+    /// Sum of the amounts of the successful operations, across currencies
+    pub volume: f64,
+}
+
+/// Spread of the final account balances of a currency
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BalanceDistribution {
+    pub accounts: usize,
+    pub total: f64,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+    pub p90: f64,
+    pub p99: f64,
+    /// Accounts drawing on their overdraft
+    pub overdrawn: usize,
+}
+
+impl BalanceDistribution {
+    fn from_balances(mut balances: Vec<f64>) -> Self {
+        if balances.is_empty() {
+            return BalanceDistribution::default();
+        }
+        balances.sort_by(|a, b| a.total_cmp(b));
+        let total: f64 = balances.iter().sum();
+        BalanceDistribution {
+            accounts: balances.len(),
+            total,
+            min: balances[0],
+            max: balances[balances.len() - 1],
+            mean: total / balances.len() as f64,
+            median: percentile(&balances, 50.0),
+            p90: percentile(&balances, 90.0),
+            p99: percentile(&balances, 99.0),
+            overdrawn: balances.iter().filter(|balance| **balance < 0.0).count(),
+        }
+    }
+}
+
+/// Statistics of a simulation run. The same configuration always produces the same report.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SimulationReport {
+    pub seed: u64,
+    pub customers: usize,
+    pub days: u32,
+    pub basic_wallets: usize,
+    pub multi_currency_wallets: usize,
+    pub basic_accounts: usize,
+    pub premium_accounts: usize,
+    pub attempted: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub failure_rate: f64,
+    /// Successful operations per simulated day
+    pub throughput_per_day: f64,
+    pub daily: Vec<DayStatistics>,
+    pub operations: BTreeMap<SimulatedOperation, OperationStatistics>,
+    /// Failures per error message, with the amounts in the message replaced by `#`
+    pub failure_reasons: BTreeMap<String, usize>,
+    pub balances: BTreeMap<String, BalanceDistribution>,
+}
+
+impl SimulationReport {
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to encode simulation report: {}", e))
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "Simulation of {} customers over {} days (seed {})\n",
+            self.customers, self.days, self.seed
+        );
+        text.push_str(&format!(
+            "Wallets: {} basic, {} multi-currency; accounts: {} basic, {} premium\n",
+            self.basic_wallets, self.multi_currency_wallets, self.basic_accounts, self.premium_accounts
+        ));
+        text.push_str(&format!(
+            "Operations: {} attempted, {} succeeded, {} failed ({:.2}% failure rate)\n",
+            self.attempted, self.succeeded, self.failed, self.failure_rate * 100.0
+        ));
+        text.push_str(&format!("Throughput: {:.2} successful operations per day\n", self.throughput_per_day));
+        text.push_str("\nOperation    Attempted  Succeeded     Failed  Failure       Volume\n");
+        for (operation, stats) in self.operations.iter() {
+            text.push_str(&format!(
+                "{:<10} {:>11} {:>10} {:>10} {:>7.2}% {:>12.2}\n",
+                operation.to_string(), stats.attempted, stats.succeeded, stats.failed, stats.failure_rate * 100.0, stats.volume
+            ));
+        }
+        if !self.failure_reasons.is_empty() {
+            text.push_str("\nFailure reasons\n");
+            for (reason, count) in self.failure_reasons.iter() {
+                text.push_str(&format!("{:>8}  {}\n", count, reason));
+            }
+        }
+        text.push_str("\nCurrency  Accounts         Total           Min        Median           P90           P99           Max  Overdrawn\n");
+        for (currency, balances) in self.balances.iter() {
+            text.push_str(&format!(
+                "{:<8} {:>9} {:>13.2} {:>13.2} {:>13.2} {:>13.2} {:>13.2} {:>13.2} {:>10}\n",
+                currency, balances.accounts, balances.total, balances.min, balances.median,
+                balances.p90, balances.p99, balances.max, balances.overdrawn
+            ));
+        }
+        text
+    }
+}
+
+/// Workload simulator: opens wallets for a population of customers and drives seeded random
+/// deposits, withdrawals and transfers through them, one simulated day at a time
+pub struct Simulation {
+    config: SimulationConfig,
+    rng: StdRng,
+    customers: Vec<Customer>,
+}
+
+impl Simulation {
+    pub fn new(config: SimulationConfig) -> Result<Self, String> {
+        config.validate()?;
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let mut customers = Vec::with_capacity(config.customers);
+        for index in 0..config.customers {
+            let contact = ContactDetails {
+                email: format!("customer{}@example.com", index + 1),
+                phone: None,
+                address: None,
+            };
+            let mut customer = Customer::new(&format!("Customer {}", index + 1), contact, config.kyc_tier.clone());
+            let wallet: Box<dyn Wallet> = if rng.random_bool(config.multi_currency_share) {
+                let mut wallet = MultiCurrencyWallet::new();
+                for (currency, _) in config.currency_mix.iter() {
+                    wallet.add_account(open_account(&mut rng, &config, currency))?;
+                }
+                Box::new(wallet)
+            } else {
+                let currency = pick(&mut rng, &config.currency_mix).clone();
+                if rng.random_bool(config.premium_share) {
+                    Box::new(BasicWallet::new(PremiumAccount::new(&currency, config.overdraft_limit)))
+                } else {
+                    Box::new(BasicWallet::new(BasicAccount::new(&currency)))
+                }
+            };
+            customer.add_wallet(wallet)?;
+            customers.push(customer);
+        }
+        Ok(Simulation { config, rng, customers })
+    }
+
+    pub fn get_config(&self) -> &SimulationConfig {
+        &self.config
+    }
+
+    pub fn get_customers(&self) -> &[Customer] {
+        &self.customers
+    }
+
+    /// Simulates every configured day and reports the statistics
+    pub fn run(&mut self) -> SimulationReport {
+        let mut report = SimulationReport {
+            seed: self.config.seed,
+            customers: self.customers.len(),
+            days: self.config.days,
+            basic_wallets: 0,
+            multi_currency_wallets: 0,
+            basic_accounts: 0,
+            premium_accounts: 0,
+            attempted: 0,
+            succeeded: 0,
+            failed: 0,
+            failure_rate: 0.0,
+            throughput_per_day: 0.0,
+            daily: Vec::new(),
+            operations: BTreeMap::new(),
+            failure_reasons: BTreeMap::new(),
+            balances: BTreeMap::new(),
+        };
+
+        for day in 1..=self.config.days {
+            let mut stats = DayStatistics { day, ..DayStatistics::default() };
+            for customer in 0..self.customers.len() {
+                for _ in 0..poisson(&mut self.rng, self.config.operations_per_customer_per_day) {
+                    let operation = *pick(&mut self.rng, &self.config.operation_mix);
+                    let currency = self.pick_currency(customer);
+                    let amount = self.config.amounts.sample(&mut self.rng);
+                    let result = self.apply(customer, operation, &currency, amount);
+
+                    let operation_stats = report.operations.entry(operation).or_default();
+                    operation_stats.attempted += 1;
+                    stats.attempted += 1;
+                    match result {
+                        Ok(()) => {
+                            operation_stats.succeeded += 1;
+                            operation_stats.volume += amount;
+                            stats.succeeded += 1;
+                        }
+                        Err(error) => {
+                            operation_stats.failed += 1;
+                            stats.failed += 1;
+                            *report.failure_reasons.entry(failure_reason(&error)).or_insert(0) += 1;
+                        }
+                    }
+                }
+            }
+            report.attempted += stats.attempted;
+            report.succeeded += stats.succeeded;
+            report.failed += stats.failed;
+            report.daily.push(stats);
+        }
+
+        for operation_stats in report.operations.values_mut() {
+            operation_stats.failure_rate = rate(operation_stats.failed, operation_stats.attempted);
+        }
+        report.failure_rate = rate(report.failed, report.attempted);
+        report.throughput_per_day = report.succeeded as f64 / self.config.days as f64;
+
+        let mut balances: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+        for wallet in self.customers.iter().flat_map(|customer| customer.get_wallets()) {
+            let accounts = wallet.get_accounts();
+            match wallet.get_wallet_type() {
+                WalletType::Basic => report.basic_wallets += 1,
+                WalletType::MultiCurrency => report.multi_currency_wallets += 1,
+            }
+            for account in accounts {
+                match account.get_account_type() {
+                    AccountType::Basic => report.basic_accounts += 1,
+                    AccountType::Premium => report.premium_accounts += 1,
+                }
+                balances.entry(account.get_currency().to_string()).or_default().push(account.get_balance());
+            }
+        }
+        report.balances = balances.into_iter()
+            .map(|(currency, balances)| (currency, BalanceDistribution::from_balances(balances)))
+            .collect();
+        report
+    }
+
+    /// Draws a currency of the mix that the customer holds an account in
+    fn pick_currency(&mut self, customer: usize) -> String {
+        let wallet = self.customers[customer].get_wallets()[0];
+        let held: Vec<(String, f64)> = self.config.currency_mix.iter()
+            .filter(|(currency, weight)| *weight > 0.0 && wallet.get_account_by_currency(currency).is_some())
+            .cloned()
+            .collect();
+        if held.is_empty() {
+            pick(&mut self.rng, &self.config.currency_mix).clone()
+        } else {
+            pick(&mut self.rng, &held).clone()
+        }
+    }
+
+    fn apply(&mut self, customer: usize, operation: SimulatedOperation, currency: &str, amount: f64) -> Result<(), String> {
+        let wallet_id = self.customers[customer].get_wallets()[0].get_wallet_id().to_string();
+        let (is_successful, error_message) = match operation {
+            SimulatedOperation::Deposit => {
+                let response = self.customers[customer].deposit(&wallet_id, currency, amount);
+                (response.is_successful, response.error_message)
+            }
+            SimulatedOperation::Withdrawal => {
+                let response = self.customers[customer].withdraw(&wallet_id, currency, amount);
+                (response.is_successful, response.error_message)
+            }
+            SimulatedOperation::Transfer => {
+                let offset = self.rng.random_range(1..self.customers.len());
+                let recipient = (customer + offset) % self.customers.len();
+                let (sender, recipient) = if customer < recipient {
+                    let (head, tail) = self.customers.split_at_mut(recipient);
+                    (&mut head[customer], &mut tail[0])
+                } else {
+                    let (head, tail) = self.customers.split_at_mut(customer);
+                    (&mut tail[0], &mut head[recipient])
+                };
+                let recipient_wallet_id = recipient.get_wallets()[0].get_wallet_id().to_string();
+                let response = sender.transfer_to_customer(&wallet_id, recipient, &recipient_wallet_id, currency, amount);
+                (response.is_successful, response.error_message)
+            }
+        };
+        if is_successful {
+            Ok(())
+        } else {
+            Err(error_message.unwrap_or_else(|| format!("{} failed", operation)))
+        }
+    }
+}
+
+fn open_account(rng: &mut StdRng, config: &SimulationConfig, currency: &str) -> Box<dyn Account> {
+    if rng.random_bool(config.premium_share) {
+        Box::new(PremiumAccount::new(currency, config.overdraft_limit))
+    } else {
+        Box::new(BasicAccount::new(currency))
+    }
+}
+
+fn validate_weights(name: &str, weights: impl Iterator<Item = f64>) -> Result<(), String> {
+    let mut total = 0.0;
+    for weight in weights {
+        if !(weight.is_finite() && weight >= 0.0) {
+            return Err(format!("Invalid {} weight: {}", name, weight));
+        }
+        total += weight;
+    }
+    if total > 0.0 {
+        Ok(())
+    } else {
+        Err(format!("The {} mix needs at least one positive weight", name))
+    }
+}
+
+/// Picks an item with probability proportional to its weight
+fn pick<'a, T>(rng: &mut StdRng, weighted: &'a [(T, f64)]) -> &'a T {
+    let total: f64 = weighted.iter().map(|(_, weight)| weight).sum();
+    let mut target = rng.random_range(0.0..total);
+    for (item, weight) in weighted.iter() {
+        if target < *weight {
+            return item;
+        }
+        target -= weight;
+    }
+    // floating point drift can leave the target just past the last positive weight
+    &weighted.iter().rev().find(|(_, weight)| *weight > 0.0).unwrap().0
+}
+
+/// Number of arrivals in a period with the given mean (Knuth's method, or a normal
+/// approximation for large means)
+fn poisson(rng: &mut StdRng, mean: f64) -> usize {
+    if mean <= 0.0 {
+        return 0;
+    }
+    if mean > 30.0 {
+        return (mean + mean.sqrt() * standard_normal(rng)).round().max(0.0) as usize;
+    }
+    let limit = (-mean).exp();
+    let mut product: f64 = rng.random();
+    let mut count = 0;
+    while product > limit {
+        product *= rng.random::<f64>();
+        count += 1;
+    }
+    count
+}
+
+/// Standard normal variate (Box-Muller transform)
+fn standard_normal(rng: &mut StdRng) -> f64 {
+    let u1: f64 = 1.0 - rng.random::<f64>();
+    let u2: f64 = rng.random();
+    (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[f64], percent: f64) -> f64 {
+    let rank = ((percent / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+fn rate(part: usize, whole: usize) -> f64 {
+    if whole == 0 { 0.0 } else { part as f64 / whole as f64 }
+}
+
+/// Replaces the amounts in an error message, so failures group by their cause
+fn failure_reason(error: &str) -> String {
+    error.split(' ')
+        .map(|word| if word.chars().any(|c| c.is_ascii_digit()) { "#" } else { word })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,294 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use crate::accounts::{Account, AccountType};
+use crate::transactions::{format_timestamp, TransactionKind};
+use crate::wallets::Wallet;
+
+/// Transaction on a statement, with the balance after it
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatementLine {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub kind: TransactionKind,
+    /// Amount with the sign of its effect on the balance
+    pub amount: f64,
+    pub running_balance: f64,
+    pub counterparty_account_number: Option<String>,
+}
+
+/// Statement of one account over a period, `period_start` and `period_end` included
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountStatement {
+    pub account_number: String,
+    pub account_type: AccountType,
+    pub currency: String,
+    /// Only set for Premium accounts
+    pub overdraft_limit: Option<f64>,
+    pub period_start: u64,
+    pub period_end: u64,
+    pub opening_balance: f64,
+    pub lines: Vec<StatementLine>,
+    pub total_fees: f64,
+    pub total_interest: f64,
+    pub closing_balance: f64,
+}
+
+impl AccountStatement {
+    /// Builds the statement from the account history. The opening balance is worked back from
+    /// the current balance, so it is right even for accounts restored with an earlier balance.
+    pub fn generate(account: &dyn Account, period_start: u64, period_end: u64) -> Result<Self, String> {
+        if period_start > period_end {
+            return Err("Statement period starts after it ends".to_string());
+        }
+        let history = account.get_history();
+        let since_start: f64 = history.iter()
+            .filter(|t| t.timestamp >= period_start)
+            .map(|t| t.signed_amount())
+            .sum();
+        let opening_balance = account.get_balance() - since_start;
+
+        let mut running_balance = opening_balance;
+        let mut lines = Vec::new();
+        let mut total_fees = 0.0;
+        let mut total_interest = 0.0;
+        for transaction in history.iter().filter(|t| t.timestamp >= period_start && t.timestamp <= period_end) {
+            running_balance += transaction.signed_amount();
+            match transaction.kind {
+                TransactionKind::Fee => total_fees += transaction.amount,
+                TransactionKind::Interest => total_interest += transaction.amount,
+                _ => {}
+            }
+            lines.push(StatementLine {
+                timestamp: transaction.timestamp,
+                kind: transaction.kind.clone(),
+                amount: transaction.signed_amount(),
+                running_balance,
+                counterparty_account_number: transaction.counterparty_account_number.clone(),
+            });
+        }
+
+        Ok(AccountStatement {
+            account_number: account.get_account_number().to_string(),
+            account_type: account.get_account_type(),
+            currency: account.get_currency().to_string(),
+            overdraft_limit: match account.get_account_type() {
+                AccountType::Premium => Some(account.get_overdraft_limit()),
+                AccountType::Basic => None,
+            },
+            period_start,
+            period_end,
+            opening_balance,
+            lines,
+            total_fees,
+            total_interest,
+            closing_balance: running_balance,
+        })
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        writeln!(text, "Statement for account {} ({}, {})", self.account_number, self.account_type, self.currency).unwrap();
+        writeln!(text, "Period: {} to {} UTC", format_timestamp(self.period_start), format_timestamp(self.period_end)).unwrap();
+        if let Some(overdraft_limit) = self.overdraft_limit {
+            writeln!(text, "Overdraft limit: {:.2}", overdraft_limit).unwrap();
+        }
+        writeln!(text, "Opening balance: {:.2}", self.opening_balance).unwrap();
+        writeln!(text, "{:<19}  {:<11}  {:>12}  {:>12}  Counterparty", "Date", "Type", "Amount", "Balance").unwrap();
+        for line in self.lines.iter() {
+            writeln!(
+                text,
+                "{:<19}  {:<11}  {:>12.2}  {:>12.2}  {}",
+                format_timestamp(line.timestamp),
+                line.kind,
+                line.amount,
+                line.running_balance,
+                line.counterparty_account_number.as_deref().unwrap_or(""),
+            ).unwrap();
+        }
+        writeln!(text, "Fees: {:.2}", self.total_fees).unwrap();
+        writeln!(text, "Interest: {:.2}", self.total_interest).unwrap();
+        writeln!(text, "Closing balance: {:.2}", self.closing_balance).unwrap();
+        text
+    }
+
+    /// One row per transaction, framed by opening and closing balance rows and followed by the subtotals
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("account_number,currency,date,type,amount,balance,counterparty\n");
+        self.write_csv_rows(&mut csv);
+        csv
+    }
+
+    /// Self-contained HTML page with inline styles
+    pub fn to_html(&self) -> String {
+        html_page(&format!("Statement {}", self.account_number), &self.html_section())
+    }
+
+    fn write_csv_rows(&self, csv: &mut String) {
+        let mut row = |date: u64, kind: &str, amount: Option<f64>, balance: Option<f64>, counterparty: &str| {
+            writeln!(
+                csv,
+                "{},{},{},{},{},{},{}",
+                csv_field(&self.account_number),
+                csv_field(&self.currency),
+                format_timestamp(date),
+                kind,
+                amount.map(|a| format!("{:.2}", a)).unwrap_or_default(),
+                balance.map(|b| format!("{:.2}", b)).unwrap_or_default(),
+                csv_field(counterparty),
+            ).unwrap();
+        };
+        row(self.period_start, "OpeningBalance", None, Some(self.opening_balance), "");
+        for line in self.lines.iter() {
+            row(
+                line.timestamp,
+                &line.kind.to_string(),
+                Some(line.amount),
+                Some(line.running_balance),
+                line.counterparty_account_number.as_deref().unwrap_or(""),
+            );
+        }
+        row(self.period_end, "ClosingBalance", None, Some(self.closing_balance), "");
+        row(self.period_end, "TotalFees", Some(self.total_fees), None, "");
+        row(self.period_end, "TotalInterest", Some(self.total_interest), None, "");
+    }
+
+    fn html_section(&self) -> String {
+        let mut html = String::new();
+        writeln!(html, "<section>").unwrap();
+        writeln!(
+            html,
+            "<h2>Account {} ({}, {})</h2>",
+            html_escape(&self.account_number), self.account_type, html_escape(&self.currency)
+        ).unwrap();
+        writeln!(html, "<p>Period: {} to {} UTC</p>", format_timestamp(self.period_start), format_timestamp(self.period_end)).unwrap();
+        writeln!(html, "<dl>").unwrap();
+        if let Some(overdraft_limit) = self.overdraft_limit {
+            writeln!(html, "<dt>Overdraft limit</dt><dd>{:.2}</dd>", overdraft_limit).unwrap();
+        }
+        writeln!(html, "<dt>Opening balance</dt><dd>{:.2}</dd>", self.opening_balance).unwrap();
+        writeln!(html, "</dl>").unwrap();
+        writeln!(html, "<table>").unwrap();
+        writeln!(html, "<tr><th>Date</th><th>Type</th><th>Amount</th><th>Balance</th><th>Counterparty</th></tr>").unwrap();
+        for line in self.lines.iter() {
+            writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td class=\"amount\">{:.2}</td><td class=\"amount\">{:.2}</td><td>{}</td></tr>",
+                format_timestamp(line.timestamp),
+                line.kind,
+                line.amount,
+                line.running_balance,
+                html_escape(line.counterparty_account_number.as_deref().unwrap_or("")),
+            ).unwrap();
+        }
+        writeln!(html, "</table>").unwrap();
+        writeln!(html, "<dl>").unwrap();
+        writeln!(html, "<dt>Fees</dt><dd>{:.2}</dd>", self.total_fees).unwrap();
+        writeln!(html, "<dt>Interest</dt><dd>{:.2}</dd>", self.total_interest).unwrap();
+        writeln!(html, "<dt>Closing balance</dt><dd>{:.2}</dd>", self.closing_balance).unwrap();
+        writeln!(html, "</dl>").unwrap();
+        writeln!(html, "</section>").unwrap();
+        html
+    }
+}
+
+/// Statement of every account of a wallet that was open during a period, including accounts closed since it started
+#[derive(Clone, Debug, PartialEq)]
+pub struct WalletStatement {
+    pub wallet_id: String,
+    pub period_start: u64,
+    pub period_end: u64,
+    pub accounts: Vec<AccountStatement>,
+}
+
+impl WalletStatement {
+    pub fn generate(wallet: &dyn Wallet, period_start: u64, period_end: u64) -> Result<Self, String> {
+        let accounts = wallet.get_accounts()
+            .into_iter()
+            .chain(wallet.get_accounts_closed_since(period_start))
+            .map(|account| AccountStatement::generate(account, period_start, period_end))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(WalletStatement {
+            wallet_id: wallet.get_wallet_id().to_string(),
+            period_start,
+            period_end,
+            accounts,
+        })
+    }
+
+    /// Closing balance per currency across the wallet's accounts
+    pub fn closing_balances(&self) -> BTreeMap<String, f64> {
+        let mut balances = BTreeMap::new();
+        for account in self.accounts.iter() {
+            *balances.entry(account.currency.clone()).or_insert(0.0) += account.closing_balance;
+        }
+        balances
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        writeln!(text, "Statement for wallet {}", self.wallet_id).unwrap();
+        writeln!(text, "Period: {} to {} UTC", format_timestamp(self.period_start), format_timestamp(self.period_end)).unwrap();
+        for account in self.accounts.iter() {
+            writeln!(text).unwrap();
+            text.push_str(&account.to_text());
+        }
+        writeln!(text).unwrap();
+        writeln!(text, "Closing balances:").unwrap();
+        for (currency, balance) in self.closing_balances() {
+            writeln!(text, "  {}: {:.2}", currency, balance).unwrap();
+        }
+        text
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("account_number,currency,date,type,amount,balance,counterparty\n");
+        for account in self.accounts.iter() {
+            account.write_csv_rows(&mut csv);
+        }
+        csv
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut body = String::new();
+        writeln!(body, "<h1>Wallet {}</h1>", html_escape(&self.wallet_id)).unwrap();
+        for account in self.accounts.iter() {
+            body.push_str(&account.html_section());
+        }
+        writeln!(body, "<h2>Closing balances</h2>").unwrap();
+        writeln!(body, "<table>").unwrap();
+        for (currency, balance) in self.closing_balances() {
+            writeln!(body, "<tr><td>{}</td><td class=\"amount\">{:.2}</td></tr>", html_escape(&currency), balance).unwrap();
+        }
+        writeln!(body, "</table>").unwrap();
+        html_page(&format!("Statement {}", self.wallet_id), &body)
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn html_escape(value: &str) -> String {
+    value.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html_page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n\
+         body {{ font-family: sans-serif; margin: 2em; }}\n\
+         table {{ border-collapse: collapse; }}\n\
+         th, td {{ border: 1px solid #ccc; padding: 4px 8px; }}\n\
+         td.amount {{ text-align: right; }}\n\
+         dt {{ font-weight: bold; }}\n\
+         </style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        html_escape(title),
+        body
+    )
+}
@@ -0,0 +1,803 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::rc::Rc;
+use std::thread;
+use std::time::Duration;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use crate::accounts::{Account, AccountResponse, AccountTransferResponse, AccountType, DisplayAccount};
+use crate::basic_account::BasicAccount;
+use crate::basic_wallet::BasicWallet;
+use crate::events::EventBus;
+use crate::multi_currency_wallet::MultiCurrencyWallet;
+use crate::premium_account::PremiumAccount;
+use crate::reconciliation::RECONCILIATION_TOLERANCE;
+use crate::rendering::{AccountSummary, WalletSummary};
+use crate::transactions::Transaction;
+use crate::wallets::{DisplayWallet, TransferResponse, Wallet, WalletType, WithdrawWalletResponse};
+
+/// Currency no generated wallet holds, used to provoke failures
+pub const UNKNOWN_CURRENCY: &str = "XXX";
+
+/// Operation the harness applies to its wallets, which are addressed by index
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operation {
+    Deposit { wallet: usize, currency: String, amount: f64 },
+    Withdraw { wallet: usize, currency: String, amount: f64 },
+    Transfer { from_wallet: usize, to_wallet: usize, currency: String, amount: f64 },
+    /// Transfer whose recipient refuses the credit, so a debited sender must be rolled back.
+    /// Other faults armed on the recipient's `ReceiveTransfer` are disarmed afterwards.
+    RefusedTransfer { from_wallet: usize, to_wallet: usize, currency: String, amount: f64 },
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Operation::Deposit { wallet, currency, amount } => write!(f, "deposit {} {} into wallet {}", amount, currency, wallet),
+            Operation::Withdraw { wallet, currency, amount } => write!(f, "withdraw {} {} from wallet {}", amount, currency, wallet),
+            Operation::Transfer { from_wallet, to_wallet, currency, amount } => {
+                write!(f, "transfer {} {} from wallet {} to wallet {}", amount, currency, from_wallet, to_wallet)
+            }
+            Operation::RefusedTransfer { from_wallet, to_wallet, currency, amount } => {
+                write!(f, "transfer {} {} from wallet {} to wallet {}, refused by the recipient", amount, currency, from_wallet, to_wallet)
+            }
+        }
+    }
+}
+
+/// State of one account at a point in time
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountState {
+    pub currency: String,
+    pub balance: f64,
+    pub overdraft_limit: f64,
+    pub history_length: usize,
+}
+
+/// State of every open account of a set of wallets, keyed by account number
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    pub accounts: BTreeMap<String, AccountState>,
+}
+
+impl Snapshot {
+    pub fn capture<'a>(wallets: impl IntoIterator<Item = &'a dyn Wallet>) -> Self {
+        let accounts = wallets.into_iter()
+            .flat_map(|wallet| wallet.get_accounts())
+            .map(|account| {
+                (account.get_account_number().to_string(), AccountState {
+                    currency: account.get_currency().to_string(),
+                    balance: account.get_balance(),
+                    overdraft_limit: account.get_overdraft_limit(),
+                    history_length: account.get_history().len(),
+                })
+            })
+            .collect();
+        Snapshot { accounts }
+    }
+
+    /// Sum of the balances per currency
+    pub fn totals(&self) -> BTreeMap<String, f64> {
+        let mut totals = BTreeMap::new();
+        for account in self.accounts.values() {
+            *totals.entry(account.currency.clone()).or_insert(0.0) += account.balance;
+        }
+        totals
+    }
+}
+
+/// No account is below its overdraft floor (zero for accounts without an overdraft)
+pub fn check_overdraft_floors(snapshot: &Snapshot) -> Result<(), String> {
+    for (account_number, account) in snapshot.accounts.iter() {
+        if account.balance < -account.overdraft_limit - RECONCILIATION_TOLERANCE {
+            return Err(format!(
+                "Account {} is at {} {}, below its overdraft floor of {}",
+                account_number, account.balance, account.currency, -account.overdraft_limit
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Money per currency equals what came in from outside minus what went out
+pub fn check_conservation(snapshot: &Snapshot, external_flows: &BTreeMap<String, f64>) -> Result<(), String> {
+    let totals = snapshot.totals();
+    for currency in totals.keys().chain(external_flows.keys()) {
+        let total = totals.get(currency).copied().unwrap_or(0.0);
+        let expected = external_flows.get(currency).copied().unwrap_or(0.0);
+        if (total - expected).abs() > RECONCILIATION_TOLERANCE {
+            return Err(format!("{} total is {} but external deposits and withdrawals add up to {}", currency, total, expected));
+        }
+    }
+    Ok(())
+}
+
+/// A failed operation changed no balance and recorded no transaction. A reversed debit may
+/// leave floating point drift in the balance.
+pub fn check_untouched(before: &Snapshot, after: &Snapshot) -> Result<(), String> {
+    let numbers: BTreeSet<&String> = before.accounts.keys().chain(after.accounts.keys()).collect();
+    let changed: Vec<&str> = numbers.into_iter()
+        .filter(|number| !same_state(before.accounts.get(*number), after.accounts.get(*number)))
+        .map(|number| number.as_str())
+        .collect();
+    if changed.is_empty() {
+        return Ok(());
+    }
+    Err(format!("Failed operation changed accounts {}", changed.join(", ")))
+}
+
+fn same_state(before: Option<&AccountState>, after: Option<&AccountState>) -> bool {
+    match (before, after) {
+        (Some(before), Some(after)) => {
+            before.currency == after.currency
+                && (before.balance - after.balance).abs() <= RECONCILIATION_TOLERANCE
+                && before.overdraft_limit == after.overdraft_limit
+                && before.history_length == after.history_length
+        }
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Result of a harness run
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunSummary {
+    pub seed: u64,
+    pub steps: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// Transfers whose sender was debited before the recipient refused the credit
+    pub rolled_back: usize,
+}
+
+/// Property-based harness: applies seeded random sequences of deposits, withdrawals and
+/// transfers, many of them bound to fail, and checks the money invariants after every step.
+/// The same seed always produces the same sequence, so a failure can be replayed.
+/// The wallets are `FaultyWallet`s, so faults can be injected to exercise the rollback paths.
+pub struct InvariantHarness {
+    seed: u64,
+    rng: StdRng,
+    wallets: Vec<FaultyWallet>,
+    currencies: Vec<String>,
+    external_flows: BTreeMap<String, f64>,
+    log: Vec<Operation>,
+    rolled_back: usize,
+}
+
+impl InvariantHarness {
+    /// Harness over a basic USD wallet, a basic wallet with a premium EUR account and a
+    /// multi-currency wallet with USD, EUR and GBP accounts
+    pub fn new(seed: u64) -> Self {
+        let mut multi = MultiCurrencyWallet::new();
+        multi.add_account(Box::new(BasicAccount::new("USD"))).unwrap();
+        multi.add_account(Box::new(PremiumAccount::new("EUR", 100.0))).unwrap();
+        multi.add_account(Box::new(BasicAccount::new("GBP"))).unwrap();
+        multi.add_account(Box::new(PremiumAccount::new("USD", 25.0))).unwrap();
+        let wallets = vec![
+            FaultyWallet::new(Box::new(BasicWallet::new(BasicAccount::new("USD")))),
+            FaultyWallet::new(Box::new(BasicWallet::new(PremiumAccount::new("EUR", 50.0)))),
+            FaultyWallet::new(Box::new(multi)),
+        ];
+        Self::with_wallets(seed, wallets).unwrap()
+    }
+
+    /// Harness over the given wallets, which must hold at least two wallets; their current
+    /// balances count as money already in the system
+    pub fn with_wallets(seed: u64, wallets: Vec<FaultyWallet>) -> Result<Self, String> {
+        if wallets.len() < 2 {
+            return Err("The harness needs at least two wallets".to_string());
+        }
+        let snapshot = Snapshot::capture(wallets.iter().map(|wallet| wallet as &dyn Wallet));
+        if snapshot.accounts.is_empty() {
+            return Err("The harness needs at least one account".to_string());
+        }
+        let mut currencies: Vec<String> = snapshot.accounts.values().map(|account| account.currency.clone()).collect();
+        currencies.sort();
+        currencies.dedup();
+        Ok(InvariantHarness {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            wallets,
+            currencies,
+            external_flows: snapshot.totals(),
+            log: Vec::new(),
+            rolled_back: 0,
+        })
+    }
+
+    pub fn get_wallets(&self) -> &[FaultyWallet] {
+        &self.wallets
+    }
+
+    /// Operations applied so far, oldest first
+    pub fn get_log(&self) -> &[Operation] {
+        &self.log
+    }
+
+    /// Net external deposits minus withdrawals per currency, including the starting balances
+    pub fn get_external_flows(&self) -> &BTreeMap<String, f64> {
+        &self.external_flows
+    }
+
+    /// Draws the next random operation
+    pub fn next_operation(&mut self) -> Operation {
+        let wallet = self.rng.random_range(0..self.wallets.len());
+        // the unknown currency is drawn rarely
+        let currency = if self.rng.random_bool(0.05) {
+            UNKNOWN_CURRENCY.to_string()
+        } else {
+            self.currencies[self.rng.random_range(0..self.currencies.len())].clone()
+        };
+        let amount = match self.rng.random_range(0..20) {
+            0 => -((self.rng.random_range(1..10_000) as f64) / 100.0),
+            1 => 0.0,
+            2 => self.rng.random_range(1_000..100_000) as f64,
+            _ => (self.rng.random_range(1..30_000) as f64) / 100.0,
+        };
+        match self.rng.random_range(0..3) {
+            0 => Operation::Deposit { wallet, currency, amount },
+            1 => Operation::Withdraw { wallet, currency, amount },
+            _ => {
+                let offset = self.rng.random_range(1..self.wallets.len());
+                let to_wallet = (wallet + offset) % self.wallets.len();
+                if self.rng.random_bool(0.25) {
+                    Operation::RefusedTransfer { from_wallet: wallet, to_wallet, currency, amount }
+                } else {
+                    Operation::Transfer { from_wallet: wallet, to_wallet, currency, amount }
+                }
+            }
+        }
+    }
+
+    /// Applies an operation, returning whether the wallets reported success
+    pub fn apply(&mut self, operation: &Operation) -> bool {
+        self.log.push(operation.clone());
+        match operation {
+            Operation::Deposit { wallet, currency, amount } => {
+                let succeeded = self.wallets[*wallet].deposit(currency, *amount).is_successful;
+                if succeeded {
+                    *self.external_flows.entry(currency.clone()).or_insert(0.0) += amount;
+                }
+                succeeded
+            }
+            Operation::Withdraw { wallet, currency, amount } => {
+                let succeeded = self.wallets[*wallet].withdraw(currency, *amount).is_successful;
+                if succeeded {
+                    *self.external_flows.entry(currency.clone()).or_insert(0.0) -= amount;
+                }
+                succeeded
+            }
+            Operation::Transfer { from_wallet, to_wallet, currency, amount } => {
+                self.transfer(*from_wallet, *to_wallet, currency, *amount)
+            }
+            Operation::RefusedTransfer { from_wallet, to_wallet, currency, amount } => {
+                let faults = self.wallets[*to_wallet].get_faults();
+                let received = faults.get_calls(WalletCall::ReceiveTransfer);
+                faults.inject_once(WalletCall::ReceiveTransfer, Fault::Fail("Transfer refused by the recipient".to_string()));
+                let succeeded = self.transfer(*from_wallet, *to_wallet, currency, *amount);
+                // the refusal is disarmed when the transfer failed before reaching the recipient
+                faults.disarm(WalletCall::ReceiveTransfer);
+                if faults.get_calls(WalletCall::ReceiveTransfer) > received {
+                    self.rolled_back += 1;
+                }
+                succeeded
+            }
+        }
+    }
+
+    fn transfer(&mut self, from_wallet: usize, to_wallet: usize, currency: &str, amount: f64) -> bool {
+        let (from, to) = if from_wallet < to_wallet {
+            let (head, tail) = self.wallets.split_at_mut(to_wallet);
+            (&mut head[from_wallet], &mut tail[0])
+        } else {
+            let (head, tail) = self.wallets.split_at_mut(from_wallet);
+            (&mut tail[0], &mut head[to_wallet])
+        };
+        from.transfer(to, currency, amount).is_successful
+    }
+
+    /// Applies one operation and checks every invariant
+    pub fn step(&mut self, operation: &Operation) -> Result<bool, String> {
+        let before = self.snapshot();
+        let succeeded = self.apply(operation);
+        let after = self.snapshot();
+
+        let checked = check_overdraft_floors(&after)
+            .and_then(|_| check_conservation(&after, &self.external_flows))
+            .and_then(|_| if succeeded { Ok(()) } else { check_untouched(&before, &after) });
+        checked.map(|_| succeeded).map_err(|error| {
+            format!("Seed {}, step {} ({}): {}", self.seed, self.log.len(), operation, error)
+        })
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot::capture(self.wallets.iter().map(|wallet| wallet as &dyn Wallet))
+    }
+
+    /// Applies `steps` random operations, stopping at the first broken invariant
+    pub fn run(&mut self, steps: usize) -> Result<RunSummary, String> {
+        let rolled_back = self.rolled_back;
+        let mut summary = RunSummary { seed: self.seed, steps: 0, succeeded: 0, failed: 0, rolled_back: 0 };
+        for _ in 0..steps {
+            let operation = self.next_operation();
+            if self.step(&operation)? {
+                summary.succeeded += 1;
+            } else {
+                summary.failed += 1;
+            }
+            summary.steps += 1;
+        }
+        summary.rolled_back = self.rolled_back - rolled_back;
+        Ok(summary)
+    }
+}
+
+/// Misbehaviour injected into a call of a `FaultyAccount` or `FaultyWallet`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Fault {
+    /// The call returns an unsuccessful response with this message, without reaching the wrapped account or wallet
+    Fail(String),
+    /// The call panics with this message, e.g. to prove that it is never made
+    Panic(String),
+    /// The call sleeps, then goes through
+    Delay(Duration),
+}
+
+/// Mutating calls of an `Account` that faults can be injected into
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AccountCall {
+    Deposit,
+    Withdraw,
+    DepositFrom,
+    WithdrawTo,
+    ReverseTransferOut,
+    ChargeFee,
+    PayInterest,
+    Transfer,
+}
+
+/// Mutating calls of a `Wallet` that faults can be injected into
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WalletCall {
+    AddAccount,
+    Deposit,
+    Withdraw,
+    Transfer,
+    TransferFromAccount,
+    DepositToAccount,
+    WithdrawFromAccount,
+    ReceiveTransfer,
+}
+
+struct ArmedFault<C> {
+    call: C,
+    fault: Fault,
+    /// Matching calls to let through before the fault applies
+    skip: usize,
+    /// Matching calls the fault still applies to, or `None` for all of them
+    remaining: Option<usize>,
+}
+
+struct FaultState<C> {
+    armed: Vec<ArmedFault<C>>,
+    calls: BTreeMap<C, usize>,
+}
+
+/// Faults armed on a fake and the calls it received. Clones are handles to the same state,
+/// so a test keeps control of a fake after moving it into a wallet.
+pub struct FaultInjector<C> {
+    state: Rc<RefCell<FaultState<C>>>,
+}
+
+impl<C> Clone for FaultInjector<C> {
+    fn clone(&self) -> Self {
+        FaultInjector { state: Rc::clone(&self.state) }
+    }
+}
+
+impl<C: Copy + Ord> Default for FaultInjector<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Copy + Ord> FaultInjector<C> {
+    pub fn new() -> Self {
+        FaultInjector {
+            state: Rc::new(RefCell::new(FaultState { armed: Vec::new(), calls: BTreeMap::new() })),
+        }
+    }
+
+    /// Applies the fault to every later `call`
+    pub fn inject(&self, call: C, fault: Fault) {
+        self.arm(call, fault, 0, None);
+    }
+
+    /// Applies the fault to the next `call` only
+    pub fn inject_once(&self, call: C, fault: Fault) {
+        self.arm(call, fault, 0, Some(1));
+    }
+
+    /// Applies the fault to the `n`th later `call` only, counting from 1
+    pub fn inject_nth(&self, call: C, n: usize, fault: Fault) {
+        self.arm(call, fault, n.saturating_sub(1), Some(1));
+    }
+
+    /// Disarms every fault; the call counts are kept
+    pub fn clear(&self) {
+        self.state.borrow_mut().armed.clear();
+    }
+
+    /// Disarms the faults armed for `call`
+    pub fn disarm(&self, call: C) {
+        self.state.borrow_mut().armed.retain(|armed| armed.call != call);
+    }
+
+    /// Number of times `call` was made, including the faulted ones
+    pub fn get_calls(&self, call: C) -> usize {
+        self.state.borrow().calls.get(&call).copied().unwrap_or(0)
+    }
+
+    /// Records a call, applies a panic or delay armed for it and returns the failure to report, if any
+    pub fn check(&self, call: C) -> Result<(), String> {
+        match self.trigger(call) {
+            Some(Fault::Fail(message)) => Err(message),
+            Some(Fault::Panic(message)) => panic!("{}", message),
+            Some(Fault::Delay(duration)) => {
+                thread::sleep(duration);
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    fn arm(&self, call: C, fault: Fault, skip: usize, remaining: Option<usize>) {
+        self.state.borrow_mut().armed.push(ArmedFault { call, fault, skip, remaining });
+    }
+
+    /// The first armed fault that applies wins; the borrow ends before a panic is raised
+    fn trigger(&self, call: C) -> Option<Fault> {
+        let mut state = self.state.borrow_mut();
+        *state.calls.entry(call).or_insert(0) += 1;
+        let mut triggered = None;
+        for armed in state.armed.iter_mut().filter(|armed| armed.call == call) {
+            if armed.skip > 0 {
+                armed.skip -= 1;
+                continue;
+            }
+            if triggered.is_none() && armed.remaining != Some(0) {
+                armed.remaining = armed.remaining.map(|remaining| remaining - 1);
+                triggered = Some(armed.fault.clone());
+            }
+        }
+        state.armed.retain(|armed| armed.remaining != Some(0));
+        triggered
+    }
+}
+
+/// Account that delegates to a real one unless a fault is injected into the call.
+/// Only built for tests and the `test-support` feature, like the rest of this module.
+pub struct FaultyAccount {
+    account: Box<dyn Account>,
+    faults: FaultInjector<AccountCall>,
+}
+
+impl FaultyAccount {
+    pub fn new(account: Box<dyn Account>) -> Self {
+        FaultyAccount { account, faults: FaultInjector::new() }
+    }
+
+    /// Handle to the faults of this account
+    pub fn get_faults(&self) -> FaultInjector<AccountCall> {
+        self.faults.clone()
+    }
+
+    fn failed(&self, error: String) -> AccountResponse {
+        AccountResponse {
+            account_number: self.account.get_account_number().to_string(),
+            account_type: self.account.get_account_type(),
+            currency: self.account.get_currency().to_string(),
+            balance: self.account.get_balance(),
+            is_successful: false,
+            error_message: Some(error),
+        }
+    }
+}
+
+impl DisplayAccount for FaultyAccount {
+    fn summary(&self) -> AccountSummary {
+        self.account.summary()
+    }
+}
+
+impl Account for FaultyAccount {
+    fn get_balance(&self) -> f64 {
+        self.account.get_balance()
+    }
+
+    fn deposit(&mut self, amount: f64) -> AccountResponse {
+        match self.faults.check(AccountCall::Deposit) {
+            Ok(()) => self.account.deposit(amount),
+            Err(error) => self.failed(error),
+        }
+    }
+
+    fn withdraw(&mut self, amount: f64) -> AccountResponse {
+        match self.faults.check(AccountCall::Withdraw) {
+            Ok(()) => self.account.withdraw(amount),
+            Err(error) => self.failed(error),
+        }
+    }
+
+    fn deposit_from(&mut self, amount: f64, from_account_number: &str) -> AccountResponse {
+        match self.faults.check(AccountCall::DepositFrom) {
+            Ok(()) => self.account.deposit_from(amount, from_account_number),
+            Err(error) => self.failed(error),
+        }
+    }
+
+    fn withdraw_to(&mut self, amount: f64, to_account_number: &str) -> AccountResponse {
+        match self.faults.check(AccountCall::WithdrawTo) {
+            Ok(()) => self.account.withdraw_to(amount, to_account_number),
+            Err(error) => self.failed(error),
+        }
+    }
+
+    fn reverse_transfer_out(&mut self, amount: f64, to_account_number: &str) -> AccountResponse {
+        match self.faults.check(AccountCall::ReverseTransferOut) {
+            Ok(()) => self.account.reverse_transfer_out(amount, to_account_number),
+            Err(error) => self.failed(error),
+        }
+    }
+
+    fn charge_fee(&mut self, amount: f64) -> AccountResponse {
+        match self.faults.check(AccountCall::ChargeFee) {
+            Ok(()) => self.account.charge_fee(amount),
+            Err(error) => self.failed(error),
+        }
+    }
+
+    fn pay_interest(&mut self, amount: f64) -> AccountResponse {
+        match self.faults.check(AccountCall::PayInterest) {
+            Ok(()) => self.account.pay_interest(amount),
+            Err(error) => self.failed(error),
+        }
+    }
+
+    fn get_currency(&self) -> &str {
+        self.account.get_currency()
+    }
+
+    fn get_account_number(&self) -> &str {
+        self.account.get_account_number()
+    }
+
+    fn get_account_type(&self) -> AccountType {
+        self.account.get_account_type()
+    }
+
+    fn get_history(&self) -> &[Transaction] {
+        self.account.get_history()
+    }
+
+    fn transfer(&mut self, to_account: &mut dyn Account, amount: f64) -> AccountTransferResponse {
+        match self.faults.check(AccountCall::Transfer) {
+            Ok(()) => self.account.transfer(to_account, amount),
+            Err(error) => AccountTransferResponse {
+                account_number: self.account.get_account_number().to_string(),
+                account_type: self.account.get_account_type(),
+                currency: self.account.get_currency().to_string(),
+                recipient_account_number: to_account.get_account_number().to_string(),
+                recipient_account_type: to_account.get_account_type(),
+                balance: self.account.get_balance(),
+                is_successful: false,
+                error_message: Some(error),
+            },
+        }
+    }
+
+    fn get_overdraft_limit(&self) -> f64 {
+        self.account.get_overdraft_limit()
+    }
+
+    fn set_event_bus(&mut self, event_bus: Option<EventBus>) {
+        self.account.set_event_bus(event_bus);
+    }
+}
+
+/// Wallet that delegates to a real one unless a fault is injected into the call.
+/// Only built for tests and the `test-support` feature, like the rest of this module.
+pub struct FaultyWallet {
+    wallet: Box<dyn Wallet>,
+    faults: FaultInjector<WalletCall>,
+}
+
+impl FaultyWallet {
+    pub fn new(wallet: Box<dyn Wallet>) -> Self {
+        FaultyWallet { wallet, faults: FaultInjector::new() }
+    }
+
+    /// Handle to the faults of this wallet
+    pub fn get_faults(&self) -> FaultInjector<WalletCall> {
+        self.faults.clone()
+    }
+
+    pub fn get_wallet(&self) -> &dyn Wallet {
+        self.wallet.as_ref()
+    }
+
+    fn account_failed(&self, account_number: Option<&str>, currency: &str, error: String) -> AccountResponse {
+        let account = match account_number {
+            Some(account_number) => self.wallet.get_account_by_number(account_number).ok(),
+            None => self.wallet.get_account_by_currency(currency),
+        };
+        AccountResponse {
+            account_number: account.map(|a| a.get_account_number().to_string()).unwrap_or_default(),
+            account_type: account.map(|a| a.get_account_type()).unwrap_or(AccountType::Basic),
+            currency: account.map(|a| a.get_currency().to_string()).unwrap_or(currency.to_string()),
+            balance: account.map(|a| a.get_balance()).unwrap_or(0.0),
+            is_successful: false,
+            error_message: Some(error),
+        }
+    }
+
+    fn withdraw_failed(&self, account_number: Option<&str>, currency: &str, amount: f64, error: String) -> WithdrawWalletResponse {
+        let response = self.account_failed(account_number, currency, error);
+        WithdrawWalletResponse {
+            wallet_id: self.wallet.get_wallet_id().to_string(),
+            wallet_type: self.wallet.get_wallet_type(),
+            currency: response.currency,
+            amount,
+            account_number: response.account_number,
+            account_type: response.account_type,
+            balance: response.balance,
+            is_successful: false,
+            error_message: response.error_message,
+        }
+    }
+
+    fn transfer_failed(
+        &self,
+        to_wallet: &dyn Wallet,
+        sender_account_number: String,
+        recipient_account_number: String,
+        currency: &str,
+        amount: f64,
+        error: String,
+    ) -> TransferResponse {
+        TransferResponse {
+            currency: currency.to_string(),
+            amount,
+            sender_account_number,
+            sender_wallet_id: self.wallet.get_wallet_id().to_string(),
+            sender_wallet_type: self.wallet.get_wallet_type(),
+            recipient_account_number,
+            recipient_wallet_id: to_wallet.get_wallet_id().to_string(),
+            recipient_wallet_type: to_wallet.get_wallet_type(),
+            is_successful: false,
+            error_message: Some(error),
+        }
+    }
+}
+
+impl DisplayWallet for FaultyWallet {
+    fn summary(&self) -> WalletSummary {
+        self.wallet.summary()
+    }
+}
+
+impl Wallet for FaultyWallet {
+    fn add_account(&mut self, account: Box<dyn Account>) -> Result<&dyn Account, String> {
+        self.faults.check(WalletCall::AddAccount)?;
+        self.wallet.add_account(account)
+    }
+
+    fn balance(&self, currency: &str) -> Result<f64, String> {
+        self.wallet.balance(currency)
+    }
+
+    fn get_wallet_id(&self) -> &str {
+        self.wallet.get_wallet_id()
+    }
+
+    fn get_wallet_type(&self) -> WalletType {
+        self.wallet.get_wallet_type()
+    }
+
+    fn find_account_index_by_currency(&self, currency: &str) -> Option<usize> {
+        self.wallet.find_account_index_by_currency(currency)
+    }
+
+    fn get_account_number_by_index(&self, index: usize) -> Option<&str> {
+        self.wallet.get_account_number_by_index(index)
+    }
+
+    fn get_account_by_currency(&self, currency: &str) -> Option<&dyn Account> {
+        self.wallet.get_account_by_currency(currency)
+    }
+
+    fn get_accounts(&self) -> Vec<&dyn Account> {
+        self.wallet.get_accounts()
+    }
+
+    fn get_accounts_closed_since(&self, timestamp: u64) -> Vec<&dyn Account> {
+        self.wallet.get_accounts_closed_since(timestamp)
+    }
+
+    fn get_account_by_number(&self, account_number: &str) -> Result<&dyn Account, String> {
+        self.wallet.get_account_by_number(account_number)
+    }
+
+    fn transfer(&mut self, to_wallet: &mut dyn Wallet, currency: &str, amount: f64) -> TransferResponse {
+        match self.faults.check(WalletCall::Transfer) {
+            Ok(()) => self.wallet.transfer(to_wallet, currency, amount),
+            Err(error) => {
+                let sender_account_number = self.wallet.get_account_by_currency(currency)
+                    .map(|account| account.get_account_number().to_string())
+                    .unwrap_or_default();
+                let recipient_account_number = to_wallet.get_account_by_currency(currency)
+                    .map(|account| account.get_account_number().to_string())
+                    .unwrap_or_default();
+                self.transfer_failed(to_wallet, sender_account_number, recipient_account_number, currency, amount, error)
+            }
+        }
+    }
+
+    fn deposit(&mut self, currency: &str, amount: f64) -> AccountResponse {
+        match self.faults.check(WalletCall::Deposit) {
+            Ok(()) => self.wallet.deposit(currency, amount),
+            Err(error) => self.account_failed(None, currency, error),
+        }
+    }
+
+    fn withdraw(&mut self, currency: &str, amount: f64) -> WithdrawWalletResponse {
+        match self.faults.check(WalletCall::Withdraw) {
+            Ok(()) => self.wallet.withdraw(currency, amount),
+            Err(error) => self.withdraw_failed(None, currency, amount, error),
+        }
+    }
+
+    fn transfer_from_account(
+        &mut self,
+        from_account_number: &str,
+        to_wallet: &mut dyn Wallet,
+        to_account_number: &str,
+        amount: f64,
+    ) -> TransferResponse {
+        match self.faults.check(WalletCall::TransferFromAccount) {
+            Ok(()) => self.wallet.transfer_from_account(from_account_number, to_wallet, to_account_number, amount),
+            Err(error) => {
+                let currency = self.wallet.get_account_by_number(from_account_number)
+                    .map(|account| account.get_currency().to_string())
+                    .unwrap_or_default();
+                self.transfer_failed(to_wallet, from_account_number.to_string(), to_account_number.to_string(), &currency, amount, error)
+            }
+        }
+    }
+
+    fn deposit_to_account(&mut self, account_number: &str, amount: f64) -> AccountResponse {
+        match self.faults.check(WalletCall::DepositToAccount) {
+            Ok(()) => self.wallet.deposit_to_account(account_number, amount),
+            Err(error) => self.account_failed(Some(account_number), "", error),
+        }
+    }
+
+    fn withdraw_from_account(&mut self, account_number: &str, amount: f64) -> WithdrawWalletResponse {
+        match self.faults.check(WalletCall::WithdrawFromAccount) {
+            Ok(()) => self.wallet.withdraw_from_account(account_number, amount),
+            Err(error) => self.withdraw_failed(Some(account_number), "", amount, error),
+        }
+    }
+
+    fn receive_transfer(&mut self, account_number: &str, amount: f64, from_account_number: &str) -> AccountResponse {
+        match self.faults.check(WalletCall::ReceiveTransfer) {
+            Ok(()) => self.wallet.receive_transfer(account_number, amount, from_account_number),
+            Err(error) => self.account_failed(Some(account_number), "", error),
+        }
+    }
+
+    fn set_event_bus(&mut self, event_bus: Option<EventBus>) {
+        self.wallet.set_event_bus(event_bus);
+    }
+}
@@ -0,0 +1,147 @@
+use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Enum defining the kinds of balance movements recorded in an account history
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TransactionKind {
+    Deposit,
+    Withdrawal,
+    TransferIn,
+    TransferOut,
+    /// Charge taken by the wallet provider
+    Fee,
+    /// Interest paid into the account
+    Interest,
+}
+
+impl fmt::Display for TransactionKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransactionKind::Deposit => write!(f, "Deposit"),
+            TransactionKind::Withdrawal => write!(f, "Withdrawal"),
+            TransactionKind::TransferIn => write!(f, "TransferIn"),
+            TransactionKind::TransferOut => write!(f, "TransferOut"),
+            TransactionKind::Fee => write!(f, "Fee"),
+            TransactionKind::Interest => write!(f, "Interest"),
+        }
+    }
+}
+
+impl FromStr for TransactionKind {
+    type Err = String;
+
+    /// Parses the names printed by `Display`, ignoring case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "deposit" => Ok(TransactionKind::Deposit),
+            "withdrawal" => Ok(TransactionKind::Withdrawal),
+            "transferin" => Ok(TransactionKind::TransferIn),
+            "transferout" => Ok(TransactionKind::TransferOut),
+            "fee" => Ok(TransactionKind::Fee),
+            "interest" => Ok(TransactionKind::Interest),
+            _ => Err(format!("Unknown transaction type: {}", s)),
+        }
+    }
+}
+
+/// A successful balance movement on an account
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Transaction {
+    pub kind: TransactionKind,
+    pub amount: f64,
+    pub balance_after: f64,
+    pub counterparty_account_number: Option<String>,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+impl Transaction {
+    pub fn new(kind: TransactionKind, amount: f64, balance_after: f64, counterparty_account_number: Option<&str>) -> Self {
+        Transaction {
+            kind,
+            amount,
+            balance_after,
+            counterparty_account_number: counterparty_account_number.map(|n| n.to_string()),
+            timestamp: current_timestamp(),
+        }
+    }
+
+    /// Amount with the sign of its effect on the balance
+    pub fn signed_amount(&self) -> f64 {
+        match self.kind {
+            TransactionKind::Deposit | TransactionKind::TransferIn | TransactionKind::Interest => self.amount,
+            TransactionKind::Withdrawal | TransactionKind::TransferOut | TransactionKind::Fee => -self.amount,
+        }
+    }
+}
+
+/// Current time in seconds since the Unix epoch
+pub fn current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Seconds since the Unix epoch for a UTC date and time
+pub fn timestamp_from_date_time(year: i64, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> Result<u64, String> {
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) || hour > 23 || minute > 59 || second > 59 {
+        return Err(format!("Invalid date: {:04}-{:02}-{:02} {:02}:{:02}:{:02}", year, month, day, hour, minute, second));
+    }
+    // days since the epoch, from Howard Hinnant's days_from_civil
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let year_of_era = y - era * 400;
+    let month_index = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    let seconds = days * 86_400 + hour as i64 * 3_600 + minute as i64 * 60 + second as i64;
+    u64::try_from(seconds).map_err(|_| format!("Date before the Unix epoch: {:04}-{:02}-{:02}", year, month, day))
+}
+
+/// UTC `(year, month, day, hour, minute, second)` of a Unix timestamp
+pub fn date_time_from_timestamp(timestamp: u64) -> (i64, u32, u32, u32, u32, u32) {
+    let days = (timestamp / 86_400) as i64;
+    let seconds_of_day = (timestamp % 86_400) as u32;
+    // Howard Hinnant's civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day, seconds_of_day / 3_600, seconds_of_day / 60 % 60, seconds_of_day % 60)
+}
+
+/// Formats a Unix timestamp as `YYYY-MM-DD HH:MM:SS` in UTC
+pub fn format_timestamp(timestamp: u64) -> String {
+    let (year, month, day, hour, minute, second) = date_time_from_timestamp(timestamp);
+    format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", year, month, day, hour, minute, second)
+}
+
+/// Parses a `YYYY-MM-DD` date as midnight UTC
+pub fn parse_date(date: &str) -> Result<u64, String> {
+    let parts: Vec<&str> = date.trim().split('-').collect();
+    let parse = |part: &str| part.parse::<u32>().map_err(|_| format!("Invalid date: {}", date));
+    match parts.as_slice() {
+        [year, month, day] if year.len() == 4 => {
+            timestamp_from_date_time(parse(year)? as i64, parse(month)?, parse(day)?, 0, 0, 0)
+        }
+        _ => Err(format!("Invalid date: {}, expected YYYY-MM-DD", date)),
+    }
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        4 | 6 | 9 | 11 => 30,
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        _ => 31,
+    }
+}
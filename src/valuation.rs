@@ -0,0 +1,77 @@
+use crate::accounts::{Account, AccountType};
+use crate::exchange::ExchangeRateSource;
+
+/// Value of a single account expressed in the reporting currency
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccountValuation {
+    pub account_number: String,
+    pub account_type: AccountType,
+    pub currency: String,
+    /// Balance in the account currency; negative while a `PremiumAccount` is overdrawn
+    pub balance: f64,
+    pub rate: f64,
+    pub value: f64,
+}
+
+/// Exchange rate applied while valuing a wallet
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExchangeRateUsed {
+    pub from_currency: String,
+    pub to_currency: String,
+    pub rate: f64,
+}
+
+/// Value of a whole wallet in its reporting currency, with the per-account breakdown
+#[derive(Clone, Debug, PartialEq)]
+pub struct WalletValuation {
+    pub wallet_id: String,
+    pub base_currency: String,
+    pub accounts: Vec<AccountValuation>,
+    pub rates_used: Vec<ExchangeRateUsed>,
+    pub total: f64,
+}
+
+/// Converts every account balance into `base_currency` and sums them up
+pub fn value_accounts(
+    wallet_id: &str,
+    accounts: &[&dyn Account],
+    base_currency: &str,
+    rates: &dyn ExchangeRateSource,
+) -> Result<WalletValuation, String> {
+    let mut account_valuations = Vec::new();
+    let mut rates_used: Vec<ExchangeRateUsed> = Vec::new();
+
+    for account in accounts {
+        let currency = account.get_currency();
+        let rate = rates.get_rate(currency, base_currency).ok_or(format!(
+            "No exchange rate available from {} to {}",
+            currency, base_currency
+        ))?;
+
+        if !rates_used.iter().any(|used| used.from_currency == currency) {
+            rates_used.push(ExchangeRateUsed {
+                from_currency: currency.to_string(),
+                to_currency: base_currency.to_string(),
+                rate,
+            });
+        }
+
+        account_valuations.push(AccountValuation {
+            account_number: account.get_account_number().to_string(),
+            account_type: account.get_account_type(),
+            currency: currency.to_string(),
+            balance: account.get_balance(),
+            rate,
+            value: account.get_balance() * rate,
+        });
+    }
+
+    let total = account_valuations.iter().map(|valuation| valuation.value).sum();
+    Ok(WalletValuation {
+        wallet_id: wallet_id.to_string(),
+        base_currency: base_currency.to_string(),
+        accounts: account_valuations,
+        rates_used,
+        total,
+    })
+}
@@ -1,12 +1,38 @@
+use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
 use crate::accounts::{Account, AccountResponse, AccountType};
-use std::borrow::Cow;
+use crate::events::EventBus;
+use crate::rendering::{Render, RenderFormat, WalletSummary};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum WalletType {
     Basic,
     MultiCurrency,
 }
 
+impl FromStr for WalletType {
+    type Err = String;
+
+    /// Parses `basic` or `multi`/`multicurrency`, ignoring case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "basic" => Ok(WalletType::Basic),
+            "multi" | "multicurrency" => Ok(WalletType::MultiCurrency),
+            _ => Err(format!("Unknown wallet type: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for WalletType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WalletType::Basic => write!(f, "Basic"),
+            WalletType::MultiCurrency => write!(f, "MultiCurrency"),
+        }
+    }
+}
+
 pub struct TransferResponse  {
     pub currency: String,
     pub amount: f64,
@@ -32,8 +58,20 @@ pub struct WithdrawWalletResponse {
     pub error_message: Option<String>,
 }
 
+/// Structured view of a wallet and its accounts, rendered on demand instead of printed
 pub trait DisplayWallet {
-    fn display_details(&self);
+    fn summary(&self) -> WalletSummary;
+
+    /// Summary formatted as an aligned text table, JSON or Markdown
+    fn render(&self, format: RenderFormat) -> String {
+        self.summary().render(format)
+    }
+
+    /// Prints the text rendering to standard output
+    #[deprecated(note = "use `render(RenderFormat::Text)` and print the returned string")]
+    fn display_details(&self) {
+        print!("{}", self.render(RenderFormat::Text));
+    }
 }
 
 // trait with functions that must be implemented by all wallets
@@ -51,6 +89,15 @@ pub trait Wallet : DisplayWallet {
 
     fn get_account_by_currency(&self, currency: &str) -> Option<&dyn Account>;
 
+    /// Returns all accounts held by the wallet
+    fn get_accounts(&self) -> Vec<&dyn Account>;
+
+    /// Returns the accounts closed at or after `timestamp`, in the order they were closed
+    fn get_accounts_closed_since(&self, timestamp: u64) -> Vec<&dyn Account>;
+
+    /// Looks up an account by number, rejecting malformed account numbers
+    fn get_account_by_number(&self, account_number: &str) -> Result<&dyn Account, String>;
+
     fn transfer(
         &mut self,
         to_wallet: &mut dyn Wallet,
@@ -61,5 +108,24 @@ pub trait Wallet : DisplayWallet {
     fn deposit(&mut self, currency: &str, amount: f64) -> AccountResponse;
 
     fn withdraw(&mut self, currency: &str, amount: f64) -> WithdrawWalletResponse;
+
+    /// Transfers money from a specific account to a specific account in another wallet
+    fn transfer_from_account(
+        &mut self,
+        from_account_number: &str,
+        to_wallet: &mut dyn Wallet,
+        to_account_number: &str,
+        amount: f64,
+    ) -> TransferResponse;
+
+    fn deposit_to_account(&mut self, account_number: &str, amount: f64) -> AccountResponse;
+
+    fn withdraw_from_account(&mut self, account_number: &str, amount: f64) -> WithdrawWalletResponse;
+
+    /// Credits the receiving side of a transfer, recorded against the sender's account number
+    fn receive_transfer(&mut self, account_number: &str, amount: f64, from_account_number: &str) -> AccountResponse;
+
+    /// Attaches the bus that receives the events of the wallet and all of its accounts
+    fn set_event_bus(&mut self, event_bus: Option<EventBus>);
 }
 
@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod tests {
+    use wallet_system::account_number::AccountNumber;
+    use wallet_system::accounts::{Account, AccountType};
+    use wallet_system::basic_account::BasicAccount;
+    use wallet_system::basic_wallet::BasicWallet;
+    use wallet_system::multi_currency_wallet::MultiCurrencyWallet;
+    use wallet_system::premium_account::PremiumAccount;
+    use wallet_system::wallets::Wallet;
+
+    #[test]
+    fn test_generated_account_number_is_valid() {
+        let account = BasicAccount::new("USD");
+        let parsed = AccountNumber::parse(account.get_account_number()).unwrap();
+        assert_eq!(parsed.issued_account_type(), AccountType::Basic);
+        assert_eq!(parsed.as_str(), account.get_account_number());
+
+        let premium = PremiumAccount::new("USD", 100.0);
+        assert!(AccountNumber::is_valid(premium.get_account_number()));
+        assert!(premium.get_account_number().starts_with("Premium-"));
+    }
+
+    #[test]
+    fn test_single_character_typo_is_rejected() {
+        let account_number = AccountNumber::generate(AccountType::Basic).to_string();
+        let body_start = "Basic-".len();
+        for position in body_start..account_number.len() {
+            let original = account_number.as_bytes()[position];
+            if original == b'-' {
+                continue;
+            }
+            for replacement in b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789" {
+                if *replacement == original {
+                    continue;
+                }
+                let mut typo = account_number.clone().into_bytes();
+                typo[position] = *replacement;
+                let typo = String::from_utf8(typo).unwrap();
+                assert!(!AccountNumber::is_valid(&typo), "typo {} was accepted", typo);
+            }
+        }
+    }
+
+    #[test]
+    fn test_changed_account_type_prefix_is_rejected() {
+        assert!(AccountNumber::is_valid("Basic-7KQ2M9X4TR-40"));
+        let result = AccountNumber::parse("Premium-7KQ2M9X4TR-40");
+        assert!(result.unwrap_err().starts_with("Invalid check digits"));
+    }
+
+    #[test]
+    fn test_malformed_account_numbers_are_rejected() {
+        for value in ["", "Basic", "Basic-ABC-12", "Gold-ABCDEFGHIJ-12", "Basic-abcdefghij-12", "Basic-ABCDEFGHIJ-1X"] {
+            assert!(AccountNumber::parse(value).is_err(), "{} was accepted", value);
+        }
+    }
+
+    #[test]
+    fn test_generate_unique_skips_taken_numbers() {
+        let taken = AccountNumber::generate(AccountType::Premium).to_string();
+        let fresh = AccountNumber::generate_unique(AccountType::Premium, |candidate| candidate == taken).unwrap();
+        assert_ne!(fresh.as_str(), taken);
+
+        let exhausted = AccountNumber::generate_unique(AccountType::Premium, |_| true);
+        assert!(exhausted.is_err());
+    }
+
+    #[test]
+    fn test_wallet_rejects_duplicate_account_number() {
+        let mut wallet = MultiCurrencyWallet::new();
+        let account_number = wallet.issue_account_number(AccountType::Basic).unwrap();
+        wallet.add_account(Box::new(BasicAccount::with_account_number("USD", account_number.clone()).unwrap()))
+            .expect("Failed to add account");
+
+        let duplicate = BasicAccount::with_account_number("EUR", account_number).unwrap();
+        let result = wallet.add_account(Box::new(duplicate));
+        assert!(result.err().unwrap().contains("already exists"));
+    }
+
+    #[test]
+    fn test_get_account_by_number() {
+        let mut wallet = MultiCurrencyWallet::new();
+        let account = BasicAccount::new("USD");
+        let account_number = account.get_account_number().to_string();
+        wallet.add_account(Box::new(account)).expect("Failed to add account");
+
+        assert_eq!(wallet.get_account_by_number(&account_number).unwrap().get_currency(), "USD");
+
+        let unknown = AccountNumber::generate(AccountType::Basic).to_string();
+        assert!(wallet.get_account_by_number(&unknown).err().unwrap().starts_with("No account found"));
+        assert!(wallet.get_account_by_number("Basic-123").err().unwrap().starts_with("Malformed"));
+
+        let basic_wallet = BasicWallet::new(BasicAccount::new("USD"));
+        assert!(basic_wallet.get_account_by_number("not-an-account").is_err());
+    }
+
+    #[test]
+    fn test_pre_issued_number_must_match_account_type() {
+        let premium_number = AccountNumber::parse("Premium-8ZC4N2WQ7D-02").unwrap();
+        let basic_number = AccountNumber::parse("Basic-7KQ2M9X4TR-40").unwrap();
+
+        let result = BasicAccount::with_account_number("USD", premium_number.clone());
+        assert!(result.err().unwrap().contains("is not a Basic account number"));
+        let result = PremiumAccount::with_account_number("USD", 100.0, basic_number.clone());
+        assert!(result.err().unwrap().contains("is not a Premium account number"));
+
+        let basic = BasicAccount::with_account_number("USD", basic_number).unwrap();
+        assert_eq!(AccountNumber::parse(basic.get_account_number()).unwrap().issued_account_type(), basic.get_account_type());
+        let premium = PremiumAccount::with_account_number("USD", 100.0, premium_number).unwrap();
+        assert_eq!(AccountNumber::parse(premium.get_account_number()).unwrap().issued_account_type(), premium.get_account_type());
+    }
+}
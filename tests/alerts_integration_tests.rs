@@ -0,0 +1,164 @@
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::fs;
+    use std::rc::Rc;
+    use wallet_system::accounts::Account;
+    use wallet_system::alerts::{Alert, AlertEngine, AlertRule, Notifier, OutboxNotifier};
+    use wallet_system::basic_account::BasicAccount;
+    use wallet_system::events::{DeliveryMode, EventBus};
+    use wallet_system::premium_account::PremiumAccount;
+    use wallet_system::test_support::{AccountCall, Fault, FaultyAccount};
+
+    /// Notifier keeping the delivered alerts in memory
+    struct RecordingNotifier {
+        delivered: Rc<RefCell<Vec<Alert>>>,
+    }
+
+    impl Notifier for RecordingNotifier {
+        fn notify(&mut self, alert: &Alert) -> Result<(), String> {
+            self.delivered.borrow_mut().push(alert.clone());
+            Ok(())
+        }
+    }
+
+    type Delivered = Rc<RefCell<Vec<Alert>>>;
+
+    fn watched_premium_account(rules: Vec<AlertRule>) -> (PremiumAccount, Rc<RefCell<AlertEngine>>, Delivered) {
+        let delivered = Rc::new(RefCell::new(Vec::new()));
+        let engine = Rc::new(RefCell::new(AlertEngine::new(Box::new(RecordingNotifier { delivered: delivered.clone() }))));
+        let event_bus = EventBus::new(DeliveryMode::Synchronous);
+        AlertEngine::subscribe_to(&engine, &event_bus);
+
+        let mut account = PremiumAccount::new("USD", 200.0);
+        account.set_event_bus(Some(event_bus));
+        for rule in rules {
+            engine.borrow_mut().add_rule(account.get_account_number(), rule).unwrap();
+        }
+        (account, engine, delivered)
+    }
+
+    #[test]
+    fn test_overdraft_entered_and_usage() {
+        let (mut account, _, delivered) = watched_premium_account(vec![
+            AlertRule::OverdraftEntered,
+            AlertRule::OverdraftUsage { percent: 75.0 },
+        ]);
+        account.deposit(50.0);
+        account.withdraw(100.0);
+        assert_eq!(delivered.borrow().len(), 1);
+        assert_eq!(delivered.borrow()[0].rule, AlertRule::OverdraftEntered);
+
+        account.withdraw(110.0);
+        let delivered = delivered.borrow();
+        assert_eq!(delivered.len(), 2);
+        assert_eq!(delivered[1].rule, AlertRule::OverdraftUsage { percent: 75.0 });
+        assert_eq!(delivered[1].balance, -160.0);
+        assert!(delivered[1].message.contains("80%"));
+    }
+
+    #[test]
+    fn test_balance_below_is_deduplicated_within_cooldown() {
+        let (mut account, engine, delivered) = watched_premium_account(vec![AlertRule::BalanceBelow { threshold: 20.0 }]);
+        account.deposit(100.0);
+        account.withdraw(90.0);
+        account.withdraw(5.0);
+        assert_eq!(delivered.borrow().len(), 1);
+
+        engine.borrow_mut().set_cooldown_seconds(0);
+        account.withdraw(1.0);
+        assert_eq!(delivered.borrow().len(), 2);
+        assert_eq!(engine.borrow().get_alerts().len(), 2);
+    }
+
+    #[test]
+    fn test_large_deposit_includes_incoming_transfers() {
+        let (mut account, _, delivered) = watched_premium_account(vec![AlertRule::LargeDeposit { threshold: 1_000.0 }]);
+        account.deposit(999.0);
+        assert!(delivered.borrow().is_empty());
+
+        let mut other = BasicAccount::new("USD");
+        other.deposit(5_000.0);
+        other.transfer(&mut account, 2_000.0);
+        assert_eq!(delivered.borrow().len(), 1);
+        assert!(delivered.borrow()[0].message.contains("2000 USD"));
+    }
+
+    #[test]
+    fn test_rolled_back_transfer_is_not_a_deposit() {
+        let (mut account, engine, delivered) = watched_premium_account(vec![
+            AlertRule::LargeDeposit { threshold: 500.0 },
+            AlertRule::BalanceBelow { threshold: 100.0 },
+        ]);
+        account.deposit(400.0);
+        let mut refusing = FaultyAccount::new(Box::new(BasicAccount::new("USD")));
+        refusing.get_faults().inject(AccountCall::DepositFrom, Fault::Fail("Account frozen".to_string()));
+
+        let response = account.transfer(&mut refusing, 550.0);
+        assert!(!response.is_successful);
+        assert_eq!(account.get_balance(), 400.0);
+        assert!(delivered.borrow().is_empty());
+        assert!(engine.borrow().get_pending_alerts().is_empty());
+
+        // the reversed alert does not hold the rule in its cool-down
+        account.withdraw(350.0);
+        assert_eq!(delivered.borrow().len(), 1);
+        assert_eq!(delivered.borrow()[0].rule, AlertRule::BalanceBelow { threshold: 100.0 });
+    }
+
+    #[test]
+    fn test_transfer_alerts_are_delivered_once_the_transfer_settles() {
+        let (mut account, engine, delivered) = watched_premium_account(vec![
+            AlertRule::BalanceBelow { threshold: 100.0 },
+            AlertRule::OverdraftEntered,
+        ]);
+        account.deposit(50.0);
+        let mut recipient = BasicAccount::new("USD");
+        let recipient_bus = EventBus::new(DeliveryMode::Synchronous);
+        AlertEngine::subscribe_to(&engine, &recipient_bus);
+        recipient.set_event_bus(Some(recipient_bus));
+
+        let response = account.transfer(&mut recipient, 120.0);
+        assert!(response.is_successful);
+        let delivered = delivered.borrow();
+        assert_eq!(delivered.len(), 2);
+        assert_eq!(delivered[0].rule, AlertRule::BalanceBelow { threshold: 100.0 });
+        assert_eq!(delivered[1].rule, AlertRule::OverdraftEntered);
+        assert!(engine.borrow().get_pending_alerts().is_empty());
+    }
+
+    #[test]
+    fn test_rules_are_per_account() {
+        let (_, engine, delivered) = watched_premium_account(vec![AlertRule::LargeDeposit { threshold: 10.0 }]);
+        let event_bus = EventBus::new(DeliveryMode::Synchronous);
+        AlertEngine::subscribe_to(&engine, &event_bus);
+        let mut unwatched = BasicAccount::new("USD");
+        unwatched.set_event_bus(Some(event_bus));
+        unwatched.deposit(500.0);
+        assert!(delivered.borrow().is_empty());
+
+        assert!(engine.borrow_mut().add_rule("Basic-7KQ2M9X4TR-40", AlertRule::OverdraftUsage { percent: 150.0 }).is_err());
+    }
+
+    #[test]
+    fn test_outbox_notifier_appends_lines() {
+        let path = std::env::temp_dir().join(format!("wallet_alerts_{}.outbox", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let engine = Rc::new(RefCell::new(AlertEngine::new(Box::new(OutboxNotifier::new(&path)))));
+        let event_bus = EventBus::new(DeliveryMode::Synchronous);
+        AlertEngine::subscribe_to(&engine, &event_bus);
+
+        let mut account = PremiumAccount::new("EUR", 100.0);
+        account.set_event_bus(Some(event_bus));
+        engine.borrow_mut().add_rule(account.get_account_number(), AlertRule::OverdraftEntered).unwrap();
+        account.withdraw(10.0);
+
+        let outbox = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = outbox.lines().collect();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains(account.get_account_number()));
+        assert!(lines[0].contains("\tOverdraftEntered\t"));
+        assert!(engine.borrow().get_delivery_errors().is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+}
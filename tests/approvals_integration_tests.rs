@@ -0,0 +1,139 @@
+#[cfg(test)]
+mod tests {
+    use wallet_system::approvals::{ApprovalPolicy, ApprovalWallet, PendingTransferStatus};
+    use wallet_system::auth::{AuthorizedWallet, Role};
+    use wallet_system::basic_account::BasicAccount;
+    use wallet_system::basic_wallet::BasicWallet;
+    use wallet_system::wallets::Wallet;
+
+    fn users() -> AuthorizedWallet {
+        let mut wallet = BasicWallet::new(BasicAccount::new("USD"));
+        wallet.deposit("USD", 10_000.0);
+        let mut wallet = AuthorizedWallet::new(Box::new(wallet), "alice", "alice-pin").unwrap();
+        wallet.add_user("alice", "alice-pin", "bob", "bob-pin", Role::Spender { limit: 500.0 }).unwrap();
+        wallet.add_user("alice", "alice-pin", "carol", "carol-pin", Role::Viewer).unwrap();
+        wallet.add_user("alice", "alice-pin", "dave", "dave-pin", Role::Spender { limit: 500.0 }).unwrap();
+        wallet
+    }
+
+    fn treasury() -> ApprovalWallet {
+        let mut treasury = ApprovalWallet::new(users());
+        treasury.add_policy(ApprovalPolicy::new("USD", 1_000.0, 2, &["alice", "bob", "carol"]).unwrap());
+        treasury
+    }
+
+    #[test]
+    fn test_policy_validation() {
+        assert!(ApprovalPolicy::new("USD", 1_000.0, 0, &["alice"]).is_err());
+        assert!(ApprovalPolicy::new("USD", 1_000.0, 2, &["alice", "alice"]).is_err());
+        assert!(ApprovalPolicy::new("USD", -1.0, 1, &["alice"]).is_err());
+    }
+
+    #[test]
+    fn test_small_transfer_goes_straight_through() {
+        let mut treasury = treasury();
+        let mut supplier = BasicWallet::new(BasicAccount::new("USD"));
+        assert!(treasury.transfer("alice", "alice-pin", &mut supplier, "USD", 500.0).unwrap().is_successful);
+        assert_eq!(supplier.balance("USD").unwrap(), 500.0);
+        assert!(treasury.transfer("carol", "carol-pin", &mut supplier, "USD", 100.0).is_err());
+        assert!(treasury.submit_transfer("alice", "alice-pin", supplier.get_wallet_id(), "USD", 500.0).is_err());
+    }
+
+    #[test]
+    fn test_large_transfer_needs_quorum() {
+        let mut treasury = treasury();
+        let mut supplier = BasicWallet::new(BasicAccount::new("USD"));
+        assert!(treasury.transfer("alice", "alice-pin", &mut supplier, "USD", 5_000.0).is_err());
+        assert!(treasury.withdraw("alice", "alice-pin", "USD", 5_000.0).is_err());
+
+        let pending = treasury.submit_transfer("alice", "alice-pin", supplier.get_wallet_id(), "USD", 5_000.0).unwrap();
+        assert!(treasury.approve(&pending.transfer_id, "alice", "alice-pin").is_err());
+        assert!(treasury.approve(&pending.transfer_id, "dave", "dave-pin").is_err());
+        assert!(treasury.approve(&pending.transfer_id, "bob", "bob-pin").is_ok());
+        assert!(treasury.approve(&pending.transfer_id, "bob", "bob-pin").is_err());
+        assert!(treasury.execute(&pending.transfer_id, "alice", "alice-pin", &mut supplier).is_err());
+        assert_eq!(supplier.balance("USD").unwrap(), 0.0);
+
+        assert!(treasury.approve(&pending.transfer_id, "carol", "carol-pin").unwrap().has_quorum());
+        let mut other = BasicWallet::new(BasicAccount::new("USD"));
+        assert!(treasury.execute(&pending.transfer_id, "alice", "alice-pin", &mut other).is_err());
+        assert!(treasury.execute(&pending.transfer_id, "carol", "carol-pin", &mut supplier).is_err());
+
+        // The quorum replaces bob's spending limit
+        let response = treasury.execute(&pending.transfer_id, "bob", "bob-pin", &mut supplier).unwrap();
+        assert!(response.is_successful);
+        assert_eq!(supplier.balance("USD").unwrap(), 5_000.0);
+        assert_eq!(treasury.balance("carol", "carol-pin", "USD").unwrap(), 5_000.0);
+        assert_eq!(treasury.get_pending_transfer(&pending.transfer_id).unwrap().status, PendingTransferStatus::Executed);
+        assert!(treasury.execute(&pending.transfer_id, "alice", "alice-pin", &mut supplier).is_err());
+    }
+
+    #[test]
+    fn test_approvers_must_authenticate() {
+        let mut treasury = treasury();
+        let pending = treasury.submit_transfer("alice", "alice-pin", "Basic-anything", "USD", 5_000.0).unwrap();
+        assert!(treasury.submit_transfer("alice", "wrong-pin", "Basic-anything", "USD", 5_000.0).is_err());
+        assert!(treasury.submit_transfer("carol", "carol-pin", "Basic-anything", "USD", 5_000.0).is_err());
+
+        // alice cannot approve her own transfer by giving another approver's name
+        let error = treasury.approve(&pending.transfer_id, "bob", "alice-pin").unwrap_err();
+        assert_eq!(error, "Invalid credentials for user bob");
+        assert!(treasury.approve(&pending.transfer_id, "mallory", "alice-pin").is_err());
+        assert!(treasury.reject(&pending.transfer_id, "carol", "alice-pin").is_err());
+        let pending = treasury.get_pending_transfer(&pending.transfer_id).unwrap();
+        assert!(pending.approvals.is_empty());
+        assert_eq!(pending.status, PendingTransferStatus::Pending);
+    }
+
+    #[test]
+    fn test_unreachable_quorum_is_refused() {
+        let mut treasury = ApprovalWallet::new(users());
+        treasury.add_policy(ApprovalPolicy::new("USD", 1_000.0, 2, &["alice", "bob"]).unwrap());
+
+        // alice cannot approve her own transfer, which leaves only bob
+        let error = treasury.submit_transfer("alice", "alice-pin", "Basic-anything", "USD", 5_000.0).unwrap_err();
+        assert_eq!(error, "Transfer of 5000 USD needs 2 approvals but only 1 approvers other than alice can give them");
+        assert!(treasury.get_pending_transfers().is_empty());
+        assert!(treasury.submit_transfer("dave", "dave-pin", "Basic-anything", "USD", 5_000.0).is_ok());
+    }
+
+    #[test]
+    fn test_rejected_transfer_cannot_execute() {
+        let mut treasury = treasury();
+        let mut supplier = BasicWallet::new(BasicAccount::new("USD"));
+        let pending = treasury.submit_transfer("alice", "alice-pin", supplier.get_wallet_id(), "USD", 5_000.0).unwrap();
+        treasury.approve(&pending.transfer_id, "bob", "bob-pin").unwrap();
+        let rejected = treasury.reject(&pending.transfer_id, "carol", "carol-pin").unwrap();
+        assert_eq!(rejected.status, PendingTransferStatus::Rejected);
+        assert_eq!(rejected.rejected_by.as_deref(), Some("carol"));
+        assert!(treasury.approve(&pending.transfer_id, "carol", "carol-pin").is_err());
+        assert!(treasury.execute(&pending.transfer_id, "alice", "alice-pin", &mut supplier).is_err());
+        assert_eq!(treasury.balance("alice", "alice-pin", "USD").unwrap(), 10_000.0);
+    }
+
+    #[test]
+    fn test_pending_transfer_expires() {
+        let mut treasury = treasury();
+        treasury.set_approval_validity_seconds(0);
+        let pending = treasury.submit_transfer("alice", "alice-pin", "Basic-anything", "USD", 5_000.0).unwrap();
+        let error = treasury.approve(&pending.transfer_id, "bob", "bob-pin").err().unwrap();
+        assert!(error.contains("Expired"));
+
+        treasury.submit_transfer("alice", "alice-pin", "Basic-anything", "USD", 2_000.0).unwrap();
+        assert_eq!(treasury.expire_stale_transfers(), 1);
+    }
+
+    #[test]
+    fn test_failed_execution_is_recorded() {
+        let mut treasury = treasury();
+        let mut supplier = BasicWallet::new(BasicAccount::new("USD"));
+        let pending = treasury.submit_transfer("alice", "alice-pin", supplier.get_wallet_id(), "USD", 50_000.0).unwrap();
+        treasury.approve(&pending.transfer_id, "bob", "bob-pin").unwrap();
+        treasury.approve(&pending.transfer_id, "carol", "carol-pin").unwrap();
+        let response = treasury.execute(&pending.transfer_id, "alice", "alice-pin", &mut supplier).unwrap();
+        assert!(!response.is_successful);
+        let pending = treasury.get_pending_transfer(&pending.transfer_id).unwrap();
+        assert_eq!(pending.status, PendingTransferStatus::Failed);
+        assert_eq!(pending.error_message.as_deref(), Some("Insufficient funds"));
+    }
+}
@@ -0,0 +1,163 @@
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process::Command;
+    use std::rc::Rc;
+    use wallet_system::audit::{self, AuditLog, AuditRecord, GENESIS_HASH};
+    use wallet_system::basic_account::BasicAccount;
+    use wallet_system::basic_wallet::BasicWallet;
+    use wallet_system::events::{DeliveryMode, EventBus};
+    use wallet_system::wallets::Wallet;
+
+    fn temp_log_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("wallet_audit_{}_{}.jsonl", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    fn sample_records() -> Vec<AuditRecord> {
+        let mut log = AuditLog::in_memory();
+        log.append("AccountCredited", None, Some("Basic-7KQ2M9X4TR-40"), "USD", 100.0, "Deposit").unwrap();
+        log.append("AccountDebited", None, Some("Basic-7KQ2M9X4TR-40"), "USD", 30.0, "Withdrawal").unwrap();
+        log.append("AccountDebited", None, Some("Basic-7KQ2M9X4TR-40"), "USD", 20.0, "Withdrawal").unwrap();
+        log.get_records().to_vec()
+    }
+
+    #[test]
+    fn test_records_are_chained() {
+        let records = sample_records();
+        assert_eq!(records[0].previous_hash, GENESIS_HASH);
+        assert_eq!(records[1].previous_hash, records[0].hash);
+        let verification = audit::verify_records(&records).unwrap();
+        assert_eq!(verification.records, 3);
+        assert_eq!(verification.head_hash, records[2].hash);
+    }
+
+    #[test]
+    fn test_modification_is_detected() {
+        let mut records = sample_records();
+        records[1].amount = 3.0;
+        let error = audit::verify_records(&records).unwrap_err();
+        assert!(error.contains("Record 2 was modified"));
+
+        // recomputing the hash of the edited record breaks the link to the next one
+        records[1].hash = records[1].compute_hash();
+        let error = audit::verify_records(&records).unwrap_err();
+        assert!(error.contains("Record 3 does not chain"));
+    }
+
+    #[test]
+    fn test_hash_keeps_fields_apart() {
+        let record = sample_records().remove(0);
+
+        // "Deposit" + "Wallet|X" and "Deposit|Wallet" + "X" join to the same text
+        let mut original = record.clone();
+        original.operation = "Deposit".to_string();
+        original.wallet_id = Some("Wallet|X".to_string());
+        let mut shifted = record.clone();
+        shifted.operation = "Deposit|Wallet".to_string();
+        shifted.wallet_id = Some("X".to_string());
+        assert_ne!(shifted.compute_hash(), original.compute_hash());
+
+        let mut missing = record.clone();
+        missing.wallet_id = None;
+        let mut empty = record.clone();
+        empty.wallet_id = Some("".to_string());
+        assert_ne!(missing.compute_hash(), empty.compute_hash());
+
+        let mut missing = record.clone();
+        missing.account_number = None;
+        let mut empty = record.clone();
+        empty.account_number = Some("".to_string());
+        assert_ne!(missing.compute_hash(), empty.compute_hash());
+    }
+
+    #[test]
+    fn test_insertion_and_deletion_are_detected() {
+        let records = sample_records();
+
+        let mut deleted = records.clone();
+        deleted.remove(1);
+        assert!(audit::verify_records(&deleted).is_err());
+
+        let mut inserted = records.clone();
+        inserted.insert(1, records[0].clone());
+        assert!(audit::verify_records(&inserted).is_err());
+
+        let truncated = &records[..2];
+        assert!(audit::verify_records(truncated).is_ok());
+        assert!(audit::verify_records_against(truncated, &records[2].hash).is_err());
+        assert!(audit::verify_records_against(&records, &records[2].hash).is_ok());
+    }
+
+    #[test]
+    fn test_event_bus_operations_are_logged_to_file() {
+        let path = temp_log_path("events");
+        let log = Rc::new(RefCell::new(AuditLog::open(&path).unwrap()));
+        let event_bus = EventBus::new(DeliveryMode::Synchronous);
+        AuditLog::subscribe_to(&log, &event_bus);
+
+        let mut sender = BasicWallet::new(BasicAccount::new("USD"));
+        let mut recipient = BasicWallet::new(BasicAccount::new("USD"));
+        sender.set_event_bus(Some(event_bus));
+        sender.deposit("USD", 100.0);
+        sender.transfer(&mut recipient, "USD", 40.0);
+        sender.withdraw("USD", 500.0);
+
+        let operations: Vec<String> = log.borrow().get_records().iter().map(|r| r.operation.clone()).collect();
+        assert_eq!(operations, vec!["AccountCredited", "AccountDebited", "TransferCompleted", "OperationRejected"]);
+        assert!(log.borrow().get_write_errors().is_empty());
+
+        let from_file = audit::read_log_file(&path).unwrap();
+        assert_eq!(from_file, log.borrow().get_records());
+
+        // reopening continues the chain
+        let mut reopened = AuditLog::open(&path).unwrap();
+        reopened.append("Note", None, None, "", 0.0, "reopened").unwrap();
+        assert_eq!(audit::verify_log_file(&path).unwrap().records, 5);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_non_finite_amounts_are_refused() {
+        let path = temp_log_path("non_finite");
+        let mut log = AuditLog::open(&path).unwrap();
+        log.append("AccountCredited", None, Some("Basic-7KQ2M9X4TR-40"), "USD", 100.0, "Deposit").unwrap();
+        for amount in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(log.append("AccountCredited", None, Some("Basic-7KQ2M9X4TR-40"), "USD", amount, "Deposit").is_err());
+        }
+        log.append("AccountDebited", None, Some("Basic-7KQ2M9X4TR-40"), "USD", 30.0, "Withdrawal").unwrap();
+
+        assert_eq!(log.get_records().len(), 2);
+        assert_eq!(audit::read_log_file(&path).unwrap(), log.get_records());
+        assert_eq!(audit::verify_log_file(&path).unwrap().records, 2);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_cli_verifies_log_file() {
+        let path = temp_log_path("cli");
+        let mut log = AuditLog::open(&path).unwrap();
+        log.append("AccountCredited", None, Some("Basic-7KQ2M9X4TR-40"), "USD", 100.0, "Deposit").unwrap();
+        log.append("AccountDebited", None, Some("Basic-7KQ2M9X4TR-40"), "USD", 30.0, "Withdrawal").unwrap();
+
+        let status = Command::new(env!("CARGO_BIN_EXE_wallet_cli"))
+            .args(["verify_audit_log", path.to_str().unwrap()])
+            .output()
+            .unwrap();
+        assert!(status.status.success());
+        assert!(String::from_utf8_lossy(&status.stdout).contains("2 records"));
+
+        let tampered = fs::read_to_string(&path).unwrap().replace("\"amount\":30.0", "\"amount\":3.0");
+        fs::write(&path, tampered).unwrap();
+        let status = Command::new(env!("CARGO_BIN_EXE_wallet_cli"))
+            .args(["verify_audit_log", path.to_str().unwrap()])
+            .output()
+            .unwrap();
+        assert!(!status.status.success());
+        assert!(String::from_utf8_lossy(&status.stderr).contains("Record 2 was modified"));
+        fs::remove_file(&path).unwrap();
+    }
+}
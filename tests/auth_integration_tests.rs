@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+    use wallet_system::auth::{AuthorizedWallet, Credential, Role};
+    use wallet_system::basic_account::BasicAccount;
+    use wallet_system::basic_wallet::BasicWallet;
+    use wallet_system::wallets::Wallet;
+
+    fn shared_wallet() -> AuthorizedWallet {
+        let mut wallet = BasicWallet::new(BasicAccount::new("USD"));
+        wallet.deposit("USD", 500.0);
+        let mut shared = AuthorizedWallet::new(Box::new(wallet), "alice", "1234").expect("Failed to create wallet");
+        shared.add_user("alice", "1234", "bob", "correct horse", Role::Spender { limit: 100.0 }).unwrap();
+        shared.add_user("alice", "1234", "carol", "5678", Role::Viewer).unwrap();
+        shared
+    }
+
+    #[test]
+    fn test_credential_hashes_secret() {
+        let credential = Credential::new("1234").unwrap();
+        assert!(credential.verify("1234"));
+        assert!(!credential.verify("4321"));
+        assert!(!format!("{:?}", credential).contains("1234"));
+        assert!(Credential::new("12").is_err());
+    }
+
+    #[test]
+    fn test_owner_has_full_access() {
+        let mut shared = shared_wallet();
+        assert!(shared.withdraw("alice", "1234", "USD", 300.0).unwrap().is_successful);
+        assert!(shared.deposit("alice", "1234", "USD", 50.0).unwrap().is_successful);
+        assert_eq!(shared.balance("alice", "1234", "USD").unwrap(), 250.0);
+    }
+
+    #[test]
+    fn test_spender_limit() {
+        let mut shared = shared_wallet();
+        let mut other = BasicWallet::new(BasicAccount::new("USD"));
+
+        assert!(shared.withdraw("bob", "correct horse", "USD", 100.0).unwrap().is_successful);
+        let error = shared.withdraw("bob", "correct horse", "USD", 150.0).err().unwrap();
+        assert!(error.contains("spending limit"));
+        assert!(shared.transfer("bob", "correct horse", &mut other, "USD", 150.0).is_err());
+        assert!(shared.transfer("bob", "correct horse", &mut other, "USD", 80.0).unwrap().is_successful);
+        assert_eq!(other.balance("USD").unwrap(), 80.0);
+        assert!(shared.add_user("bob", "correct horse", "dave", "0000", Role::Owner).is_err());
+    }
+
+    #[test]
+    fn test_viewer_is_read_only() {
+        let mut shared = shared_wallet();
+        assert_eq!(shared.balance("carol", "5678", "USD").unwrap(), 500.0);
+        assert_eq!(shared.view("carol", "5678").unwrap().get_accounts().len(), 1);
+        assert!(shared.deposit("carol", "5678", "USD", 10.0).is_err());
+        assert!(shared.withdraw("carol", "5678", "USD", 10.0).is_err());
+        assert_eq!(shared.balance("alice", "1234", "USD").unwrap(), 500.0);
+    }
+
+    #[test]
+    fn test_lockout_after_failed_attempts() {
+        let mut shared = shared_wallet();
+        assert!(shared.balance("bob", "wrong", "USD").is_err());
+        assert!(shared.balance("bob", "wrong", "USD").is_err());
+        assert!(!shared.is_locked("bob"));
+        let error = shared.balance("bob", "wrong", "USD").err().unwrap();
+        assert!(error.contains("locked"));
+        assert!(shared.is_locked("bob"));
+
+        // the correct secret is refused while locked
+        assert!(shared.withdraw("bob", "correct horse", "USD", 10.0).is_err());
+        shared.unlock_user("alice", "1234", "bob").unwrap();
+        assert!(shared.withdraw("bob", "correct horse", "USD", 10.0).unwrap().is_successful);
+    }
+
+    #[test]
+    fn test_lockout_expires_for_a_single_owner() {
+        let mut wallet = BasicWallet::new(BasicAccount::new("USD"));
+        wallet.deposit("USD", 50.0);
+        let mut shared = AuthorizedWallet::new(Box::new(wallet), "alice", "1234").unwrap();
+        for _ in 0..3 {
+            assert!(shared.balance("alice", "0000", "USD").is_err());
+        }
+        assert!(shared.is_locked("alice"));
+        assert!(shared.balance("alice", "1234", "USD").err().unwrap().contains("locked"));
+
+        // nobody else can unlock the only owner, so the lockout runs out instead
+        shared.set_lockout_seconds(0);
+        assert!(!shared.is_locked("alice"));
+        assert_eq!(shared.balance("alice", "1234", "USD").unwrap(), 50.0);
+
+        // the expired lockout does not count towards the next one
+        shared.set_lockout_seconds(3600);
+        assert!(shared.balance("alice", "0000", "USD").is_err());
+        assert!(!shared.is_locked("alice"));
+    }
+
+    #[test]
+    fn test_change_secret_and_remove_user() {
+        let mut shared = shared_wallet();
+        shared.change_secret("carol", "5678", "8765").unwrap();
+        assert!(shared.authenticate("carol", "5678").is_err());
+        assert_eq!(shared.authenticate("carol", "8765").unwrap(), Role::Viewer);
+
+        shared.remove_user("alice", "1234", "carol").unwrap();
+        assert!(shared.authenticate("carol", "8765").is_err());
+        assert!(shared.remove_user("alice", "1234", "alice").is_err());
+    }
+
+    #[test]
+    fn test_wallet_keeps_an_owner() {
+        let mut shared = shared_wallet();
+        let error = shared.add_user("alice", "1234", "alice", "1234", Role::Viewer).unwrap_err();
+        assert_eq!(error, "User alice is the last owner of the wallet");
+        assert_eq!(shared.authenticate("alice", "1234").unwrap(), Role::Owner);
+        // replacing the last owner's secret keeps the role
+        shared.add_user("alice", "1234", "alice", "4321", Role::Owner).unwrap();
+
+        // with a second owner, either may step down or be removed
+        shared.add_user("alice", "4321", "dave", "0000", Role::Owner).unwrap();
+        shared.add_user("alice", "4321", "alice", "4321", Role::Spender { limit: 10.0 }).unwrap();
+        assert!(shared.add_user("dave", "0000", "dave", "0000", Role::Viewer).is_err());
+        shared.add_user("dave", "0000", "alice", "4321", Role::Owner).unwrap();
+        shared.remove_user("alice", "4321", "dave").unwrap();
+        assert!(shared.authenticate("dave", "0000").is_err());
+    }
+}
@@ -1,11 +1,8 @@
-use wallet_system::wallets::{Wallet, WalletType, TransferResponse};
 use wallet_system::basic_account::BasicAccount;
 use wallet_system::accounts::Account;
 
 #[cfg(test)]
 mod tests {
-    use rand::distributions::Alphanumeric;
-    use rand::Rng;
     use wallet_system::accounts::AccountType;
     use super::*;
 
@@ -20,7 +17,7 @@ mod tests {
 
     #[test]
     fn test_override_default_impl() {
-        let mut account = BasicAccount::new("USD");
+        let account = BasicAccount::new("USD");
         account.default_impl();
     }
 
@@ -1,9 +1,8 @@
 #[cfg(test)]
 mod tests {
-    use std::io;
-    use super::*;
-    use wallet_system::accounts::AccountType;
-    use wallet_system::wallets::{Wallet, WalletType, TransferResponse, DisplayWallet};
+    use wallet_system::accounts::Account;
+    use wallet_system::transactions::TransactionKind;
+    use wallet_system::wallets::{Wallet, WalletType};
     use wallet_system::basic_account::BasicAccount;
     use wallet_system::basic_wallet::BasicWallet;
 
@@ -89,4 +88,27 @@ mod tests {
         assert_eq!(response.error_message.unwrap(), "Source account currency: USD, Receiver account currency: EUR, mismatch");
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_transfer_from_account_records_counterparties() {
+        let account1 = BasicAccount::new("USD");
+        let sender_number = account1.get_account_number().to_string();
+        let mut wallet1: BasicWallet<BasicAccount> = BasicWallet::new(account1);
+        let account2 = BasicAccount::new("USD");
+        let recipient_number = account2.get_account_number().to_string();
+        let mut wallet2: BasicWallet<BasicAccount> = BasicWallet::new(account2);
+        wallet1.deposit_to_account(&sender_number, 100.0);
+
+        let response = wallet1.transfer_from_account(&sender_number, &mut wallet2, &recipient_number, 40.0);
+        assert!(response.is_successful);
+        assert_eq!(wallet1.balance("USD").unwrap(), 60.0);
+        assert_eq!(wallet2.balance("USD").unwrap(), 40.0);
+
+        let sent = wallet1.get_account_by_number(&sender_number).unwrap().get_history().last().unwrap().clone();
+        assert_eq!(sent.kind, TransactionKind::TransferOut);
+        assert_eq!(sent.counterparty_account_number, Some(recipient_number.clone()));
+        let received = wallet2.get_account_by_number(&recipient_number).unwrap().get_history().last().unwrap().clone();
+        assert_eq!(received.kind, TransactionKind::TransferIn);
+        assert_eq!(received.counterparty_account_number, Some(sender_number));
+    }
+
+}
@@ -0,0 +1,158 @@
+#[cfg(test)]
+mod tests {
+    use wallet_system::basic_account::BasicAccount;
+    use wallet_system::basic_wallet::BasicWallet;
+    use wallet_system::customer::{ContactDetails, Customer, KycTier};
+    use wallet_system::multi_currency_wallet::MultiCurrencyWallet;
+    use wallet_system::premium_account::PremiumAccount;
+    use wallet_system::wallets::Wallet;
+
+    fn contact() -> ContactDetails {
+        ContactDetails {
+            email: "jane@example.com".to_string(),
+            phone: Some("+44 20 7946 0000".to_string()),
+            address: None,
+        }
+    }
+
+    fn customer_with_two_wallets(kyc_tier: KycTier) -> (Customer, String, String) {
+        let mut customer = Customer::new("Jane Doe", contact(), kyc_tier);
+        let basic_wallet = BasicWallet::new(BasicAccount::new("USD"));
+        let basic_wallet_id = basic_wallet.get_wallet_id().to_string();
+        let mut multi_wallet = MultiCurrencyWallet::new();
+        multi_wallet.add_account(Box::new(PremiumAccount::new("USD", 100.0))).expect("Failed to add account");
+        multi_wallet.add_account(Box::new(BasicAccount::new("EUR"))).expect("Failed to add account");
+        let multi_wallet_id = multi_wallet.get_wallet_id().to_string();
+        customer.add_wallet(Box::new(basic_wallet)).expect("Failed to add wallet");
+        customer.add_wallet(Box::new(multi_wallet)).expect("Failed to add wallet");
+        (customer, basic_wallet_id, multi_wallet_id)
+    }
+
+    #[test]
+    fn test_new_customer() {
+        let customer = Customer::new("Jane Doe", contact(), KycTier::Verified);
+        assert!(customer.get_customer_id().starts_with("Customer-"));
+        assert_eq!(customer.get_name(), "Jane Doe");
+        assert_eq!(customer.get_contact().email, "jane@example.com");
+        assert_eq!(customer.get_kyc_tier(), KycTier::Verified);
+        assert!(customer.get_created_at() > 0);
+        assert!(customer.get_wallets().is_empty());
+    }
+
+    #[test]
+    fn test_list_wallets_and_aggregate_balances() {
+        let (mut customer, basic_wallet_id, multi_wallet_id) = customer_with_two_wallets(KycTier::Verified);
+        assert_eq!(customer.get_wallets().len(), 2);
+        assert!(customer.get_wallet(&multi_wallet_id).is_some());
+
+        assert!(customer.deposit(&basic_wallet_id, "USD", 100.0).is_successful);
+        assert!(customer.deposit(&multi_wallet_id, "USD", 50.0).is_successful);
+        assert!(customer.deposit(&multi_wallet_id, "EUR", 20.0).is_successful);
+
+        assert_eq!(customer.total_balance("USD"), 150.0);
+        let balances = customer.balances();
+        assert_eq!(balances.get("USD"), Some(&150.0));
+        assert_eq!(balances.get("EUR"), Some(&20.0));
+
+        let response = customer.transfer_between_wallets(&basic_wallet_id, &multi_wallet_id, "USD", 40.0).unwrap();
+        assert!(response.is_successful);
+        assert_eq!(customer.get_wallet(&multi_wallet_id).unwrap().balance("USD").unwrap(), 90.0);
+        assert_eq!(customer.total_balance("USD"), 150.0);
+    }
+
+    #[test]
+    fn test_kyc_balance_cap() {
+        let (mut customer, basic_wallet_id, multi_wallet_id) = customer_with_two_wallets(KycTier::Unverified);
+        assert!(customer.deposit(&basic_wallet_id, "USD", 600.0).is_successful);
+
+        // the cap applies across all of the customer's wallets
+        let response = customer.deposit(&multi_wallet_id, "USD", 500.0);
+        assert!(!response.is_successful);
+        assert!(response.error_message.unwrap().contains("Unverified KYC tier limit"));
+        assert_eq!(customer.total_balance("USD"), 600.0);
+
+        customer.set_kyc_tier(KycTier::Verified).unwrap();
+        assert!(customer.deposit(&multi_wallet_id, "USD", 500.0).is_successful);
+
+        // the balance no longer fits the lower tier
+        let error = customer.set_kyc_tier(KycTier::Unverified).unwrap_err();
+        assert_eq!(error, "Balance of 1100 USD exceeds the Unverified KYC tier limit of 1000");
+        assert_eq!(customer.get_kyc_tier(), KycTier::Verified);
+        assert!(customer.withdraw(&multi_wallet_id, "USD", 200.0).is_successful);
+        customer.set_kyc_tier(KycTier::Unverified).unwrap();
+        assert_eq!(customer.get_kyc_tier(), KycTier::Unverified);
+    }
+
+    #[test]
+    fn test_kyc_balance_cap_applies_to_incoming_transfers() {
+        let mut sender = Customer::new("John Roe", contact(), KycTier::Verified);
+        let sender_wallet = BasicWallet::new(BasicAccount::new("USD"));
+        let sender_wallet_id = sender_wallet.get_wallet_id().to_string();
+        sender.add_wallet(Box::new(sender_wallet)).unwrap();
+        assert!(sender.deposit(&sender_wallet_id, "USD", 2_000.0).is_successful);
+        let (mut recipient, basic_wallet_id, multi_wallet_id) = customer_with_two_wallets(KycTier::Unverified);
+        assert!(recipient.deposit(&basic_wallet_id, "USD", 900.0).is_successful);
+
+        let response = sender.transfer_to_customer(&sender_wallet_id, &mut recipient, &multi_wallet_id, "USD", 200.0);
+        assert!(!response.is_successful);
+        assert!(response.error_message.unwrap().contains("Unverified KYC tier limit"));
+        assert_eq!(sender.total_balance("USD"), 2_000.0);
+        assert_eq!(recipient.total_balance("USD"), 900.0);
+
+        assert!(sender.transfer_to_customer(&sender_wallet_id, &mut recipient, &multi_wallet_id, "USD", 100.0).is_successful);
+        assert_eq!(recipient.total_balance("USD"), 1_000.0);
+        let response = sender.transfer_to_customer(&sender_wallet_id, &mut recipient, "Basic-unknown", "USD", 1.0);
+        assert_eq!(response.error_message.unwrap(), "No wallet found with id: Basic-unknown");
+    }
+
+    #[test]
+    fn test_moving_money_between_own_wallets_at_the_cap() {
+        let (mut customer, basic_wallet_id, multi_wallet_id) = customer_with_two_wallets(KycTier::Unverified);
+        assert!(customer.deposit(&basic_wallet_id, "USD", 800.0).is_successful);
+        assert!(customer.deposit(&multi_wallet_id, "USD", 200.0).is_successful);
+
+        // the total stays at the cap, so the move is allowed
+        let response = customer.transfer_between_wallets(&basic_wallet_id, &multi_wallet_id, "USD", 200.0).unwrap();
+        assert!(response.is_successful);
+        assert_eq!(customer.get_wallet(&multi_wallet_id).unwrap().balance("USD").unwrap(), 400.0);
+        assert!(!customer.deposit(&basic_wallet_id, "USD", 1.0).is_successful);
+        assert!(customer.transfer_between_wallets(&basic_wallet_id, &multi_wallet_id, "USD", 300.0).is_err());
+    }
+
+    #[test]
+    fn test_kyc_transfer_cap() {
+        let (mut customer, basic_wallet_id, _) = customer_with_two_wallets(KycTier::Unverified);
+        customer.deposit(&basic_wallet_id, "USD", 900.0);
+
+        let response = customer.withdraw(&basic_wallet_id, "USD", 300.0);
+        assert!(!response.is_successful);
+        assert!(response.error_message.unwrap().contains("transfer limit"));
+
+        let mut other = BasicWallet::new(BasicAccount::new("USD"));
+        let response = customer.transfer(&basic_wallet_id, &mut other, "USD", 300.0);
+        assert!(!response.is_successful);
+        assert_eq!(other.balance("USD").unwrap(), 0.0);
+
+        let response = customer.transfer(&basic_wallet_id, &mut other, "USD", 200.0);
+        assert!(response.is_successful);
+        assert_eq!(other.balance("USD").unwrap(), 200.0);
+    }
+
+    #[test]
+    fn test_add_wallet_over_balance_cap_fails() {
+        let mut customer = Customer::new("Jane Doe", contact(), KycTier::Unverified);
+        let mut wallet = BasicWallet::new(BasicAccount::new("USD"));
+        wallet.deposit("USD", 5_000.0);
+        let result = customer.add_wallet(Box::new(wallet));
+        assert!(result.is_err());
+        assert!(customer.get_wallets().is_empty());
+    }
+
+    #[test]
+    fn test_unknown_wallet() {
+        let mut customer = Customer::new("Jane Doe", contact(), KycTier::Verified);
+        let response = customer.deposit("Basic-unknown", "USD", 10.0);
+        assert!(!response.is_successful);
+        assert_eq!(response.error_message.unwrap(), "No wallet found with id: Basic-unknown");
+    }
+}
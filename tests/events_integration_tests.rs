@@ -0,0 +1,242 @@
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use wallet_system::accounts::{Account, AccountResponse, AccountTransferResponse, AccountType, DisplayAccount};
+    use wallet_system::basic_account::BasicAccount;
+    use wallet_system::basic_wallet::BasicWallet;
+    use wallet_system::events::{DeliveryMode, EventBus, WalletEvent};
+    use wallet_system::multi_currency_wallet::MultiCurrencyWallet;
+    use wallet_system::premium_account::PremiumAccount;
+    use wallet_system::rendering::AccountSummary;
+    use wallet_system::transactions::{Transaction, TransactionKind};
+    use wallet_system::wallets::Wallet;
+
+    /// Account that refuses every incoming transfer
+    struct RefusingAccount {
+        inner: BasicAccount,
+    }
+
+    impl DisplayAccount for RefusingAccount {
+        fn summary(&self) -> AccountSummary {
+            self.inner.summary()
+        }
+    }
+
+    impl Account for RefusingAccount {
+        fn get_balance(&self) -> f64 { self.inner.get_balance() }
+        fn deposit(&mut self, amount: f64) -> AccountResponse { self.inner.deposit(amount) }
+        fn withdraw(&mut self, amount: f64) -> AccountResponse { self.inner.withdraw(amount) }
+        fn deposit_from(&mut self, _amount: f64, _from_account_number: &str) -> AccountResponse {
+            AccountResponse {
+                account_number: self.inner.get_account_number().to_string(),
+                account_type: AccountType::Basic,
+                currency: self.inner.get_currency().to_string(),
+                balance: self.inner.get_balance(),
+                is_successful: false,
+                error_message: Some("Account frozen".to_string()),
+            }
+        }
+        fn withdraw_to(&mut self, amount: f64, to_account_number: &str) -> AccountResponse { self.inner.withdraw_to(amount, to_account_number) }
+        fn reverse_transfer_out(&mut self, amount: f64, to_account_number: &str) -> AccountResponse { self.inner.reverse_transfer_out(amount, to_account_number) }
+        fn get_currency(&self) -> &str { self.inner.get_currency() }
+        fn get_account_number(&self) -> &str { self.inner.get_account_number() }
+        fn get_account_type(&self) -> AccountType { self.inner.get_account_type() }
+        fn get_history(&self) -> &[Transaction] { self.inner.get_history() }
+        fn transfer(&mut self, to_account: &mut dyn Account, amount: f64) -> AccountTransferResponse { self.inner.transfer(to_account, amount) }
+        fn get_overdraft_limit(&self) -> f64 { 0.0 }
+        fn set_event_bus(&mut self, event_bus: Option<EventBus>) { self.inner.set_event_bus(event_bus) }
+    }
+
+    fn recording_bus(delivery_mode: DeliveryMode) -> (EventBus, Rc<RefCell<Vec<WalletEvent>>>) {
+        let event_bus = EventBus::new(delivery_mode);
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let recorded = events.clone();
+        event_bus.subscribe(move |event| recorded.borrow_mut().push(event.clone()));
+        (event_bus, events)
+    }
+
+    #[test]
+    fn test_account_events_are_delivered_synchronously() {
+        let (event_bus, events) = recording_bus(DeliveryMode::Synchronous);
+        let mut account = BasicAccount::new("USD");
+        account.set_event_bus(Some(event_bus));
+
+        account.deposit(100.0);
+        account.withdraw(30.0);
+        account.withdraw(500.0);
+
+        let events = events.borrow();
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[0], WalletEvent::AccountCredited { transaction, .. } if transaction.amount == 100.0));
+        assert!(matches!(&events[1], WalletEvent::AccountDebited { transaction, .. } if transaction.balance_after == 70.0));
+        match &events[2] {
+            WalletEvent::OperationRejected { operation, reason, account_number, .. } => {
+                assert_eq!(*operation, TransactionKind::Withdrawal);
+                assert_eq!(reason, "Insufficient funds");
+                assert_eq!(account_number.as_deref(), Some(account.get_account_number()));
+            }
+            event => panic!("Unexpected event: {:?}", event),
+        }
+    }
+
+    #[test]
+    fn test_overdraft_usage_events() {
+        let (event_bus, events) = recording_bus(DeliveryMode::Synchronous);
+        let mut account = PremiumAccount::new("USD", 100.0);
+        account.set_event_bus(Some(event_bus));
+
+        account.deposit(10.0);
+        account.withdraw(40.0);
+        account.withdraw(20.0);
+
+        let overdrafts: Vec<(f64, bool)> = events.borrow().iter()
+            .filter_map(|event| match event {
+                WalletEvent::OverdraftUsed { balance, entered, .. } => Some((*balance, *entered)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(overdrafts, vec![(-30.0, true), (-50.0, false)]);
+    }
+
+    #[test]
+    fn test_buffered_delivery() {
+        let (event_bus, events) = recording_bus(DeliveryMode::Buffered);
+        let mut wallet = MultiCurrencyWallet::new();
+        wallet.add_account(Box::new(BasicAccount::new("USD"))).unwrap();
+        wallet.set_event_bus(Some(event_bus.clone()));
+
+        wallet.deposit("USD", 10.0);
+        wallet.deposit("EUR", 10.0);
+        assert!(events.borrow().is_empty());
+        assert_eq!(event_bus.pending_events(), 2);
+
+        assert_eq!(event_bus.flush(), 2);
+        let events = events.borrow();
+        assert!(matches!(&events[0], WalletEvent::AccountCredited { .. }));
+        assert!(matches!(&events[1], WalletEvent::OperationRejected { wallet_id: Some(_), currency, .. } if currency == "EUR"));
+    }
+
+    #[test]
+    fn test_wallet_transfer_events() {
+        let (event_bus, events) = recording_bus(DeliveryMode::Synchronous);
+        let mut sender = BasicWallet::new(BasicAccount::new("USD"));
+        let mut recipient = MultiCurrencyWallet::new();
+        recipient.add_account(Box::new(BasicAccount::new("USD"))).unwrap();
+        sender.set_event_bus(Some(event_bus.clone()));
+        recipient.set_event_bus(Some(event_bus));
+
+        sender.deposit("USD", 50.0);
+        sender.transfer(&mut recipient, "USD", 20.0);
+        sender.transfer(&mut recipient, "USD", 80.0);
+
+        let events = events.borrow();
+        assert!(matches!(&events[1], WalletEvent::AccountDebited { transaction, .. } if transaction.kind == TransactionKind::TransferOut));
+        assert!(matches!(&events[2], WalletEvent::AccountCredited { transaction, .. } if transaction.kind == TransactionKind::TransferIn));
+        assert!(matches!(&events[3], WalletEvent::TransferCompleted { amount, .. } if *amount == 20.0));
+        assert!(matches!(events.last().unwrap(), WalletEvent::TransferFailed { reason, .. } if reason == "Insufficient funds"));
+    }
+
+    #[test]
+    fn test_account_lifecycle_events() {
+        let (event_bus, events) = recording_bus(DeliveryMode::Synchronous);
+        let mut wallet = MultiCurrencyWallet::new();
+        let savings = BasicAccount::new("USD");
+        let savings_number = savings.get_account_number().to_string();
+        let current = BasicAccount::new("USD");
+        let current_number = current.get_account_number().to_string();
+        wallet.add_account(Box::new(savings)).unwrap();
+        wallet.add_account(Box::new(current)).unwrap();
+        wallet.set_event_bus(Some(event_bus));
+        wallet.deposit("USD", 40.0);
+
+        wallet.upgrade_account(&current_number, 75.0).unwrap();
+        wallet.downgrade_account(&current_number).unwrap();
+        wallet.internal_transfer(&savings_number, &current_number, 15.0).unwrap();
+        wallet.close_account_sweeping_to(&savings_number, &current_number).unwrap();
+
+        let events = events.borrow();
+        let lifecycle: Vec<&WalletEvent> = events.iter()
+            .filter(|event| !matches!(event, WalletEvent::AccountCredited { .. } | WalletEvent::AccountDebited { .. }))
+            .collect();
+        assert_eq!(lifecycle.len(), 5);
+        assert!(matches!(lifecycle[0], WalletEvent::AccountTypeChanged { account_number, account_type: AccountType::Premium, overdraft_limit, .. }
+            if account_number == &current_number && *overdraft_limit == 75.0));
+        assert!(matches!(lifecycle[1], WalletEvent::AccountTypeChanged { account_type: AccountType::Basic, overdraft_limit, .. }
+            if *overdraft_limit == 0.0));
+        assert!(matches!(lifecycle[2], WalletEvent::InternalTransferCompleted { from_account_number, to_account_number, amount, .. }
+            if from_account_number == &savings_number && to_account_number == &current_number && *amount == 15.0));
+        // the sweep moves the remaining balance before closing the account
+        assert!(matches!(lifecycle[3], WalletEvent::InternalTransferCompleted { amount, .. } if *amount == 25.0));
+        assert!(matches!(lifecycle[4], WalletEvent::AccountClosed { account_number, currency, .. }
+            if account_number == &savings_number && currency == "USD"));
+    }
+
+    #[test]
+    fn test_rollback_is_published() {
+        let (event_bus, events) = recording_bus(DeliveryMode::Synchronous);
+        let mut sender = BasicWallet::new(BasicAccount::new("USD"));
+        let mut recipient = BasicWallet::new(RefusingAccount { inner: BasicAccount::new("USD") });
+        sender.set_event_bus(Some(event_bus));
+        sender.deposit("USD", 50.0);
+
+        let response = sender.transfer(&mut recipient, "USD", 20.0);
+        assert!(!response.is_successful);
+        assert_eq!(sender.balance("USD").unwrap(), 50.0);
+        let history = sender.get_account_by_currency("USD").unwrap().get_history();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].kind, TransactionKind::Deposit);
+
+        let events = events.borrow();
+        let kinds: Vec<&str> = events.iter()
+            .map(|event| match event {
+                WalletEvent::AccountCredited { .. } => "credited",
+                WalletEvent::AccountDebited { .. } => "debited",
+                WalletEvent::AccountDebitReversed { .. } => "reversed",
+                WalletEvent::TransferRolledBack { .. } => "rolled back",
+                WalletEvent::TransferFailed { .. } => "failed",
+                _ => "other",
+            })
+            .collect();
+        assert_eq!(kinds, vec!["credited", "debited", "reversed", "rolled back", "failed"]);
+        assert!(matches!(&events[3], WalletEvent::TransferRolledBack { reason, .. } if reason == "Account frozen"));
+    }
+
+    #[test]
+    fn test_unsubscribe_and_nested_publish() {
+        let event_bus = EventBus::new(DeliveryMode::Synchronous);
+        let count = Rc::new(RefCell::new(0));
+        let counter = count.clone();
+        let id = event_bus.subscribe(move |_| *counter.borrow_mut() += 1);
+
+        // a subscriber that publishes is delivered after the current event
+        let republisher = event_bus.clone();
+        event_bus.subscribe(move |event| {
+            if let WalletEvent::ExchangeFailed { quote_id, .. } = event {
+                if quote_id == "first" {
+                    republisher.publish(WalletEvent::ExchangeFailed {
+                        wallet_id: "".to_string(),
+                        quote_id: "second".to_string(),
+                        from_currency: "".to_string(),
+                        to_currency: "".to_string(),
+                        reason: "".to_string(),
+                    });
+                }
+            }
+        });
+        event_bus.publish(WalletEvent::ExchangeFailed {
+            wallet_id: "".to_string(),
+            quote_id: "first".to_string(),
+            from_currency: "".to_string(),
+            to_currency: "".to_string(),
+            reason: "".to_string(),
+        });
+        assert_eq!(*count.borrow(), 2);
+
+        event_bus.unsubscribe(id);
+        let mut account = BasicAccount::new("USD");
+        account.set_event_bus(Some(event_bus));
+        account.deposit(1.0);
+        assert_eq!(*count.borrow(), 2);
+    }
+}
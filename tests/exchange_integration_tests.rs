@@ -0,0 +1,142 @@
+#[cfg(test)]
+mod tests {
+    use wallet_system::basic_account::BasicAccount;
+    use wallet_system::exchange::StaticExchangeRates;
+    use wallet_system::multi_currency_wallet::MultiCurrencyWallet;
+    use wallet_system::premium_account::PremiumAccount;
+    use wallet_system::transactions::TransactionKind;
+    use wallet_system::wallets::Wallet;
+
+    fn rates() -> StaticExchangeRates {
+        let mut rates = StaticExchangeRates::new();
+        rates.set_rate("USD", "EUR", 0.8).unwrap();
+        rates
+    }
+
+    fn wallet_with_usd_and_eur() -> MultiCurrencyWallet {
+        let mut wallet = MultiCurrencyWallet::new();
+        wallet.add_account(Box::new(BasicAccount::new("USD"))).expect("Failed to add account");
+        wallet.add_account(Box::new(BasicAccount::new("EUR"))).expect("Failed to add account");
+        wallet.deposit("USD", 100.0);
+        wallet
+    }
+
+    #[test]
+    fn test_quote_then_execute_exchange() {
+        let mut wallet = wallet_with_usd_and_eur();
+        wallet.set_exchange_spread(0.25).unwrap();
+
+        let quote = wallet.quote_exchange("USD", "EUR", 50.0, &rates()).expect("Failed to quote");
+        assert_eq!(quote.market_rate, 0.8);
+        assert!((quote.effective_rate - 0.6).abs() < 1e-9);
+        assert!(quote.expires_at > quote.quoted_at);
+        // nothing moves until the quote is executed
+        assert_eq!(wallet.balance("USD").unwrap(), 100.0);
+
+        let response = wallet.execute_exchange(&quote.quote_id);
+        assert!(response.is_successful);
+        assert_eq!(response.debited_amount, 50.0);
+        assert_eq!(response.credited_amount, quote.converted_amount);
+        assert_eq!(wallet.balance("USD").unwrap(), 50.0);
+        assert_eq!(wallet.balance("EUR").unwrap(), quote.converted_amount);
+
+        let record = &wallet.get_exchange_history()[0];
+        assert_eq!(record.effective_rate, quote.effective_rate);
+        assert_eq!(record.market_rate, 0.8);
+
+        // a quote can only be executed once
+        let response = wallet.execute_exchange(&quote.quote_id);
+        assert!(!response.is_successful);
+    }
+
+    #[test]
+    fn test_exchange_legs_recorded_in_history() {
+        let mut wallet = wallet_with_usd_and_eur();
+        let response = wallet.exchange("USD", "EUR", 10.0, &rates());
+        assert!(response.is_successful);
+
+        let usd = wallet.get_account_by_currency("USD").unwrap();
+        let eur = wallet.get_account_by_currency("EUR").unwrap();
+        let usd_leg = usd.get_history().last().unwrap();
+        let eur_leg = eur.get_history().last().unwrap();
+        assert_eq!(usd_leg.kind, TransactionKind::TransferOut);
+        assert_eq!(usd_leg.counterparty_account_number.as_deref(), Some(eur.get_account_number()));
+        assert_eq!(eur_leg.kind, TransactionKind::TransferIn);
+        assert_eq!(eur_leg.amount, 8.0);
+    }
+
+    #[test]
+    fn test_expired_quote_is_rejected() {
+        let mut wallet = wallet_with_usd_and_eur();
+        wallet.set_quote_validity_seconds(0);
+        let quote = wallet.quote_exchange("USD", "EUR", 50.0, &rates()).expect("Failed to quote");
+
+        let response = wallet.execute_exchange(&quote.quote_id);
+        assert!(!response.is_successful);
+        assert!(response.error_message.unwrap().contains("expired"));
+        assert_eq!(wallet.balance("USD").unwrap(), 100.0);
+        assert_eq!(wallet.balance("EUR").unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_exchange_insufficient_funds_posts_nothing() {
+        let mut wallet = wallet_with_usd_and_eur();
+        let response = wallet.exchange("USD", "EUR", 500.0, &rates());
+        assert!(!response.is_successful);
+        assert_eq!(response.error_message.unwrap(), "Insufficient funds");
+        assert_eq!(wallet.balance("USD").unwrap(), 100.0);
+        assert_eq!(wallet.balance("EUR").unwrap(), 0.0);
+        assert!(wallet.get_exchange_history().is_empty());
+    }
+
+    #[test]
+    fn test_failed_quote_stays_pending() {
+        let mut wallet = wallet_with_usd_and_eur();
+        let quote = wallet.quote_exchange("USD", "EUR", 150.0, &rates()).expect("Failed to quote");
+        let response = wallet.execute_exchange(&quote.quote_id);
+        assert_eq!(response.error_message.unwrap(), "Insufficient funds");
+
+        wallet.deposit("USD", 100.0);
+        assert!(wallet.execute_exchange(&quote.quote_id).is_successful);
+        assert_eq!(wallet.balance("USD").unwrap(), 50.0);
+        assert!(!wallet.execute_exchange(&quote.quote_id).is_successful);
+    }
+
+    #[test]
+    fn test_quote_keeps_its_accounts() {
+        let mut wallet = wallet_with_usd_and_eur();
+        let quote = wallet.quote_exchange("USD", "EUR", 50.0, &rates()).expect("Failed to quote");
+        assert_eq!(quote.from_account_number, wallet.get_account_by_currency("USD").unwrap().get_account_number());
+
+        // a new default account for the currency does not change what the quote debits
+        let premium = wallet.add_account(Box::new(PremiumAccount::new("USD", 500.0))).unwrap().get_account_number().to_string();
+        wallet.set_default_account(&premium).unwrap();
+        let response = wallet.execute_exchange(&quote.quote_id);
+        assert!(response.is_successful);
+        assert_eq!(response.from_account_number, quote.from_account_number);
+        assert_eq!(wallet.get_account_by_number(&quote.from_account_number).unwrap().get_balance(), 50.0);
+        assert_eq!(wallet.get_account_by_number(&premium).unwrap().get_balance(), 0.0);
+    }
+
+    #[test]
+    fn test_expired_quotes_are_purged() {
+        let mut wallet = wallet_with_usd_and_eur();
+        wallet.set_quote_validity_seconds(0);
+        let expired = wallet.quote_exchange("USD", "EUR", 10.0, &rates()).expect("Failed to quote");
+        wallet.set_quote_validity_seconds(60);
+        let quote = wallet.quote_exchange("USD", "EUR", 10.0, &rates()).expect("Failed to quote");
+
+        let response = wallet.execute_exchange(&expired.quote_id);
+        assert_eq!(response.error_message.unwrap(), format!("No pending quote found with id: {}", expired.quote_id));
+        assert!(wallet.execute_exchange(&quote.quote_id).is_successful);
+    }
+
+    #[test]
+    fn test_quote_validation() {
+        let mut wallet = wallet_with_usd_and_eur();
+        assert!(wallet.quote_exchange("USD", "GBP", 10.0, &rates()).is_err());
+        assert!(wallet.quote_exchange("USD", "USD", 10.0, &rates()).is_err());
+        assert!(wallet.quote_exchange("USD", "EUR", -1.0, &rates()).is_err());
+        assert!(wallet.set_exchange_spread(1.5).is_err());
+    }
+}
@@ -0,0 +1,156 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use std::process::Command;
+    use wallet_system::account_number::AccountNumber;
+    use wallet_system::accounts::{Account, AccountType};
+    use wallet_system::basic_account::BasicAccount;
+    use wallet_system::export::{self, ActivityRecord, ExportFormat};
+    use wallet_system::service::{StoredAccount, StoredWallet};
+    use wallet_system::transactions::{self, Transaction, TransactionKind};
+    use wallet_system::wallets::WalletType;
+
+    const GENERATED_AT: u64 = 1_706_788_800; // 2024-02-01 12:00:00 UTC
+
+    fn transaction(kind: TransactionKind, amount: f64, balance_after: f64, counterparty: Option<&str>, timestamp: u64) -> Transaction {
+        let mut transaction = Transaction::new(kind, amount, balance_after, counterparty);
+        transaction.timestamp = timestamp;
+        transaction
+    }
+
+    fn sample_account() -> BasicAccount {
+        let day = |date: &str| transactions::parse_date(date).unwrap();
+        let history = vec![
+            transaction(TransactionKind::Deposit, 250.0, 250.0, None, day("2024-01-02") + 9 * 3_600),
+            transaction(TransactionKind::Withdrawal, 40.5, 209.5, None, day("2024-01-05") + 14 * 3_600 + 30 * 60),
+            transaction(TransactionKind::TransferOut, 100.0, 109.5, Some("Premium-8ZC4N2WQ7D-02"), day("2024-01-10") + 8 * 3_600),
+            transaction(TransactionKind::TransferIn, 20.0, 129.5, Some("Basic-Q3V7H1JK5R-84"), day("2024-01-15") + 17 * 3_600),
+            transaction(TransactionKind::Fee, 1.25, 128.25, None, day("2024-01-31") + 23 * 3_600),
+        ];
+        BasicAccount::restore(AccountNumber::parse("Basic-4F9T2XK8LM-19").unwrap(), "USD", 128.25, history)
+    }
+
+    fn fixture(name: &str) -> String {
+        let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "fixtures", name].iter().collect();
+        fs::read_to_string(path).unwrap()
+    }
+
+    fn records(account: &BasicAccount) -> Vec<ActivityRecord> {
+        account.get_history().iter().map(ActivityRecord::from).collect()
+    }
+
+    #[test]
+    fn test_ofx_matches_sample_file() {
+        let account = sample_account();
+        assert_eq!(export::export_account(ExportFormat::Ofx, &account, GENERATED_AT), fixture("sample.ofx"));
+    }
+
+    #[test]
+    fn test_qif_matches_sample_file() {
+        let account = sample_account();
+        assert_eq!(export::export_account(ExportFormat::Qif, &account, GENERATED_AT), fixture("sample.qif"));
+    }
+
+    #[test]
+    fn test_ofx_round_trip() {
+        let account = sample_account();
+        let activity = export::parse_ofx(&fixture("sample.ofx")).unwrap();
+        assert_eq!(activity.account_number.as_deref(), Some("Basic-4F9T2XK8LM-19"));
+        assert_eq!(activity.currency.as_deref(), Some("USD"));
+        assert_eq!(activity.ledger_balance, Some(128.25));
+        assert_eq!(activity.records, records(&account));
+    }
+
+    #[test]
+    fn test_qif_round_trip_keeps_dates() {
+        let account = sample_account();
+        let activity = export::parse_qif(&fixture("sample.qif")).unwrap();
+        assert_eq!(activity.account_number.as_deref(), Some("Basic-4F9T2XK8LM-19"));
+        assert_eq!(activity.currency, None);
+        let expected: Vec<ActivityRecord> = records(&account)
+            .into_iter()
+            .map(|record| ActivityRecord { timestamp: record.timestamp - record.timestamp % 86_400, ..record })
+            .collect();
+        assert_eq!(activity.records, expected);
+    }
+
+    #[test]
+    fn test_parses_files_from_other_tools() {
+        let qif = "!Type:Bank\nD1/31'24\nT-1,050.00\nPLandlord\n^\nD02/01/2024\nT12.00\n^\n";
+        let activity = export::parse_qif(qif).unwrap();
+        assert_eq!(activity.records.len(), 2);
+        assert_eq!(activity.records[0].kind, TransactionKind::Withdrawal);
+        assert_eq!(activity.records[0].amount, 1050.0);
+        assert_eq!(activity.records[0].counterparty_account_number, None);
+        assert_eq!(activity.records[1].timestamp, transactions::parse_date("2024-02-01").unwrap());
+        assert!(export::parse_qif("!Type:Bank\nT5.00\n^\n").is_err());
+
+        let ofx = "<OFX><STMTRS><CURDEF>EUR<BANKTRANLIST><STMTTRN><TRNTYPE>INT<DTPOSTED>20240131120000.000[0:GMT]<TRNAMT>0.42</STMTTRN></BANKTRANLIST></STMTRS></OFX>";
+        let activity = export::parse_ofx(ofx).unwrap();
+        assert_eq!(activity.currency.as_deref(), Some("EUR"));
+        assert_eq!(activity.records[0].kind, TransactionKind::Interest);
+        assert_eq!(activity.records[0].timestamp, transactions::parse_date("2024-01-31").unwrap() + 12 * 3_600);
+        assert!(export::parse_ofx("not ofx").is_err());
+        assert!("csv".parse::<ExportFormat>().is_err());
+    }
+
+    #[test]
+    fn test_qif_lines_starting_with_multibyte_characters() {
+        let qif = "!Type:Bank\nD01/31/2024\nT-12.00\n€ conversion note\nPCafé Zürich\nMTransferOut\n^\n";
+        let activity = export::parse_qif(qif).unwrap();
+        assert_eq!(activity.records.len(), 1);
+        assert_eq!(activity.records[0].amount, 12.0);
+        assert_eq!(activity.records[0].counterparty_account_number.as_deref(), Some("Café Zürich"));
+        assert!(export::parse_qif("!Account\nÑ\n^\n").is_ok());
+    }
+
+    #[test]
+    fn test_cli_export() {
+        let account = sample_account();
+        let state_path = std::env::temp_dir().join(format!("wallet_export_state_{}.json", std::process::id()));
+        let output_path = std::env::temp_dir().join(format!("wallet_export_{}.qif", std::process::id()));
+        let wallet = StoredWallet {
+            wallet_id: "Basic-wallet".to_string(),
+            wallet_type: WalletType::Basic,
+            accounts: vec![StoredAccount {
+                account_number: account.get_account_number().to_string(),
+                account_type: AccountType::Basic,
+                currency: "USD".to_string(),
+                balance: account.get_balance(),
+                overdraft_limit: 0.0,
+                is_default: true,
+                history: account.get_history().to_vec(),
+            }],
+        };
+        fs::write(&state_path, serde_json::to_string(&vec![wallet]).unwrap()).unwrap();
+        let state = state_path.to_str().unwrap();
+
+        let status = Command::new(env!("CARGO_BIN_EXE_wallet_cli"))
+            .args(["export", "Basic-wallet", "--account", "Basic-4F9T2XK8LM-19", "--state", state, "--format", "qif", "--output", output_path.to_str().unwrap()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        assert_eq!(fs::read_to_string(&output_path).unwrap(), fixture("sample.qif"));
+
+        // A wallet with a single account needs no --account
+        let output = Command::new(env!("CARGO_BIN_EXE_wallet_cli"))
+            .args(["export", "Basic-wallet", "--state", state])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let activity = export::parse_ofx(&String::from_utf8(output.stdout).unwrap()).unwrap();
+        assert_eq!(activity.account_number.as_deref(), Some("Basic-4F9T2XK8LM-19"));
+        assert_eq!(activity.currency.as_deref(), Some("USD"));
+        assert_eq!(activity.records, records(&account));
+
+        let output = Command::new(env!("CARGO_BIN_EXE_wallet_cli"))
+            .args(["export", "Basic-wallet", "--account", "Basic-Q3V7H1JK5R-84", "--state", state])
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+
+        fs::remove_file(state_path).unwrap();
+        fs::remove_file(output_path).unwrap();
+    }
+}
@@ -0,0 +1,185 @@
+#[cfg(test)]
+mod tests {
+    use wallet_system::import::{ColumnMapping, CsvImporter, ImportOperation};
+    use wallet_system::service::{AddAccountRequest, AmountRequest, CreateWalletRequest, WalletService};
+    use wallet_system::transactions::TransactionKind;
+
+    /// Service with a basic USD wallet, a multi-currency wallet holding premium USD (overdraft 50) and EUR accounts
+    fn service() -> (WalletService, String, String) {
+        let mut service = WalletService::new();
+        let basic = service.create_wallet(&CreateWalletRequest {
+            wallet_type: "basic".to_string(),
+            currency: Some("USD".to_string()),
+            account_type: None,
+            overdraft_limit: None,
+        }).unwrap();
+        let multi = service.create_wallet(&CreateWalletRequest {
+            wallet_type: "multi".to_string(),
+            currency: None,
+            account_type: None,
+            overdraft_limit: None,
+        }).unwrap();
+        for (account_type, currency, overdraft_limit) in [("premium", "USD", Some(50.0)), ("basic", "EUR", None)] {
+            service.add_account(&multi.wallet_id, &AddAccountRequest {
+                account_type: account_type.to_string(),
+                currency: currency.to_string(),
+                overdraft_limit,
+            }).unwrap();
+        }
+        (service, basic.wallet_id, multi.wallet_id)
+    }
+
+    fn balance(service: &WalletService, wallet_id: &str, currency: &str) -> f64 {
+        service.balance(wallet_id, currency).unwrap().balance
+    }
+
+    #[test]
+    fn test_import_applies_every_row() {
+        let (mut service, basic, multi) = service();
+        let csv = format!(
+            "wallet_id,currency,amount,type,counterparty\n\
+             {basic},USD,100.00,deposit,\n\
+             {basic},USD,30,transfer,{multi}\n\
+             {multi},USD,70,withdrawal,\n\
+             \n\
+             {multi},EUR,12.5,Deposit,\n"
+        );
+        let report = CsvImporter::default().import(&mut service, &csv);
+
+        assert!(report.is_successful(), "{:?}", report.errors);
+        assert!(!report.dry_run);
+        assert_eq!((report.rows, report.applied), (4, 4));
+        assert_eq!(balance(&service, &basic, "USD"), 70.0);
+        // the transfer funds part of the withdrawal, the overdraft covers the rest
+        assert_eq!(balance(&service, &multi, "USD"), -40.0);
+        assert_eq!(balance(&service, &multi, "EUR"), 12.5);
+    }
+
+    #[test]
+    fn test_failed_batch_is_not_applied() {
+        let (mut service, basic, multi) = service();
+        let csv = format!(
+            "wallet_id,currency,amount,type,counterparty\n\
+             {basic},USD,100,deposit,\n\
+             {basic},USD,150,withdrawal,\n\
+             Basic-missing,USD,5,deposit,\n\
+             {multi},GBP,5,deposit,\n\
+             {multi},USD,-5,deposit,\n\
+             {multi},USD,abc,refund,\n\
+             {multi},USD,5,transfer,\n\
+             {multi},USD,5,transfer,{multi}\n\
+             {multi},USD,60,withdrawal,\n"
+        );
+        let report = CsvImporter::default().import(&mut service, &csv);
+
+        assert!(!report.is_successful());
+        assert_eq!((report.rows, report.applied), (1, 0));
+        let lines: Vec<(usize, Option<&str>, &str)> = report.errors.iter()
+            .map(|e| (e.line, e.column.as_deref(), e.message.as_str()))
+            .collect();
+        assert_eq!(lines[0], (3, None, "Insufficient funds"));
+        assert_eq!(lines[1], (4, None, "No wallet found with id: Basic-missing"));
+        assert!(lines[2].2.starts_with("No account found with currency: GBP"));
+        assert_eq!(lines[3], (6, Some("amount"), "Amount must be a positive number: -5"));
+        assert_eq!(lines[4], (7, Some("amount"), "Invalid amount: abc"));
+        assert_eq!(lines[5], (7, Some("type"), "Unknown transaction type: refund"));
+        assert_eq!(lines[6], (8, Some("counterparty"), "A transfer needs a counterparty wallet id"));
+        assert_eq!(lines[7], (9, None, "Cannot transfer between a wallet and itself"));
+        assert_eq!(lines[8], (10, None, "Overdraft limit exceeded"));
+        assert_eq!(report.errors.len(), 9);
+
+        assert_eq!(balance(&service, &basic, "USD"), 0.0);
+        assert!(service.history(&basic, &service.get_wallet(&basic).unwrap().accounts[0].account_number).unwrap().is_empty());
+
+        let error_report = report.errors_to_csv();
+        assert!(error_report.starts_with("line,column,error\n3,,Insufficient funds\n"));
+        assert!(error_report.contains("7,amount,Invalid amount: abc\n"));
+    }
+
+    #[test]
+    fn test_refused_row_leaves_the_service_untouched() {
+        let (mut service, basic, multi) = service();
+        let request = AmountRequest { currency: "USD".to_string(), amount: 20.0, account_number: None };
+        service.deposit(&multi, &request).unwrap();
+
+        // the last deposit passes validation, but the wallet refuses the balance it would overflow to
+        let csv = format!(
+            "wallet_id,currency,amount,type,counterparty\n\
+             {basic},USD,100,deposit,\n\
+             {basic},USD,30,transfer,{multi}\n\
+             {multi},USD,45,withdrawal,\n\
+             {basic},USD,{max:e},deposit,\n\
+             {basic},USD,{max:e},deposit,\n",
+            max = f64::MAX,
+        );
+        let report = CsvImporter::default().import(&mut service, &csv);
+
+        assert!(!report.is_successful());
+        assert_eq!((report.rows, report.applied), (5, 0));
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].line, 6);
+        assert!(report.errors[0].message.contains("would overflow"));
+
+        assert_eq!(balance(&service, &basic, "USD"), 0.0);
+        assert_eq!(balance(&service, &multi, "USD"), 20.0);
+        let basic_account = service.get_wallet(&basic).unwrap().accounts[0].account_number.clone();
+        assert!(service.history(&basic, &basic_account).unwrap().is_empty());
+        let multi_account = service.get_wallet(&multi).unwrap().accounts[0].account_number.clone();
+        let history = service.history(&multi, &multi_account).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].kind, TransactionKind::Deposit);
+    }
+
+    #[test]
+    fn test_dry_run_changes_nothing() {
+        let (service, basic, _) = service();
+        let csv = format!("wallet_id,currency,amount,type\n{basic},USD,10,deposit\n{basic},USD,10,withdraw\n");
+        let report = CsvImporter::default().dry_run(&service, &csv);
+
+        assert!(report.is_successful());
+        assert!(report.dry_run);
+        assert_eq!((report.rows, report.applied), (2, 0));
+        assert_eq!(balance(&service, &basic, "USD"), 0.0);
+    }
+
+    #[test]
+    fn test_custom_mapping_and_quoting() {
+        let (mut service, basic, multi) = service();
+        let mapping = ColumnMapping {
+            wallet_id: "Wallet".to_string(),
+            currency: "Ccy".to_string(),
+            amount: "Value".to_string(),
+            operation: "Kind".to_string(),
+            counterparty: "To".to_string(),
+        };
+        let importer = CsvImporter::new(mapping);
+        let csv = format!(
+            "Note,Kind,Value,Ccy,Wallet,To\r\n\
+             \"opening, \"\"legacy\"\" system\",deposit,20,USD,{basic},\r\n\
+             \"two\nlines\",transfer,5,USD,{basic},{multi}\r\n\
+             last,deposit,1,EUR,{multi},\r\n"
+        );
+        let report = importer.import(&mut service, &csv);
+
+        assert!(report.is_successful(), "{:?}", report.errors);
+        assert_eq!(report.applied, 3);
+        assert_eq!(balance(&service, &basic, "USD"), 15.0);
+        assert_eq!(balance(&service, &multi, "USD"), 5.0);
+
+        let report = importer.dry_run(&service, &format!("Note,Kind,Value,Ccy,Wallet\n\"a\nb\",deposit,x,USD,{basic}\n"));
+        assert_eq!(report.errors[0].line, 2);
+        let report = importer.dry_run(&service, &format!("Kind,Value,Ccy,Wallet\ndeposit,1,USD,{basic}\n\"open"));
+        assert_eq!(report.errors[0].line, 3);
+        assert_eq!(report.errors[0].message, "Unterminated quoted field");
+    }
+
+    #[test]
+    fn test_header_problems() {
+        let (service, _, _) = service();
+        let report = CsvImporter::default().dry_run(&service, "wallet_id,amount\n");
+        let missing: Vec<&str> = report.errors.iter().map(|e| e.column.as_deref().unwrap()).collect();
+        assert_eq!(missing, vec!["currency", "type"]);
+        assert_eq!(CsvImporter::default().dry_run(&service, "").errors[0].message, "The file is empty");
+        assert_eq!("WITHDRAW".parse::<ImportOperation>(), Ok(ImportOperation::Withdrawal));
+    }
+}
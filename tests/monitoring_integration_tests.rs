@@ -0,0 +1,207 @@
+#[cfg(test)]
+mod tests {
+    use wallet_system::basic_account::BasicAccount;
+    use wallet_system::basic_wallet::BasicWallet;
+    use wallet_system::monitoring::{
+        CustomRule, Evidence, LargeTransactionRule, MonitoredWallet, NewCounterpartiesRule, RapidMovementRule, RuleAction,
+        StructuringRule,
+    };
+    use wallet_system::service::{AmountRequest, ServiceErrorKind, TransferRequest, WalletService};
+    use wallet_system::transactions::TransactionKind;
+    use wallet_system::wallets::Wallet;
+
+    fn monitored_wallet() -> MonitoredWallet {
+        MonitoredWallet::new(Box::new(BasicWallet::new(BasicAccount::new("USD"))))
+    }
+
+    fn recipient() -> BasicWallet<BasicAccount> {
+        BasicWallet::new(BasicAccount::new("USD"))
+    }
+
+    #[test]
+    fn test_large_transaction_alerts_or_blocks() {
+        let mut wallet = monitored_wallet();
+        wallet.add_rule(Box::new(LargeTransactionRule::new("USD", 10_000.0).unwrap()), RuleAction::Alert);
+        wallet.add_rule(Box::new(LargeTransactionRule::new("USD", 50_000.0).unwrap()), RuleAction::Block);
+
+        assert!(wallet.deposit("USD", 9_999.0).is_successful);
+        assert!(wallet.get_alerts().is_empty());
+        assert!(wallet.deposit("USD", 10_000.0).is_successful);
+        assert_eq!(wallet.get_alerts().len(), 1);
+        assert!(!wallet.get_alerts()[0].blocked);
+        assert_eq!(wallet.get_alerts()[0].rule_name, "LargeTransaction");
+        assert!(wallet.get_alerts()[0].alert_id.starts_with("Aml-"));
+
+        let error = wallet.deposit("USD", 60_000.0).error_message.unwrap();
+        assert_eq!(error, "Deposit blocked by AML rules: LargeTransaction");
+        // both thresholds fired, and both alerts record that the deposit was blocked
+        assert_eq!(wallet.get_alerts().len(), 3);
+        assert!(wallet.get_alerts()[1..].iter().all(|alert| alert.blocked));
+        assert_eq!(wallet.balance("USD").unwrap(), 19_999.0);
+        assert_eq!(wallet.get_history().len(), 2);
+
+        assert!(LargeTransactionRule::new("USD", 0.0).is_err());
+    }
+
+    #[test]
+    fn test_structuring() {
+        let mut wallet = monitored_wallet();
+        wallet.add_rule(Box::new(StructuringRule::new("USD", 10_000.0, 10.0, 3, 3_600).unwrap()), RuleAction::Alert);
+
+        assert!(wallet.deposit("USD", 9_500.0).is_successful);
+        assert!(wallet.deposit("USD", 4_000.0).is_successful);
+        assert!(wallet.deposit("USD", 9_900.0).is_successful);
+        assert!(wallet.get_alerts().is_empty());
+        assert!(wallet.withdraw("USD", 9_000.0).is_successful);
+
+        let alerts = wallet.get_alerts();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].operation.kind, TransactionKind::Withdrawal);
+        let amounts: Vec<f64> = alerts[0].evidence.related_operations.iter().map(|o| o.amount).collect();
+        assert_eq!(amounts, vec![9_500.0, 9_900.0]);
+        assert!(alerts[0].evidence.description.starts_with("3 operations"));
+
+        let mut wallet = monitored_wallet();
+        wallet.add_rule(Box::new(StructuringRule::new("USD", 10_000.0, 10.0, 2, 0).unwrap()), RuleAction::Alert);
+        assert!(wallet.deposit("USD", 9_500.0).is_successful);
+        assert!(wallet.deposit("USD", 9_500.0).is_successful);
+        // earlier operations fall outside an empty window
+        assert!(wallet.get_alerts().is_empty());
+
+        assert!(StructuringRule::new("USD", 10_000.0, 100.0, 3, 60).is_err());
+        assert!(StructuringRule::new("USD", 10_000.0, 10.0, 1, 60).is_err());
+    }
+
+    #[test]
+    fn test_rapid_movement() {
+        let mut wallet = monitored_wallet();
+        let mut recipient = recipient();
+        wallet.add_rule(Box::new(RapidMovementRule::new(80.0, 86_400).unwrap()), RuleAction::Block);
+
+        assert!(!wallet.withdraw("USD", 10.0).is_successful);
+        assert!(wallet.deposit("USD", 1_000.0).is_successful);
+        assert!(wallet.withdraw("USD", 500.0).is_successful);
+        let error = wallet.transfer(&mut recipient, "USD", 400.0).error_message.unwrap();
+        assert_eq!(error, "TransferOut blocked by AML rules: RapidMovement");
+        assert_eq!(recipient.balance("USD").unwrap(), 0.0);
+        assert!(wallet.transfer(&mut recipient, "USD", 200.0).is_successful);
+
+        let evidence = &wallet.get_alerts()[0].evidence;
+        assert_eq!(evidence.description, "900 USD out against 1000 USD in within 86400 seconds");
+        assert_eq!(evidence.related_operations.len(), 2);
+    }
+
+    #[test]
+    fn test_new_counterparties() {
+        let mut wallet = monitored_wallet();
+        wallet.add_rule(Box::new(NewCounterpartiesRule::new(2, 86_400)), RuleAction::Alert);
+        assert!(wallet.deposit("USD", 100.0).is_successful);
+
+        let mut recipients: Vec<BasicWallet<BasicAccount>> = (0..3).map(|_| recipient()).collect();
+        assert!(wallet.transfer(&mut recipients[0], "USD", 1.0).is_successful);
+        assert!(wallet.transfer(&mut recipients[1], "USD", 1.0).is_successful);
+        // paying a known counterparty again is fine
+        assert!(wallet.transfer(&mut recipients[0], "USD", 1.0).is_successful);
+        assert!(wallet.get_alerts().is_empty());
+        assert!(wallet.transfer(&mut recipients[2], "USD", 1.0).is_successful);
+
+        let alert = &wallet.get_alerts()[0];
+        assert_eq!(alert.rule_name, "NewCounterparties");
+        assert_eq!(alert.operation.counterparty_wallet_id.as_deref(), Some(recipients[2].get_wallet_id()));
+        assert_eq!(alert.evidence.related_operations.len(), 2);
+    }
+
+    #[test]
+    fn test_custom_rule() {
+        let mut wallet = monitored_wallet();
+        wallet.add_rule(
+            Box::new(CustomRule::new("RoundAmounts", |operation, history| {
+                let round = history.iter().chain(std::iter::once(operation)).filter(|o| o.amount % 1_000.0 == 0.0).count();
+                (round >= 2).then(|| Evidence {
+                    description: format!("{} round amounts", round),
+                    related_operations: Vec::new(),
+                })
+            })),
+            RuleAction::Block,
+        );
+        assert_eq!(wallet.get_rule_names(), vec!["RoundAmounts"]);
+        assert!(wallet.deposit("USD", 2_000.0).is_successful);
+        assert!(!wallet.deposit("USD", 3_000.0).is_successful);
+        assert_eq!(wallet.get_alerts()[0].evidence.description, "2 round amounts");
+        assert_eq!(wallet.balance("USD").unwrap(), 2_000.0);
+    }
+
+    #[test]
+    fn test_incoming_transfers_count_as_money_in() {
+        let mut wallet = monitored_wallet();
+        let mut sender = recipient();
+        wallet.add_rule(Box::new(RapidMovementRule::new(80.0, 86_400).unwrap()), RuleAction::Block);
+        assert!(sender.deposit("USD", 1_000.0).is_successful);
+        assert!(sender.transfer(&mut wallet, "USD", 1_000.0).is_successful);
+        assert_eq!(wallet.get_history()[0].kind, TransactionKind::TransferIn);
+
+        let response = wallet.withdraw("USD", 900.0);
+        assert_eq!(response.error_message.unwrap(), "Withdrawal blocked by AML rules: RapidMovement");
+        assert_eq!(wallet.get_alerts()[0].evidence.related_operations[0].kind, TransactionKind::TransferIn);
+        assert_eq!(wallet.balance("USD").unwrap(), 1_000.0);
+    }
+
+    #[test]
+    fn test_blocked_incoming_transfer_is_rolled_back() {
+        let mut wallet = monitored_wallet();
+        let mut sender = recipient();
+        wallet.add_rule(Box::new(LargeTransactionRule::new("USD", 500.0).unwrap()), RuleAction::Block);
+        assert!(sender.deposit("USD", 1_000.0).is_successful);
+
+        assert!(!sender.transfer(&mut wallet, "USD", 600.0).is_successful);
+        assert_eq!(sender.balance("USD").unwrap(), 1_000.0);
+        assert_eq!(sender.get_accounts()[0].get_history().len(), 1);
+        assert_eq!(wallet.balance("USD").unwrap(), 0.0);
+        assert!(wallet.get_history().is_empty());
+        assert_eq!(wallet.get_alerts()[0].operation.kind, TransactionKind::TransferIn);
+        assert!(wallet.get_alerts()[0].blocked);
+    }
+
+    #[test]
+    fn test_account_number_calls_are_screened() {
+        let mut wallet = monitored_wallet();
+        let mut recipient = recipient();
+        wallet.add_rule(Box::new(LargeTransactionRule::new("USD", 500.0).unwrap()), RuleAction::Block);
+        let account_number = wallet.get_accounts()[0].get_account_number().to_string();
+        let recipient_account_number = recipient.get_accounts()[0].get_account_number().to_string();
+
+        let response = wallet.deposit_to_account(&account_number, 600.0);
+        assert_eq!(response.error_message.unwrap(), "Deposit blocked by AML rules: LargeTransaction");
+        assert!(wallet.deposit_to_account(&account_number, 400.0).is_successful);
+        assert!(wallet.deposit_to_account(&account_number, 400.0).is_successful);
+        let response = wallet.withdraw_from_account(&account_number, 600.0);
+        assert_eq!(response.error_message.unwrap(), "Withdrawal blocked by AML rules: LargeTransaction");
+        let response = wallet.transfer_from_account(&account_number, &mut recipient, &recipient_account_number, 600.0);
+        assert_eq!(response.error_message.unwrap(), "TransferOut blocked by AML rules: LargeTransaction");
+        assert_eq!(response.sender_account_number, account_number);
+
+        assert_eq!(wallet.balance("USD").unwrap(), 800.0);
+        assert_eq!(recipient.balance("USD").unwrap(), 0.0);
+        assert_eq!(wallet.get_history().len(), 2);
+        assert_eq!(wallet.get_alerts().len(), 3);
+    }
+
+    #[test]
+    fn test_service_calls_are_screened() {
+        let mut wallet = monitored_wallet();
+        wallet.add_rule(Box::new(LargeTransactionRule::new("USD", 500.0).unwrap()), RuleAction::Block);
+        let mut service = WalletService::new();
+        let monitored = service.add_wallet(Box::new(wallet)).unwrap().wallet_id;
+        let other = service.add_wallet(Box::new(recipient())).unwrap().wallet_id;
+        let request = |amount: f64| AmountRequest { currency: "USD".to_string(), amount, account_number: None };
+
+        let error = service.deposit(&monitored, &request(600.0)).err().unwrap();
+        assert_eq!(error.kind, ServiceErrorKind::InvalidRequest);
+        assert_eq!(error.message, "Deposit blocked by AML rules: LargeTransaction");
+        service.deposit(&other, &request(600.0)).unwrap();
+        let transfer = TransferRequest { to_wallet_id: monitored.clone(), currency: "USD".to_string(), amount: 600.0 };
+        assert!(service.transfer(&other, &transfer).is_err());
+        assert_eq!(service.balance(&other, "USD").unwrap().balance, 600.0);
+        assert_eq!(service.balance(&monitored, "USD").unwrap().balance, 0.0);
+    }
+}
@@ -1,9 +1,10 @@
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use wallet_system::account_number::AccountNumber;
     use wallet_system::accounts::{Account, AccountType};
     use wallet_system::basic_account::BasicAccount;
-    use wallet_system::wallets::{Wallet, WalletType, TransferResponse};
+    use wallet_system::premium_account::PremiumAccount;
+    use wallet_system::wallets::{Wallet, WalletType};
     use wallet_system::basic_wallet::BasicWallet;
     use wallet_system::multi_currency_wallet::MultiCurrencyWallet;
 
@@ -128,4 +129,354 @@ mod tests {
         assert!(!response.is_successful);
         assert_eq!(response.error_message.unwrap(), "Source account currency: USD, Receiver account currency: EUR, mismatch");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_upgrade_account_preserves_number_balance_and_history() {
+        let mut wallet = MultiCurrencyWallet::new();
+        let account = BasicAccount::new("USD");
+        let account_number = account.get_account_number().to_string();
+        wallet.add_account(Box::new(account)).expect("Failed to add account");
+        wallet.deposit("USD", 100.0);
+        wallet.withdraw("USD", 30.0);
+
+        let upgraded = wallet.upgrade_account(&account_number, 500.0).expect("Failed to upgrade account");
+        assert_eq!(upgraded.get_account_type(), AccountType::Premium);
+        assert_eq!(upgraded.get_account_number(), account_number);
+        assert_eq!(upgraded.get_balance(), 70.0);
+        assert_eq!(upgraded.get_overdraft_limit(), 500.0);
+        assert_eq!(upgraded.get_history().len(), 2);
+        // the number keeps the prefix of the type it was issued for
+        assert_eq!(AccountNumber::parse(&account_number).unwrap().issued_account_type(), AccountType::Basic);
+
+        // the overdraft is usable straight away
+        let response = wallet.withdraw("USD", 200.0);
+        assert!(response.is_successful);
+        assert_eq!(wallet.balance("USD").unwrap(), -130.0);
+    }
+
+    #[test]
+    fn test_upgrade_refuses_invalid_overdraft_limits() {
+        let mut wallet = MultiCurrencyWallet::new();
+        let account = BasicAccount::new("USD");
+        let account_number = account.get_account_number().to_string();
+        wallet.add_account(Box::new(account)).expect("Failed to add account");
+
+        for overdraft_limit in [-1.0, f64::NAN, f64::INFINITY] {
+            let result = wallet.upgrade_account(&account_number, overdraft_limit);
+            assert!(result.err().unwrap().contains("finite, non-negative"));
+        }
+        assert_eq!(wallet.get_account_by_currency("USD").unwrap().get_account_type(), AccountType::Basic);
+        assert!(!wallet.withdraw("USD", 1_000_000.0).is_successful);
+    }
+
+    #[test]
+    fn test_upgrade_premium_account_fails() {
+        let mut wallet = MultiCurrencyWallet::new();
+        let account = PremiumAccount::new("USD", 100.0);
+        let account_number = account.get_account_number().to_string();
+        wallet.add_account(Box::new(account)).expect("Failed to add account");
+
+        let result = wallet.upgrade_account(&account_number, 500.0);
+        assert!(result.err().unwrap().contains("already a Premium account"));
+    }
+
+    #[test]
+    fn test_downgrade_account() {
+        let mut wallet = MultiCurrencyWallet::new();
+        let account = PremiumAccount::new("EUR", 100.0);
+        let account_number = account.get_account_number().to_string();
+        wallet.add_account(Box::new(account)).expect("Failed to add account");
+        wallet.deposit("EUR", 40.0);
+
+        let downgraded = wallet.downgrade_account(&account_number).expect("Failed to downgrade account");
+        assert_eq!(downgraded.get_account_type(), AccountType::Basic);
+        assert_eq!(downgraded.get_account_number(), account_number);
+        assert_eq!(downgraded.get_balance(), 40.0);
+        assert_eq!(downgraded.get_overdraft_limit(), 0.0);
+        assert_eq!(downgraded.get_history().len(), 1);
+        assert_eq!(AccountNumber::parse(&account_number).unwrap().issued_account_type(), AccountType::Premium);
+    }
+
+    #[test]
+    fn test_downgrade_overdrawn_account_fails() {
+        let mut wallet = MultiCurrencyWallet::new();
+        let account = PremiumAccount::new("EUR", 100.0);
+        let account_number = account.get_account_number().to_string();
+        wallet.add_account(Box::new(account)).expect("Failed to add account");
+        wallet.withdraw("EUR", 50.0);
+
+        let result = wallet.downgrade_account(&account_number);
+        assert!(result.err().unwrap().contains("balance is negative"));
+        assert_eq!(wallet.get_account_by_currency("EUR").unwrap().get_account_type(), AccountType::Premium);
+        assert_eq!(wallet.balance("EUR").unwrap(), -50.0);
+    }
+
+    #[test]
+    fn test_multiple_accounts_same_currency() {
+        let mut wallet = MultiCurrencyWallet::new();
+        let everyday = BasicAccount::new("USD");
+        let everyday_number = everyday.get_account_number().to_string();
+        let premium = PremiumAccount::new("USD", 200.0);
+        let premium_number = premium.get_account_number().to_string();
+        wallet.add_account(Box::new(everyday)).expect("Failed to add account");
+        wallet.add_account(Box::new(premium)).expect("Failed to add account");
+
+        // the first account of a currency is the default for currency-based calls
+        assert_eq!(wallet.get_account_by_currency("USD").unwrap().get_account_number(), everyday_number);
+        wallet.deposit("USD", 100.0);
+        assert!(wallet.deposit_to_account(&premium_number, 50.0).is_successful);
+
+        assert_eq!(wallet.get_account_by_number(&everyday_number).unwrap().get_balance(), 100.0);
+        assert_eq!(wallet.get_account_by_number(&premium_number).unwrap().get_balance(), 50.0);
+        assert_eq!(wallet.balance("USD").unwrap(), 150.0);
+        assert_eq!(wallet.get_accounts_by_currency("USD").len(), 2);
+
+        wallet.set_default_account(&premium_number).expect("Failed to set default account");
+        let response = wallet.withdraw("USD", 120.0);
+        assert!(response.is_successful);
+        assert_eq!(response.account_number, premium_number);
+        assert_eq!(wallet.get_account_by_number(&premium_number).unwrap().get_balance(), -70.0);
+    }
+
+    #[test]
+    fn test_withdraw_from_account_by_number() {
+        let mut wallet = MultiCurrencyWallet::new();
+        let account = BasicAccount::new("USD");
+        let account_number = account.get_account_number().to_string();
+        wallet.add_account(Box::new(account)).expect("Failed to add account");
+        wallet.deposit_to_account(&account_number, 80.0);
+
+        let response = wallet.withdraw_from_account(&account_number, 30.0);
+        assert!(response.is_successful);
+        assert_eq!(response.balance, 50.0);
+
+        let response = wallet.withdraw_from_account("Basic-NOTANUMBER-00", 30.0);
+        assert!(!response.is_successful);
+        assert!(response.error_message.unwrap().starts_with("Invalid check digits"));
+    }
+
+    #[test]
+    fn test_transfer_from_account_to_account() {
+        let mut wallet1 = MultiCurrencyWallet::new();
+        let sender = PremiumAccount::new("USD", 100.0);
+        let sender_number = sender.get_account_number().to_string();
+        wallet1.add_account(Box::new(BasicAccount::new("USD"))).expect("Failed to add account");
+        wallet1.add_account(Box::new(sender)).expect("Failed to add account");
+
+        let mut wallet2 = MultiCurrencyWallet::new();
+        let recipient = BasicAccount::new("USD");
+        let recipient_number = recipient.get_account_number().to_string();
+        wallet2.add_account(Box::new(BasicAccount::new("USD"))).expect("Failed to add account");
+        wallet2.add_account(Box::new(recipient)).expect("Failed to add account");
+
+        let response = wallet1.transfer_from_account(&sender_number, &mut wallet2, &recipient_number, 60.0);
+        assert!(response.is_successful);
+        assert_eq!(response.sender_account_number, sender_number);
+        assert_eq!(response.recipient_account_number, recipient_number);
+        assert_eq!(wallet1.get_account_by_number(&sender_number).unwrap().get_balance(), -60.0);
+        assert_eq!(wallet2.get_account_by_number(&recipient_number).unwrap().get_balance(), 60.0);
+        // the default accounts were not touched
+        assert_eq!(wallet2.get_account_by_currency("USD").unwrap().get_balance(), 0.0);
+    }
+
+    #[test]
+    fn test_transfer_from_account_to_unknown_account() {
+        let mut wallet1 = MultiCurrencyWallet::new();
+        let sender = BasicAccount::new("USD");
+        let sender_number = sender.get_account_number().to_string();
+        wallet1.add_account(Box::new(sender)).expect("Failed to add account");
+        wallet1.deposit("USD", 100.0);
+        let mut wallet2 = MultiCurrencyWallet::new();
+        wallet2.add_account(Box::new(BasicAccount::new("USD"))).expect("Failed to add account");
+
+        let unknown = BasicAccount::new("USD").get_account_number().to_string();
+        let response = wallet1.transfer_from_account(&sender_number, &mut wallet2, &unknown, 60.0);
+        assert!(!response.is_successful);
+        assert!(response.error_message.unwrap().starts_with("No account found with account number"));
+        assert_eq!(wallet1.balance("USD").unwrap(), 100.0);
+    }
+
+    #[test]
+    fn test_internal_transfer_between_accounts() {
+        let mut wallet = MultiCurrencyWallet::new();
+        let everyday = BasicAccount::new("USD");
+        let everyday_number = everyday.get_account_number().to_string();
+        let savings = BasicAccount::new("USD");
+        let savings_number = savings.get_account_number().to_string();
+        wallet.add_account(Box::new(everyday)).expect("Failed to add account");
+        wallet.add_account(Box::new(savings)).expect("Failed to add account");
+        wallet.deposit("USD", 100.0);
+
+        let response = wallet.internal_transfer(&everyday_number, &savings_number, 40.0).expect("Failed to transfer");
+        assert!(response.is_successful);
+        assert_eq!(wallet.get_account_by_number(&everyday_number).unwrap().get_balance(), 60.0);
+        assert_eq!(wallet.get_account_by_number(&savings_number).unwrap().get_balance(), 40.0);
+        assert_eq!(wallet.balance("USD").unwrap(), 100.0);
+
+        let response = wallet.internal_transfer(&savings_number, &everyday_number, 400.0).expect("Failed to transfer");
+        assert!(!response.is_successful);
+
+        assert!(wallet.internal_transfer(&savings_number, &savings_number, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_internal_transfer_currency_mismatch() {
+        let mut wallet = MultiCurrencyWallet::new();
+        let usd = BasicAccount::new("USD");
+        let usd_number = usd.get_account_number().to_string();
+        let eur = BasicAccount::new("EUR");
+        let eur_number = eur.get_account_number().to_string();
+        wallet.add_account(Box::new(usd)).expect("Failed to add account");
+        wallet.add_account(Box::new(eur)).expect("Failed to add account");
+        wallet.deposit("USD", 100.0);
+
+        let result = wallet.internal_transfer(&usd_number, &eur_number, 40.0);
+        assert_eq!(result.err().unwrap(), "Source account currency: USD, Receiver account currency: EUR, mismatch");
+    }
+
+    #[test]
+    fn test_close_account_with_zero_balance() {
+        let mut wallet = MultiCurrencyWallet::new();
+        let first = BasicAccount::new("USD");
+        let first_number = first.get_account_number().to_string();
+        let second = BasicAccount::new("USD");
+        let second_number = second.get_account_number().to_string();
+        wallet.add_account(Box::new(first)).expect("Failed to add account");
+        wallet.add_account(Box::new(second)).expect("Failed to add account");
+        wallet.deposit("USD", 10.0);
+        wallet.withdraw("USD", 10.0);
+
+        let closed = wallet.close_account(&first_number).expect("Failed to close account");
+        assert_eq!(closed.get_history().len(), 2);
+
+        // the closed account is no longer usable but its history is still available
+        assert!(wallet.get_account_by_number(&first_number).is_err());
+        assert_eq!(wallet.get_closed_account_by_number(&first_number).unwrap().get_history().len(), 2);
+        assert_eq!(wallet.get_closed_accounts().len(), 1);
+        // the default moves to the remaining USD account
+        assert_eq!(wallet.get_account_by_currency("USD").unwrap().get_account_number(), second_number);
+    }
+
+    #[test]
+    fn test_close_account_with_balance_fails() {
+        let mut wallet = MultiCurrencyWallet::new();
+        let account = BasicAccount::new("USD");
+        let account_number = account.get_account_number().to_string();
+        wallet.add_account(Box::new(account)).expect("Failed to add account");
+        wallet.deposit("USD", 10.0);
+
+        let result = wallet.close_account(&account_number);
+        assert!(result.err().unwrap().contains("remaining balance"));
+        assert_eq!(wallet.balance("USD").unwrap(), 10.0);
+    }
+
+    #[test]
+    fn test_close_account_with_overdraft_fails() {
+        let mut wallet = MultiCurrencyWallet::new();
+        let account = PremiumAccount::new("USD", 100.0);
+        let account_number = account.get_account_number().to_string();
+        let other = BasicAccount::new("USD");
+        let other_number = other.get_account_number().to_string();
+        wallet.add_account(Box::new(account)).expect("Failed to add account");
+        wallet.add_account(Box::new(other)).expect("Failed to add account");
+        wallet.withdraw("USD", 25.0);
+
+        let result = wallet.close_account_sweeping_to(&account_number, &other_number);
+        assert!(result.err().unwrap().contains("overdraft"));
+        assert!(wallet.get_account_by_number(&account_number).is_ok());
+    }
+
+    #[test]
+    fn test_close_account_sweeping_to_same_wallet() {
+        let mut wallet = MultiCurrencyWallet::new();
+        let account = BasicAccount::new("USD");
+        let account_number = account.get_account_number().to_string();
+        let target = BasicAccount::new("USD");
+        let target_number = target.get_account_number().to_string();
+        wallet.add_account(Box::new(account)).expect("Failed to add account");
+        wallet.add_account(Box::new(target)).expect("Failed to add account");
+        wallet.deposit("USD", 75.0);
+
+        let closed = wallet.close_account_sweeping_to(&account_number, &target_number).expect("Failed to close account");
+        assert_eq!(closed.get_balance(), 0.0);
+        assert_eq!(wallet.get_account_by_number(&target_number).unwrap().get_balance(), 75.0);
+        assert_eq!(wallet.balance("USD").unwrap(), 75.0);
+    }
+
+    #[test]
+    fn test_close_account_sweeping_to_other_wallet() {
+        let mut wallet1 = MultiCurrencyWallet::new();
+        let account = BasicAccount::new("EUR");
+        let account_number = account.get_account_number().to_string();
+        wallet1.add_account(Box::new(account)).expect("Failed to add account");
+        wallet1.deposit("EUR", 30.0);
+
+        let target = BasicAccount::new("EUR");
+        let target_number = target.get_account_number().to_string();
+        let mut wallet2: BasicWallet<BasicAccount> = BasicWallet::new(target);
+
+        wallet1.close_account_sweeping_to_wallet(&account_number, &mut wallet2, &target_number).expect("Failed to close account");
+        assert_eq!(wallet2.balance("EUR").unwrap(), 30.0);
+        assert!(wallet1.balance("EUR").is_err());
+
+        // a closed account number cannot be reused
+        let reused = BasicAccount::with_account_number("EUR", AccountNumber::parse(&account_number).unwrap()).unwrap();
+        assert!(wallet1.add_account(Box::new(reused)).is_err());
+    }
+
+    #[test]
+    fn test_empty_wallet_deposit_and_withdraw_do_not_panic() {
+        let mut wallet = MultiCurrencyWallet::new();
+        let response = wallet.deposit("USD", 100.0);
+        assert!(!response.is_successful);
+        assert_eq!(response.error_message.unwrap(), "No account found with currency: USD");
+
+        let response = wallet.withdraw("USD", 100.0);
+        assert!(!response.is_successful);
+        assert_eq!(response.error_message.unwrap(), "No account found with currency: USD");
+    }
+
+    #[test]
+    fn test_auto_open_account_on_first_deposit() {
+        let mut wallet = MultiCurrencyWallet::new();
+        wallet.set_auto_open_account_type(Some(AccountType::Basic));
+
+        let response = wallet.deposit("GBP", 100.0);
+        assert!(response.is_successful);
+        assert_eq!(response.account_type, AccountType::Basic);
+        assert_eq!(wallet.balance("GBP").unwrap(), 100.0);
+
+        // later deposits reuse the opened account
+        wallet.deposit("GBP", 5.0);
+        assert_eq!(wallet.get_accounts_by_currency("GBP").len(), 1);
+        assert_eq!(wallet.balance("GBP").unwrap(), 105.0);
+
+        // withdrawals never open accounts
+        assert!(!wallet.withdraw("JPY", 1.0).is_successful);
+        assert!(wallet.get_account_by_currency("JPY").is_none());
+    }
+
+    #[test]
+    fn test_auto_open_premium_account() {
+        let mut wallet = MultiCurrencyWallet::new();
+        wallet.set_auto_open_account_type(Some(AccountType::Premium));
+        wallet.set_auto_open_overdraft_limit(250.0).unwrap();
+        for overdraft_limit in [-1.0, f64::NAN, f64::INFINITY] {
+            assert!(wallet.set_auto_open_overdraft_limit(overdraft_limit).is_err());
+        }
+
+        assert!(wallet.deposit("CHF", 10.0).is_successful);
+        let account = wallet.get_account_by_currency("CHF").unwrap();
+        assert_eq!(account.get_account_type(), AccountType::Premium);
+        assert_eq!(account.get_overdraft_limit(), 250.0);
+    }
+
+    #[test]
+    fn test_auto_open_skips_negative_deposit() {
+        let mut wallet = MultiCurrencyWallet::new();
+        wallet.set_auto_open_account_type(Some(AccountType::Basic));
+
+        assert!(!wallet.deposit("USD", -10.0).is_successful);
+        assert!(wallet.get_account_by_currency("USD").is_none());
+    }
+}
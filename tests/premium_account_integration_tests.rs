@@ -1,9 +1,10 @@
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison, clippy::format_in_format_args)]
 mod tests {
-    use super::*;
-    use wallet_system::accounts::{Account, AccountType, DisplayAccount};
+    use wallet_system::accounts::{Account, AccountType};
     use wallet_system::basic_account::BasicAccount;
     use wallet_system::premium_account::PremiumAccount;
+    use wallet_system::transactions::TransactionKind;
 
     #[test]
     fn test_new_account() {
@@ -80,4 +81,44 @@ mod tests {
         let response = account1.transfer(&mut account2, 600.0);
         assert_eq!(response.is_successful, false);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_transfer_records_history_with_counterparty() {
+        let mut account1 = PremiumAccount::new("USD", 500.0);
+        let mut account2 = BasicAccount::new("USD");
+        account1.deposit(100.0);
+        account1.transfer(&mut account2, 300.0);
+
+        let sender_history = account1.get_history();
+        assert_eq!(sender_history.len(), 2);
+        assert_eq!(sender_history[0].kind, TransactionKind::Deposit);
+        assert_eq!(sender_history[1].kind, TransactionKind::TransferOut);
+        assert_eq!(sender_history[1].balance_after, -200.0);
+        assert_eq!(sender_history[1].counterparty_account_number.as_deref(), Some(account2.get_account_number()));
+
+        let recipient_history = account2.get_history();
+        assert_eq!(recipient_history.len(), 1);
+        assert_eq!(recipient_history[0].kind, TransactionKind::TransferIn);
+        assert_eq!(recipient_history[0].counterparty_account_number.as_deref(), Some(account1.get_account_number()));
+    }
+
+    #[test]
+    fn test_reverse_transfer_out() {
+        let mut account = PremiumAccount::new("USD", 500.0);
+        account.deposit(100.0);
+        account.withdraw_to(300.0, "Basic-4F9T2XK8LM-19");
+
+        assert!(!account.reverse_transfer_out(300.0, "Basic-Q3V7H1JK5R-84").is_successful);
+        assert!(!account.reverse_transfer_out(200.0, "Basic-4F9T2XK8LM-19").is_successful);
+        let response = account.reverse_transfer_out(300.0, "Basic-4F9T2XK8LM-19");
+        assert!(response.is_successful);
+        assert_eq!(response.balance, 100.0);
+        assert_eq!(account.get_history().len(), 1);
+        assert_eq!(account.get_history()[0].kind, TransactionKind::Deposit);
+
+        // only the latest entry can be reversed
+        let response = account.reverse_transfer_out(300.0, "Basic-4F9T2XK8LM-19");
+        assert_eq!(response.error_message.as_deref(), Some("No matching transaction to reverse"));
+        assert_eq!(account.get_balance(), 100.0);
+    }
+}
@@ -0,0 +1,136 @@
+#[cfg(test)]
+mod tests {
+    use wallet_system::account_number::AccountNumber;
+    use wallet_system::accounts::{Account, AccountType};
+    use wallet_system::basic_account::BasicAccount;
+    use wallet_system::basic_wallet::BasicWallet;
+    use wallet_system::exchange::StaticExchangeRates;
+    use wallet_system::multi_currency_wallet::MultiCurrencyWallet;
+    use wallet_system::premium_account::PremiumAccount;
+    use wallet_system::reconciliation::{self, Discrepancy};
+    use wallet_system::service::{AmountRequest, CreateWalletRequest, TransferRequest, WalletService};
+    use wallet_system::transactions::{Transaction, TransactionKind};
+    use wallet_system::wallets::Wallet;
+
+    fn create_wallet(service: &mut WalletService, account_type: &str, overdraft_limit: Option<f64>) -> String {
+        service.create_wallet(&CreateWalletRequest {
+            wallet_type: "basic".to_string(),
+            currency: Some("USD".to_string()),
+            account_type: Some(account_type.to_string()),
+            overdraft_limit,
+        }).unwrap().wallet_id
+    }
+
+    fn amount(amount: f64) -> AmountRequest {
+        AmountRequest { currency: "USD".to_string(), amount, account_number: None }
+    }
+
+    #[test]
+    fn test_consistent_service_is_balanced() {
+        let mut service = WalletService::new();
+        let alice = create_wallet(&mut service, "basic", None);
+        let bob = create_wallet(&mut service, "premium", Some(100.0));
+        service.deposit(&alice, &amount(200.0)).unwrap();
+        service.transfer(&alice, &TransferRequest { to_wallet_id: bob.clone(), currency: "USD".to_string(), amount: 75.0 }).unwrap();
+        service.withdraw(&bob, &amount(150.0)).unwrap();
+        assert!(service.withdraw(&alice, &amount(500.0)).is_err());
+
+        let report = reconciliation::reconcile_service(&service);
+        assert!(report.is_balanced(), "{:?}", report.discrepancies);
+        assert_eq!(report.accounts.len(), 2);
+        assert_eq!(report.accounts[1].recomputed_balance, -75.0);
+        let usd = &report.currencies["USD"];
+        assert_eq!(usd.accounts, 2);
+        assert_eq!((usd.deposits, usd.withdrawals), (200.0, 150.0));
+        assert_eq!((usd.transfers_in, usd.transfers_out), (75.0, 75.0));
+        assert_eq!(usd.reported_balance, 50.0);
+    }
+
+    #[test]
+    fn test_balance_and_running_balance_mismatches() {
+        let history = vec![
+            Transaction::new(TransactionKind::Deposit, 100.0, 100.0, None),
+            Transaction::new(TransactionKind::Fee, 1.0, 98.0, None),
+            Transaction::new(TransactionKind::Interest, 2.0, 100.0, None),
+        ];
+        let tampered = BasicAccount::restore(AccountNumber::generate(AccountType::Basic), "EUR", 150.0, history);
+        let healthy = PremiumAccount::restore(AccountNumber::generate(AccountType::Premium), "EUR", 0.0, 10.0, Vec::new());
+
+        let report = reconciliation::reconcile(&[&tampered, &healthy]);
+        assert_eq!(report.discrepancies, vec![
+            Discrepancy::RunningBalanceMismatch {
+                account_number: tampered.get_account_number().to_string(),
+                currency: "EUR".to_string(),
+                index: 1,
+                timestamp: tampered.get_history()[1].timestamp,
+                expected_balance: 99.0,
+                recorded_balance: 98.0,
+            },
+            Discrepancy::BalanceMismatch {
+                account_number: tampered.get_account_number().to_string(),
+                currency: "EUR".to_string(),
+                reported_balance: 150.0,
+                recomputed_balance: 101.0,
+            },
+            Discrepancy::CurrencyTotalMismatch {
+                currency: "EUR".to_string(),
+                reported_balance: 150.0,
+                recomputed_balance: 101.0,
+            },
+        ]);
+        assert_eq!((report.currencies["EUR"].fees, report.currencies["EUR"].interest), (1.0, 2.0));
+    }
+
+    #[test]
+    fn test_unmatched_transfer() {
+        let mut sender = BasicAccount::new("USD");
+        let mut recipient = BasicAccount::new("USD");
+        sender.deposit(50.0);
+        assert!(sender.transfer(&mut recipient, 20.0).is_successful);
+        let lost = PremiumAccount::restore(
+            AccountNumber::generate(AccountType::Premium),
+            "USD",
+            -5.0,
+            5.0,
+            vec![Transaction::new(TransactionKind::TransferOut, 5.0, -5.0, Some(recipient.get_account_number()))],
+        );
+
+        assert!(reconciliation::reconcile(&[&sender, &recipient]).is_balanced());
+        // a counterparty outside the reconciled set is not matched
+        assert!(reconciliation::reconcile(&[&sender]).is_balanced());
+
+        let report = reconciliation::reconcile(&[&sender, &recipient, &lost]);
+        assert_eq!(report.discrepancies.len(), 1);
+        match &report.discrepancies[0] {
+            Discrepancy::UnmatchedTransfer { sent, received, .. } => assert_eq!((*sent, *received), (5.0, 0.0)),
+            other => panic!("unexpected discrepancy {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_wallets_with_exchanges() {
+        let mut rates = StaticExchangeRates::new();
+        rates.set_rate("USD", "EUR", 0.5).unwrap();
+        let mut multi = MultiCurrencyWallet::new();
+        multi.add_account(Box::new(BasicAccount::new("USD"))).unwrap();
+        multi.add_account(Box::new(BasicAccount::new("EUR"))).unwrap();
+        multi.deposit("USD", 100.0);
+        assert!(multi.exchange("USD", "EUR", 40.0, &rates).is_successful);
+        let mut basic = BasicWallet::new(BasicAccount::new("EUR"));
+        assert!(multi.transfer(&mut basic, "EUR", 5.0).is_successful);
+
+        let report = reconciliation::reconcile_wallets(&[&multi, &basic]);
+        assert!(report.is_balanced(), "{:?}", report.discrepancies);
+        assert_eq!(report.currencies["USD"].reported_balance, 60.0);
+        assert_eq!(report.currencies["EUR"].accounts, 2);
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["accounts"].as_array().unwrap().len(), 3);
+        assert_eq!(json["currencies"]["EUR"]["transfers_out"], 5.0);
+        assert!(json["discrepancies"].as_array().unwrap().is_empty());
+
+        let tampered = BasicAccount::restore(AccountNumber::generate(AccountType::Basic), "USD", 1.0, Vec::new());
+        let json: serde_json::Value = serde_json::from_str(&reconciliation::reconcile(&[&tampered]).to_json().unwrap()).unwrap();
+        assert_eq!(json["discrepancies"][0]["kind"], "BalanceMismatch");
+    }
+}
@@ -0,0 +1,175 @@
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use wallet_system::account_number::AccountNumber;
+    use wallet_system::accounts::{AccountType, DisplayAccount};
+    use wallet_system::basic_account::BasicAccount;
+    use wallet_system::basic_wallet::BasicWallet;
+    use wallet_system::multi_currency_wallet::MultiCurrencyWallet;
+    use wallet_system::premium_account::PremiumAccount;
+    use wallet_system::rendering::{AccountSummary, Render, RenderFormat, WalletSummary};
+    use wallet_system::wallets::{DisplayWallet, Wallet, WalletType};
+
+    fn basic_account() -> BasicAccount {
+        BasicAccount::restore(AccountNumber::parse("Basic-4F9T2XK8LM-19").unwrap(), "USD", 128.25, Vec::new())
+    }
+
+    fn premium_account() -> PremiumAccount {
+        PremiumAccount::restore(AccountNumber::parse("Premium-8ZC4N2WQ7D-02").unwrap(), "EUR", -42.5, 1500.0, Vec::new())
+    }
+
+    fn wallet_summary() -> WalletSummary {
+        WalletSummary {
+            wallet_id: "MultiCurrency-abcdefghij".to_string(),
+            wallet_type: WalletType::MultiCurrency,
+            accounts: vec![basic_account().summary(), premium_account().summary()],
+        }
+    }
+
+    #[test]
+    fn test_account_summary() {
+        let summary = premium_account().summary();
+        assert_eq!(summary, AccountSummary {
+            account_number: "Premium-8ZC4N2WQ7D-02".to_string(),
+            account_type: AccountType::Premium,
+            currency: "EUR".to_string(),
+            balance: -42.5,
+            overdraft_limit: 1500.0,
+        });
+        assert_eq!(basic_account().summary(), AccountSummary::from_account(&basic_account()));
+        assert_eq!(premium_account().render(RenderFormat::Text), summary.render(RenderFormat::Text));
+    }
+
+    #[test]
+    fn test_render_account() {
+        assert_eq!(
+            basic_account().render(RenderFormat::Text),
+            "Account Number   Basic-4F9T2XK8LM-19\n\
+             Account Type     Basic\n\
+             Currency         USD\n\
+             Balance          128.25\n\
+             Overdraft Limit  0.00\n"
+        );
+        assert_eq!(
+            premium_account().render(RenderFormat::Markdown),
+            "### Account Premium-8ZC4N2WQ7D-02\n\n\
+             | Field | Value |\n\
+             | --- | --- |\n\
+             | Account Number | Premium-8ZC4N2WQ7D-02 |\n\
+             | Account Type | Premium |\n\
+             | Currency | EUR |\n\
+             | Balance | -42.50 |\n\
+             | Overdraft Limit | 1500.00 |\n"
+        );
+        let json = premium_account().render(RenderFormat::Json);
+        assert_eq!(serde_json::from_str::<AccountSummary>(&json).unwrap(), premium_account().summary());
+    }
+
+    #[test]
+    fn test_render_wallet() {
+        let summary = wallet_summary();
+        assert_eq!(
+            summary.render(RenderFormat::Text),
+            "Wallet ID    MultiCurrency-abcdefghij\n\
+             Wallet Type  MultiCurrency\n\
+             \n\
+             Account Number         Type     Currency  Balance  Overdraft Limit\n\
+             ---------------------  -------  --------  -------  ---------------\n\
+             Basic-4F9T2XK8LM-19    Basic    USD        128.25             0.00\n\
+             Premium-8ZC4N2WQ7D-02  Premium  EUR        -42.50          1500.00\n"
+        );
+        assert_eq!(
+            summary.render(RenderFormat::Markdown),
+            "### Wallet MultiCurrency-abcdefghij (MultiCurrency)\n\n\
+             | Account Number | Type | Currency | Balance | Overdraft Limit |\n\
+             | --- | --- | --- | ---: | ---: |\n\
+             | Basic-4F9T2XK8LM-19 | Basic | USD | 128.25 | 0.00 |\n\
+             | Premium-8ZC4N2WQ7D-02 | Premium | EUR | -42.50 | 1500.00 |\n"
+        );
+        let json = summary.render(RenderFormat::Json);
+        assert_eq!(serde_json::from_str::<WalletSummary>(&json).unwrap(), summary);
+
+        let empty = WalletSummary { accounts: Vec::new(), ..summary };
+        assert!(empty.render(RenderFormat::Text).ends_with("\nNo accounts\n"));
+        assert!(empty.render(RenderFormat::Markdown).ends_with("\nNo accounts\n"));
+    }
+
+    #[test]
+    fn test_render_several_wallets() {
+        let wallets = vec![wallet_summary(), WalletSummary { wallet_id: "Basic-0123456789".to_string(), ..wallet_summary() }];
+        let json = wallets.render(RenderFormat::Json);
+        assert_eq!(serde_json::from_str::<Vec<WalletSummary>>(&json).unwrap(), wallets);
+        assert_eq!(
+            wallets.render(RenderFormat::Text),
+            format!("{}\n{}", wallets[0].render(RenderFormat::Text), wallets[1].render(RenderFormat::Text))
+        );
+    }
+
+    #[test]
+    fn test_wallet_summaries_match_wallet_contents() {
+        let wallet = BasicWallet::new(premium_account());
+        assert_eq!(wallet.summary(), WalletSummary::from_wallet(&wallet));
+        assert_eq!(wallet.summary().accounts, vec![premium_account().summary()]);
+
+        let mut multi = MultiCurrencyWallet::new();
+        multi.add_account(Box::new(basic_account())).unwrap();
+        multi.add_account(Box::new(PremiumAccount::new("GBP", 100.0))).unwrap();
+        multi.deposit("GBP", 12.0);
+        let summary = multi.summary();
+        assert_eq!(summary, WalletSummary::from_wallet(&multi));
+        assert_eq!(summary.wallet_type, WalletType::MultiCurrency);
+        assert_eq!(summary.accounts[1].balance, 12.0);
+        assert_eq!(multi.render(RenderFormat::Markdown), summary.render(RenderFormat::Markdown));
+    }
+
+    #[test]
+    fn test_parse_render_format() {
+        assert_eq!("text".parse::<RenderFormat>().unwrap(), RenderFormat::Text);
+        assert_eq!("JSON".parse::<RenderFormat>().unwrap(), RenderFormat::Json);
+        assert_eq!("md".parse::<RenderFormat>().unwrap(), RenderFormat::Markdown);
+        assert_eq!("markdown".parse::<RenderFormat>().unwrap(), RenderFormat::Markdown);
+        assert_eq!("html".parse::<RenderFormat>().unwrap_err(), "Unknown render format: html");
+    }
+
+    #[test]
+    fn test_cli_create_wallet() {
+        let output = Command::new(env!("CARGO_BIN_EXE_wallet_cli"))
+            .args(["create_wallet", "basic", "--currency", "EUR", "--account-type", "premium", "--overdraft", "250", "--format", "json"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let summary: WalletSummary = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(summary.wallet_type, WalletType::Basic);
+        assert_eq!(summary.accounts.len(), 1);
+        assert_eq!(summary.accounts[0].account_type, AccountType::Premium);
+        assert_eq!(summary.accounts[0].overdraft_limit, 250.0);
+
+        let output = Command::new(env!("CARGO_BIN_EXE_wallet_cli"))
+            .args(["create_wallet", "multi"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let text = String::from_utf8(output.stdout).unwrap();
+        assert!(text.starts_with("Wallet ID    MultiCurrency-"));
+        assert!(text.contains("Wallet Type  MultiCurrency\n"));
+
+        let output = Command::new(env!("CARGO_BIN_EXE_wallet_cli"))
+            .args(["create_wallet", "basic", "--currency", "USD", "--format", "markdown"])
+            .output()
+            .unwrap();
+        assert!(String::from_utf8(output.stdout).unwrap().contains("| --- | --- | --- | ---: | ---: |\n"));
+
+        let output = Command::new(env!("CARGO_BIN_EXE_wallet_cli"))
+            .args(["create_wallet", "basic"])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(String::from_utf8(output.stderr).unwrap(), "A basic wallet needs a currency\n");
+
+        let output = Command::new(env!("CARGO_BIN_EXE_wallet_cli"))
+            .args(["create_wallet", "multi", "--format", "html"])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(2));
+    }
+}
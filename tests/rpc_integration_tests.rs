@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::process::{Command, Stdio};
+    use serde_json::{json, Value};
+    use wallet_system::rpc;
+    use wallet_system::service::WalletService;
+
+    fn call(service: &mut WalletService, method: &str, params: Value) -> Value {
+        let request = json!({ "jsonrpc": "2.0", "method": method, "params": params, "id": 1 });
+        let response = rpc::handle_line(service, &request.to_string()).unwrap();
+        serde_json::from_str(&response).unwrap()
+    }
+
+    #[test]
+    fn test_every_subcommand_is_a_method() {
+        let mut service = WalletService::new();
+        let wallet = call(&mut service, "create_wallet", json!({ "type": "multi" }));
+        let wallet_id = wallet["result"]["wallet_id"].as_str().unwrap().to_string();
+        let other = call(&mut service, "create_wallet", json!({ "type": "basic", "currency": "USD" }));
+        let other_id = other["result"]["wallet_id"].as_str().unwrap().to_string();
+
+        let account = call(&mut service, "create_account", json!({ "wallet_id": wallet_id, "account_type": "basic", "currency": "USD" }));
+        assert_eq!(account["result"]["currency"], "USD");
+        let deposit = call(&mut service, "deposit", json!({ "wallet_id": wallet_id, "currency": "USD", "amount": 50.0 }));
+        assert_eq!(deposit["result"]["balance"], 50.0);
+        let withdraw = call(&mut service, "withdraw", json!({ "wallet_id": wallet_id, "currency": "USD", "amount": 5.0 }));
+        assert_eq!(withdraw["result"]["balance"], 45.0);
+        let transfer = call(&mut service, "transfer", json!({ "from_wallet_id": wallet_id, "to_wallet_id": other_id, "currency": "USD", "amount": 15.0 }));
+        assert_eq!(transfer["result"]["amount"], 15.0);
+        let balance = call(&mut service, "balance", json!({ "wallet_id": other_id, "currency": "USD" }));
+        assert_eq!(balance["result"]["balance"], 15.0);
+        assert_eq!(balance["id"], 1);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut service = WalletService::new();
+        let response = call(&mut service, "balance", json!({ "wallet_id": "Basic-missing", "currency": "USD" }));
+        assert_eq!(response["error"]["code"], -32001);
+        let response = call(&mut service, "deposit", json!({ "wallet_id": "Basic-missing" }));
+        assert_eq!(response["error"]["code"], rpc::INVALID_PARAMS);
+        let response = call(&mut service, "close_wallet", json!({}));
+        assert_eq!(response["error"]["code"], rpc::METHOD_NOT_FOUND);
+
+        let response: Value = serde_json::from_str(&rpc::handle_line(&mut service, "{not json").unwrap()).unwrap();
+        assert_eq!(response["error"]["code"], rpc::PARSE_ERROR);
+        let response: Value = serde_json::from_str(&rpc::handle_line(&mut service, r#"{"method": "balance", "id": 3}"#).unwrap()).unwrap();
+        assert_eq!(response["error"]["code"], rpc::INVALID_REQUEST);
+        assert_eq!(response["id"], 3);
+    }
+
+    #[test]
+    fn test_notifications_and_batches() {
+        let mut service = WalletService::new();
+        let notification = json!({ "jsonrpc": "2.0", "method": "create_wallet", "params": { "type": "multi" } });
+        assert!(rpc::handle_line(&mut service, &notification.to_string()).is_none());
+        assert_eq!(service.list_wallets().len(), 1);
+
+        let batch = json!([
+            { "jsonrpc": "2.0", "method": "list_wallets", "id": "a" },
+            notification,
+            { "jsonrpc": "2.0", "method": "unknown", "id": "b" },
+        ]);
+        let responses: Value = serde_json::from_str(&rpc::handle_line(&mut service, &batch.to_string()).unwrap()).unwrap();
+        let responses = responses.as_array().unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0]["result"].as_array().unwrap().len(), 1);
+        assert_eq!(responses[1]["id"], "b");
+        assert_eq!(service.list_wallets().len(), 2);
+    }
+
+    #[test]
+    fn test_cli_serve_stdio_streams_responses() {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_wallet_cli"))
+            .args(["serve", "--stdio"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut stdin = child.stdin.take().unwrap();
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+
+        // each response arrives before the next request is sent
+        writeln!(stdin, "{}", json!({ "jsonrpc": "2.0", "method": "create_wallet", "params": { "type": "basic", "currency": "EUR" }, "id": 1 })).unwrap();
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        let response: Value = serde_json::from_str(&line).unwrap();
+        let wallet_id = response["result"]["wallet_id"].as_str().unwrap().to_string();
+
+        writeln!(stdin, "{}", json!({ "jsonrpc": "2.0", "method": "deposit", "params": { "wallet_id": wallet_id, "currency": "EUR", "amount": 12.5 }, "id": 2 })).unwrap();
+        line.clear();
+        stdout.read_line(&mut line).unwrap();
+        let response: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(response["result"]["balance"], 12.5);
+        assert_eq!(response["id"], 2);
+
+        drop(stdin);
+        assert!(child.wait().unwrap().success());
+    }
+}
@@ -0,0 +1,181 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use wallet_system::basic_account::BasicAccount;
+    use wallet_system::basic_wallet::BasicWallet;
+    use wallet_system::customer::{ContactDetails, Customer, KycTier};
+    use wallet_system::sanctions::{self, HoldStatus, MatchField, Party, SanctionsScreener, ScreenedTransfer, WatchList, WatchListEntry};
+    use wallet_system::wallets::Wallet;
+
+    const WATCH_LIST_CSV: &str = "entry_id,name,aliases,account_numbers\n\
+        SDN-1,Ivan Petrovich Drago,\"The Siberian Express; I. Drago\",\n\
+        SDN-2,Acme Shell Holdings Ltd,,Basic-4F9T2XK8LM-19\n";
+
+    fn customer(name: &str) -> Customer {
+        let contact = ContactDetails { email: "customer@example.com".to_string(), phone: None, address: None };
+        Customer::new(name, contact, KycTier::Verified)
+    }
+
+    /// Gives the customer a USD wallet holding `balance` and returns its id
+    fn add_wallet(customer: &mut Customer, balance: f64) -> String {
+        let mut wallet = BasicWallet::new(BasicAccount::new("USD"));
+        if balance > 0.0 {
+            wallet.deposit("USD", balance);
+        }
+        customer.add_wallet(Box::new(wallet)).unwrap().get_wallet_id().to_string()
+    }
+
+    fn screener() -> SanctionsScreener {
+        SanctionsScreener::new(WatchList::from_csv(WATCH_LIST_CSV).unwrap())
+    }
+
+    #[test]
+    fn test_name_similarity() {
+        assert_eq!(sanctions::name_similarity("Ivan Drago", "ivan  DRAGO."), 1.0);
+        assert_eq!(sanctions::name_similarity("Drago, Ivan", "Ivan Drago"), 1.0);
+        assert!(sanctions::name_similarity("Ivan Petrovitch Drago", "Ivan Petrovich Drago") > 0.95);
+        assert!(sanctions::name_similarity("Maria Lopez", "Ivan Petrovich Drago") < 0.7);
+        assert_eq!(sanctions::name_similarity("", "Ivan Drago"), 0.0);
+        assert_eq!(sanctions::name_similarity("ÉMILE ZOLA", "émile zola"), 1.0);
+        assert_eq!(sanctions::name_similarity("ВЛАДИМИР ИВАНОВ", "Владимир Иванов"), 1.0);
+    }
+
+    #[test]
+    fn test_load_watch_lists() {
+        let csv_path = std::env::temp_dir().join(format!("wallet_watch_list_{}.csv", std::process::id()));
+        let json_path = std::env::temp_dir().join(format!("wallet_watch_list_{}.json", std::process::id()));
+        fs::write(&csv_path, WATCH_LIST_CSV).unwrap();
+        fs::write(&json_path, r#"[{"entry_id": "UN-7", "name": "Rosa Klebb", "aliases": ["Colonel Klebb"]}]"#).unwrap();
+
+        let from_csv = WatchList::load(csv_path.to_str().unwrap()).unwrap();
+        assert_eq!(from_csv.get_entries().len(), 2);
+        assert_eq!(from_csv.get_entries()[0].aliases, vec!["The Siberian Express", "I. Drago"]);
+        assert_eq!(from_csv.get_entries()[1].account_numbers, vec!["Basic-4F9T2XK8LM-19"]);
+
+        let from_json = WatchList::load(json_path.to_str().unwrap()).unwrap();
+        assert_eq!(from_json.get_entries(), &[WatchListEntry {
+            entry_id: "UN-7".to_string(),
+            name: "Rosa Klebb".to_string(),
+            aliases: vec!["Colonel Klebb".to_string()],
+            account_numbers: Vec::new(),
+        }]);
+
+        assert!(WatchList::load("watch_list.xml").is_err());
+        assert!(WatchList::from_csv("entry_id,alias\n").is_err());
+        fs::remove_file(csv_path).unwrap();
+        fs::remove_file(json_path).unwrap();
+    }
+
+    #[test]
+    fn test_clean_transfer_is_executed() {
+        let mut screener = screener();
+        let (mut sender, mut recipient) = (customer("Maria Lopez"), customer("John Smith"));
+        let from_wallet = add_wallet(&mut sender, 100.0);
+        let to_wallet = add_wallet(&mut recipient, 0.0);
+
+        match screener.transfer(&mut sender, &from_wallet, &mut recipient, &to_wallet, "USD", 40.0).unwrap() {
+            ScreenedTransfer::Executed(response) => assert!(response.is_successful),
+            ScreenedTransfer::Held(held) => panic!("transfer held: {:?}", held.matches),
+        }
+        assert_eq!(recipient.total_balance("USD"), 40.0);
+        assert!(screener.get_review_queue().is_empty());
+        assert!(screener.transfer(&mut sender, "Basic-missing", &mut recipient, &to_wallet, "USD", 1.0).is_err());
+    }
+
+    #[test]
+    fn test_fuzzy_match_holds_transfer_until_review() {
+        let mut screener = screener();
+        let (mut sender, mut recipient) = (customer("Maria Lopez"), customer("Drago, Ivan Petrovitch"));
+        let from_wallet = add_wallet(&mut sender, 100.0);
+        let to_wallet = add_wallet(&mut recipient, 0.0);
+
+        let held = match screener.transfer(&mut sender, &from_wallet, &mut recipient, &to_wallet, "USD", 40.0).unwrap() {
+            ScreenedTransfer::Held(held) => held,
+            ScreenedTransfer::Executed(_) => panic!("transfer was not held"),
+        };
+        assert!(held.hold_id.starts_with("Hold-"));
+        assert_eq!(held.matches.len(), 1);
+        assert_eq!((held.matches[0].party, held.matches[0].field), (Party::Recipient, MatchField::Name));
+        assert_eq!(held.matches[0].entry_id, "SDN-1");
+        assert!(held.matches[0].score > 0.95 && held.matches[0].score < 1.0);
+        assert_eq!(sender.total_balance("USD"), 100.0);
+        assert_eq!(screener.get_review_queue().len(), 1);
+
+        let mut other = customer("Jane Roe");
+        add_wallet(&mut other, 0.0);
+        assert!(screener.release(&held.hold_id, "compliance", &mut sender, &mut other).is_err());
+        assert!(screener.release(&held.hold_id, "compliance", &mut sender, &mut recipient).unwrap().is_successful);
+        assert_eq!(recipient.total_balance("USD"), 40.0);
+        let released = screener.get_held_transfer(&held.hold_id).unwrap();
+        assert_eq!(released.status, HoldStatus::Released);
+        assert_eq!(released.reviewed_by.as_deref(), Some("compliance"));
+        assert!(screener.reject(&held.hold_id, "compliance").is_err());
+
+        // a stricter threshold lets the misspelt name through
+        screener.set_name_match_threshold(0.995).unwrap();
+        assert!(matches!(
+            screener.transfer(&mut sender, &from_wallet, &mut recipient, &to_wallet, "USD", 1.0).unwrap(),
+            ScreenedTransfer::Executed(_)
+        ));
+        assert!(screener.set_name_match_threshold(1.5).is_err());
+    }
+
+    #[test]
+    fn test_release_screens_the_accounts_again() {
+        let mut screener = screener();
+        let (mut sender, mut recipient) = (customer("Maria Lopez"), customer("Ivan Drago"));
+        let from_wallet = add_wallet(&mut sender, 100.0);
+        let to_wallet = add_wallet(&mut recipient, 0.0);
+        let held = match screener.transfer(&mut sender, &from_wallet, &mut recipient, &to_wallet, "USD", 40.0).unwrap() {
+            ScreenedTransfer::Held(held) => held,
+            ScreenedTransfer::Executed(_) => panic!("transfer was not held"),
+        };
+
+        // the recipient's account is listed after the hold was reviewed
+        let mut watch_list = WatchList::from_csv(WATCH_LIST_CSV).unwrap();
+        watch_list.add_entry(WatchListEntry {
+            entry_id: "SDN-3".to_string(),
+            name: "Drago Trading".to_string(),
+            aliases: Vec::new(),
+            account_numbers: vec![held.recipient_account_number.clone().unwrap()],
+        });
+        screener.set_watch_list(watch_list);
+
+        let error = screener.release(&held.hold_id, "compliance", &mut sender, &mut recipient).err().unwrap();
+        assert!(error.contains("now uses accounts on the watch list"));
+        let still_held = screener.get_held_transfer(&held.hold_id).unwrap();
+        assert_eq!(still_held.status, HoldStatus::Held);
+        assert_eq!(still_held.matches.len(), 2);
+        assert_eq!((still_held.matches[1].party, still_held.matches[1].field), (Party::Recipient, MatchField::AccountNumber));
+        assert_eq!(recipient.total_balance("USD"), 0.0);
+
+        // once the new match is part of the review, the transfer can be released
+        assert!(screener.release(&held.hold_id, "compliance", &mut sender, &mut recipient).unwrap().is_successful);
+        assert_eq!(recipient.total_balance("USD"), 40.0);
+    }
+
+    #[test]
+    fn test_alias_and_account_number_matches() {
+        let mut screener = screener();
+        let matches = screener.screen(Party::Sender, "I Drago", &[]);
+        assert_eq!(matches[0].field, MatchField::Alias);
+        assert_eq!(matches[0].matched_value, "I. Drago");
+
+        let matches = screener.screen(Party::Recipient, "Jane Roe", &["basic-4f9t2xk8lm-19"]);
+        assert_eq!(matches.len(), 1);
+        assert_eq!((matches[0].field, matches[0].score), (MatchField::AccountNumber, 1.0));
+
+        let (mut sender, mut recipient) = (customer("The Siberian Express"), customer("Jane Roe"));
+        let from_wallet = add_wallet(&mut sender, 100.0);
+        let to_wallet = add_wallet(&mut recipient, 0.0);
+        let held = match screener.transfer(&mut sender, &from_wallet, &mut recipient, &to_wallet, "USD", 10.0).unwrap() {
+            ScreenedTransfer::Held(held) => held,
+            ScreenedTransfer::Executed(_) => panic!("transfer was not held"),
+        };
+        assert_eq!(held.matches[0].party, Party::Sender);
+        assert_eq!(held.sender_account_number.as_deref(), sender.get_wallet(&from_wallet).unwrap().get_account_number_by_index(0));
+        assert_eq!(screener.reject(&held.hold_id, "compliance").unwrap().status, HoldStatus::Rejected);
+        assert!(screener.get_review_queue().is_empty());
+        assert_eq!(sender.total_balance("USD"), 100.0);
+    }
+}
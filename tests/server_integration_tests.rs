@@ -0,0 +1,124 @@
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::sync::mpsc;
+    use std::thread;
+    use serde_json::{json, Value};
+    use wallet_system::server::WalletServer;
+    use wallet_system::service::WalletService;
+
+    /// Starts a server on a free local port and returns the port
+    fn start_server() -> u16 {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let server = WalletServer::bind("127.0.0.1:0").expect("Failed to bind");
+            sender.send(server.local_address().unwrap().port()).unwrap();
+            server.run(WalletService::new());
+        });
+        receiver.recv().unwrap()
+    }
+
+    fn request(port: u16, method: &str, path: &str, body: Option<Value>) -> (u16, Value) {
+        let body = body.map(|b| b.to_string()).unwrap_or_default();
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            method, path, body.len(), body
+        ).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        let status = response.split(' ').nth(1).unwrap().parse().unwrap();
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    fn create_wallet(port: u16, body: Value) -> Value {
+        let (status, wallet) = request(port, "POST", "/wallets", Some(body));
+        assert_eq!(status, 201);
+        wallet
+    }
+
+    #[test]
+    fn test_wallet_lifecycle() {
+        let port = start_server();
+        let wallet = create_wallet(port, json!({ "type": "multi" }));
+        let wallet_id = wallet["wallet_id"].as_str().unwrap();
+        assert_eq!(wallet["wallet_type"], "MultiCurrency");
+
+        let (status, account) = request(port, "POST", &format!("/wallets/{}/accounts", wallet_id),
+            Some(json!({ "account_type": "premium", "currency": "USD", "overdraft_limit": 50.0 })));
+        assert_eq!(status, 201);
+        let account_number = account["account_number"].as_str().unwrap();
+
+        let (status, account) = request(port, "POST", &format!("/wallets/{}/deposit", wallet_id),
+            Some(json!({ "currency": "USD", "amount": 100.0 })));
+        assert_eq!(status, 200);
+        assert_eq!(account["balance"], 100.0);
+
+        let (status, account) = request(port, "POST", &format!("/wallets/{}/withdraw", wallet_id),
+            Some(json!({ "currency": "USD", "amount": 130.0, "account_number": account_number })));
+        assert_eq!(status, 200);
+        assert_eq!(account["balance"], -30.0);
+
+        let (status, balance) = request(port, "GET", &format!("/wallets/{}/balance?currency=USD", wallet_id), None);
+        assert_eq!(status, 200);
+        assert_eq!(balance["balance"], -30.0);
+
+        let (status, history) = request(port, "GET", &format!("/wallets/{}/accounts/{}/history", wallet_id, account_number), None);
+        assert_eq!(status, 200);
+        assert_eq!(history.as_array().unwrap().len(), 2);
+        assert_eq!(history[1]["kind"], "Withdrawal");
+    }
+
+    #[test]
+    fn test_transfer_between_wallets() {
+        let port = start_server();
+        let sender = create_wallet(port, json!({ "type": "basic", "currency": "EUR" }));
+        let recipient = create_wallet(port, json!({ "type": "basic", "currency": "EUR", "account_type": "premium" }));
+        let sender_id = sender["wallet_id"].as_str().unwrap();
+        let recipient_id = recipient["wallet_id"].as_str().unwrap();
+        request(port, "POST", &format!("/wallets/{}/deposit", sender_id), Some(json!({ "currency": "EUR", "amount": 80.0 })));
+
+        let (status, transfer) = request(port, "POST", &format!("/wallets/{}/transfer", sender_id),
+            Some(json!({ "to_wallet_id": recipient_id, "currency": "EUR", "amount": 30.0 })));
+        assert_eq!(status, 200);
+        assert_eq!(transfer["recipient_account_number"], recipient["accounts"][0]["account_number"]);
+
+        let (_, wallet) = request(port, "GET", &format!("/wallets/{}", recipient_id), None);
+        assert_eq!(wallet["accounts"][0]["balance"], 30.0);
+
+        let (status, body) = request(port, "POST", &format!("/wallets/{}/transfer", sender_id),
+            Some(json!({ "to_wallet_id": recipient_id, "currency": "EUR", "amount": 500.0 })));
+        assert_eq!(status, 422);
+        assert_eq!(body["error"], "Insufficient funds");
+    }
+
+    #[test]
+    fn test_error_status_codes() {
+        let port = start_server();
+        let wallet = create_wallet(port, json!({ "type": "basic", "currency": "USD" }));
+        let wallet_id = wallet["wallet_id"].as_str().unwrap();
+
+        let (status, _) = request(port, "GET", "/wallets/Basic-missing", None);
+        assert_eq!(status, 404);
+        let (status, _) = request(port, "GET", &format!("/wallets/{}/balance?currency=GBP", wallet_id), None);
+        assert_eq!(status, 404);
+        let (status, body) = request(port, "POST", &format!("/wallets/{}/deposit", wallet_id), Some(json!({ "currency": "USD", "amount": -5.0 })));
+        assert_eq!(status, 400);
+        assert_eq!(body["error"], "Cannot deposit a negative amount");
+        let (status, _) = request(port, "POST", &format!("/wallets/{}/accounts", wallet_id), Some(json!({ "account_type": "basic", "currency": "EUR" })));
+        assert_eq!(status, 409);
+        let (status, _) = request(port, "POST", "/wallets", Some(json!({ "type": "gold" })));
+        assert_eq!(status, 400);
+        let (status, body) = request(port, "POST", "/wallets", None);
+        assert_eq!(status, 400);
+        assert!(body["error"].as_str().unwrap().starts_with("Invalid JSON body"));
+        let (status, _) = request(port, "DELETE", &format!("/wallets/{}", wallet_id), None);
+        assert_eq!(status, 405);
+        let (status, _) = request(port, "GET", "/accounts", None);
+        assert_eq!(status, 404);
+    }
+}
@@ -0,0 +1,192 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::process::Command;
+    use wallet_system::accounts::AccountType;
+    use wallet_system::basic_account::BasicAccount;
+    use wallet_system::basic_wallet::BasicWallet;
+    use wallet_system::service::{AddAccountRequest, AmountRequest, CreateWalletRequest, ServiceError, ServiceErrorKind, TransferRequest, WalletService};
+    use wallet_system::test_support::{Fault, FaultyWallet, WalletCall};
+
+    fn amount(currency: &str, amount: f64) -> AmountRequest {
+        AmountRequest { currency: currency.to_string(), amount, account_number: None }
+    }
+
+    fn kind<T>(result: Result<T, ServiceError>) -> ServiceErrorKind {
+        result.err().unwrap().kind
+    }
+
+    fn multi_wallet(service: &mut WalletService) -> String {
+        let wallet = service.create_wallet(&CreateWalletRequest {
+            wallet_type: "multi".to_string(),
+            currency: None,
+            account_type: None,
+            overdraft_limit: None,
+        }).unwrap();
+        service.add_account(&wallet.wallet_id, &AddAccountRequest {
+            account_type: "premium".to_string(),
+            currency: "USD".to_string(),
+            overdraft_limit: Some(50.0),
+        }).unwrap();
+        wallet.wallet_id
+    }
+
+    #[test]
+    fn test_error_kinds() {
+        let mut service = WalletService::new();
+        let multi = multi_wallet(&mut service);
+        let faulty = FaultyWallet::new(Box::new(BasicWallet::new(BasicAccount::new("USD"))));
+        faulty.get_faults().inject(WalletCall::Withdraw, Fault::Fail("Insufficient funds".to_string()));
+        let basic = service.add_wallet(Box::new(faulty)).unwrap().wallet_id;
+        service.deposit(&basic, &amount("USD", 10.0)).unwrap();
+
+        assert_eq!(kind(service.withdraw(&multi, &amount("USD", 60.0))), ServiceErrorKind::InsufficientFunds);
+        assert_eq!(kind(service.withdraw(&multi, &amount("EUR", 1.0))), ServiceErrorKind::NotFound);
+        // the message reads like a lack of funds, but the balance covers the withdrawal
+        assert_eq!(kind(service.withdraw(&basic, &amount("USD", 5.0))), ServiceErrorKind::InvalidRequest);
+        assert_eq!(kind(service.deposit(&multi, &amount("USD", -5.0))), ServiceErrorKind::InvalidRequest);
+        assert_eq!(kind(service.balance(&multi, "GBP")), ServiceErrorKind::NotFound);
+        assert_eq!(kind(service.history(&multi, "not-an-account")), ServiceErrorKind::InvalidRequest);
+        assert_eq!(kind(service.history(&multi, "Basic-4F9T2XK8LM-19")), ServiceErrorKind::NotFound);
+
+        let transfer = |to_wallet_id: &str, currency: &str, amount: f64| TransferRequest {
+            to_wallet_id: to_wallet_id.to_string(),
+            currency: currency.to_string(),
+            amount,
+        };
+        assert_eq!(kind(service.transfer(&basic, &transfer(&multi, "USD", 20.0))), ServiceErrorKind::InsufficientFunds);
+        assert_eq!(kind(service.transfer(&basic, &transfer(&multi, "EUR", 1.0))), ServiceErrorKind::NotFound);
+        let request = AddAccountRequest { account_type: "basic".to_string(), currency: "EUR".to_string(), overdraft_limit: None };
+        assert_eq!(kind(service.add_account(&basic, &request)), ServiceErrorKind::Conflict);
+    }
+
+    #[test]
+    fn test_state_round_trip() {
+        let mut service = WalletService::new();
+        let multi = multi_wallet(&mut service);
+        let euros = service.add_account(&multi, &AddAccountRequest {
+            account_type: "basic".to_string(),
+            currency: "EUR".to_string(),
+            overdraft_limit: None,
+        }).unwrap();
+        service.deposit(&multi, &amount("EUR", 12.5)).unwrap();
+        service.withdraw(&multi, &amount("USD", 30.0)).unwrap();
+        let basic = service.add_wallet(Box::new(BasicWallet::new(BasicAccount::new("USD")))).unwrap().wallet_id;
+
+        let path = std::env::temp_dir().join(format!("wallet_state_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        service.save_state(path).unwrap();
+        let loaded = WalletService::load_state(path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(loaded.to_stored(), service.to_stored());
+        assert_eq!(loaded.list_wallets(), service.list_wallets());
+        assert_eq!(loaded.balance(&multi, "USD").unwrap().balance, -30.0);
+        assert_eq!(loaded.history(&multi, &euros.account_number).unwrap().len(), 1);
+        assert_eq!(loaded.wallet(&basic).unwrap().get_accounts()[0].get_account_type(), AccountType::Basic);
+        assert!(WalletService::load_state(path).unwrap().list_wallets().is_empty());
+
+        let mut stored = service.to_stored();
+        let account = stored[0].accounts[0].clone();
+        stored[1].accounts.push(account);
+        assert!(WalletService::from_stored(&stored).is_err());
+    }
+
+    #[test]
+    fn test_balances_stay_finite_through_a_state_round_trip() {
+        let mut service = WalletService::new();
+        let multi = multi_wallet(&mut service);
+        let other = multi_wallet(&mut service);
+        service.deposit(&multi, &amount("USD", f64::MAX)).unwrap();
+        service.deposit(&other, &amount("USD", f64::MAX)).unwrap();
+
+        assert_eq!(kind(service.deposit(&multi, &amount("USD", f64::MAX))), ServiceErrorKind::InvalidRequest);
+        let account_number = service.get_wallet(&multi).unwrap().accounts[0].account_number.clone();
+        let request = AmountRequest { account_number: Some(account_number), ..amount("USD", f64::MAX) };
+        assert_eq!(kind(service.deposit(&multi, &request)), ServiceErrorKind::InvalidRequest);
+        let transfer = TransferRequest { to_wallet_id: other.clone(), currency: "USD".to_string(), amount: f64::MAX / 2.0 };
+        assert_eq!(kind(service.transfer(&multi, &transfer)), ServiceErrorKind::InvalidRequest);
+        assert_eq!(service.balance(&multi, "USD").unwrap().balance, f64::MAX);
+
+        let path = std::env::temp_dir().join(format!("wallet_state_max_{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        service.save_state(path).unwrap();
+        let loaded = WalletService::load_state(path).unwrap();
+        fs::remove_file(path).unwrap();
+        assert_eq!(loaded.to_stored(), service.to_stored());
+        assert_eq!(loaded.balance(&other, "USD").unwrap().balance, f64::MAX);
+    }
+
+    #[test]
+    fn test_cli_wallet_subcommands() {
+        let state = std::env::temp_dir().join(format!("wallet_cli_state_{}.json", std::process::id()));
+        let state = state.to_str().unwrap();
+        let cli = |args: &[&str]| {
+            Command::new(env!("CARGO_BIN_EXE_wallet_cli"))
+                .args(args)
+                .args(["--state", state])
+                .output()
+                .unwrap()
+        };
+
+        let output = cli(&["create_wallet", "multi", "--format", "json"]);
+        assert!(output.status.success());
+        let multi: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let multi = multi["wallet_id"].as_str().unwrap().to_string();
+        let output = cli(&["create_wallet", "basic", "--currency", "USD", "--format", "json"]);
+        let basic: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        let basic = basic["wallet_id"].as_str().unwrap().to_string();
+
+        let output = cli(&["create_account", &multi, "premium", "USD", "100"]);
+        assert!(output.status.success());
+        assert!(String::from_utf8(output.stdout).unwrap().contains("Overdraft Limit  100.00\n"));
+        assert!(cli(&["deposit", &basic, "80", "USD"]).status.success());
+        let output = cli(&["transfer", &basic, &multi, "30", "USD"]);
+        assert!(output.status.success());
+        assert!(String::from_utf8(output.stdout).unwrap().starts_with("Transferred 30 USD from Basic-"));
+        let output = cli(&["withdraw", &multi, "120", "USD"]);
+        assert!(String::from_utf8(output.stdout).unwrap().contains("Balance          -90.00\n"));
+
+        let output = cli(&["withdraw", &basic, "60", "USD"]);
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(String::from_utf8(output.stderr).unwrap(), "Insufficient funds\n");
+        let output = cli(&["deposit", "Basic-missing", "10", "USD"]);
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(String::from_utf8(output.stderr).unwrap(), "No wallet found with id: Basic-missing\n");
+        assert_eq!(cli(&["deposit", &basic, "ten", "USD"]).status.code(), Some(2));
+
+        let loaded = WalletService::load_state(state).unwrap();
+        fs::remove_file(state).unwrap();
+        assert_eq!(loaded.balance(&basic, "USD").unwrap().balance, 50.0);
+        assert_eq!(loaded.balance(&multi, "USD").unwrap().balance, -90.0);
+    }
+
+    #[test]
+    fn test_cli_balance() {
+        let state = std::env::temp_dir().join(format!("wallet_cli_balance_{}.json", std::process::id()));
+        let state = state.to_str().unwrap();
+        let mut service = WalletService::new();
+        let wallet = service.add_wallet(Box::new(BasicWallet::new(BasicAccount::new("EUR")))).unwrap().wallet_id;
+        service.deposit(&wallet, &amount("EUR", 42.5)).unwrap();
+        service.save_state(state).unwrap();
+        let cli = |args: &[&str]| {
+            Command::new(env!("CARGO_BIN_EXE_wallet_cli"))
+                .arg("balance")
+                .args(args)
+                .args(["--state", state])
+                .output()
+                .unwrap()
+        };
+
+        let output = cli(&[&wallet, "EUR"]);
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "42.50 EUR\n");
+        let output = cli(&[&wallet, "USD"]);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(output.stdout.is_empty());
+        let output = cli(&["Basic-missing", "EUR"]);
+        assert_eq!(output.status.code(), Some(1));
+        assert_eq!(String::from_utf8(output.stderr).unwrap(), "No wallet found with id: Basic-missing\n");
+        fs::remove_file(state).unwrap();
+    }
+}
@@ -0,0 +1,145 @@
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use wallet_system::customer::KycTier;
+    use wallet_system::simulation::{self, AmountDistribution, SimulatedOperation, Simulation, SimulationConfig, SimulationReport};
+
+    fn config(seed: u64) -> SimulationConfig {
+        SimulationConfig { seed, customers: 40, days: 15, ..SimulationConfig::default() }
+    }
+
+    fn run(config: SimulationConfig) -> SimulationReport {
+        Simulation::new(config).unwrap().run()
+    }
+
+    #[test]
+    fn test_same_seed_same_report() {
+        assert_eq!(run(config(7)), run(config(7)));
+        assert_ne!(run(config(7)), run(config(8)));
+    }
+
+    #[test]
+    fn test_population_mix() {
+        let simulation = Simulation::new(config(3)).unwrap();
+        assert_eq!(simulation.get_customers().len(), 40);
+        assert!(simulation.get_customers().iter().all(|customer| customer.get_wallets().len() == 1));
+
+        let report = run(SimulationConfig { multi_currency_share: 0.0, premium_share: 0.0, ..config(3) });
+        assert_eq!(report.basic_wallets, 40);
+        assert_eq!(report.multi_currency_wallets, 0);
+        assert_eq!(report.basic_accounts, 40);
+        assert_eq!(report.premium_accounts, 0);
+
+        let report = run(SimulationConfig { multi_currency_share: 1.0, premium_share: 1.0, ..config(3) });
+        assert_eq!(report.multi_currency_wallets, 40);
+        assert_eq!(report.premium_accounts, 120);
+        assert_eq!(report.balances.len(), 3);
+        assert!(report.balances.values().all(|balances| balances.accounts == 40));
+    }
+
+    #[test]
+    fn test_statistics_add_up() {
+        let report = run(config(11));
+        assert!(report.attempted > 0);
+        assert_eq!(report.attempted, report.succeeded + report.failed);
+        assert_eq!(report.daily.len(), 15);
+        assert_eq!(report.daily.iter().map(|day| day.attempted).sum::<usize>(), report.attempted);
+        assert_eq!(report.operations.values().map(|stats| stats.attempted).sum::<usize>(), report.attempted);
+        assert_eq!(report.failure_reasons.values().sum::<usize>(), report.failed);
+        assert!((report.failure_rate - report.failed as f64 / report.attempted as f64).abs() < 1e-12);
+        assert!((report.throughput_per_day - report.succeeded as f64 / 15.0).abs() < 1e-12);
+
+        for balances in report.balances.values() {
+            assert!(balances.min <= balances.median && balances.median <= balances.p90);
+            assert!(balances.p90 <= balances.p99 && balances.p99 <= balances.max);
+            assert!(balances.min >= -500.0);
+        }
+        // money only enters through deposits and leaves through withdrawals
+        let deposited = report.operations[&SimulatedOperation::Deposit].volume;
+        let withdrawn = report.operations[&SimulatedOperation::Withdrawal].volume;
+        let total: f64 = report.balances.values().map(|balances| balances.total).sum();
+        assert!((total - (deposited - withdrawn)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_workload_configuration() {
+        let report = run(SimulationConfig { operations_per_customer_per_day: 0.0, ..config(5) });
+        assert_eq!(report.attempted, 0);
+        assert_eq!(report.failure_rate, 0.0);
+
+        let deposits_only = vec![
+            (SimulatedOperation::Deposit, 1.0),
+            (SimulatedOperation::Withdrawal, 0.0),
+            (SimulatedOperation::Transfer, 0.0),
+        ];
+        let report = run(SimulationConfig {
+            operation_mix: deposits_only,
+            amounts: AmountDistribution::Uniform { min: 10.0, max: 10.0 },
+            kyc_tier: KycTier::Enhanced,
+            ..config(5)
+        });
+        assert_eq!(report.operations.len(), 1);
+        assert_eq!(report.failed, 0);
+        assert!((report.operations[&SimulatedOperation::Deposit].volume - 10.0 * report.attempted as f64).abs() < 1e-6);
+
+        // unverified customers hit their KYC balance cap
+        let report = run(SimulationConfig {
+            kyc_tier: KycTier::Unverified,
+            amounts: AmountDistribution::Uniform { min: 200.0, max: 240.0 },
+            ..config(5)
+        });
+        assert!(report.failure_reasons.keys().any(|reason| reason.contains("KYC")));
+    }
+
+    #[test]
+    fn test_invalid_configuration() {
+        assert!(Simulation::new(SimulationConfig { customers: 1, ..config(1) }).is_err());
+        assert!(Simulation::new(SimulationConfig { days: 0, ..config(1) }).is_err());
+        assert!(Simulation::new(SimulationConfig { premium_share: 1.5, ..config(1) }).is_err());
+        assert!(Simulation::new(SimulationConfig { currency_mix: vec![("USD".to_string(), 0.0)], ..config(1) }).is_err());
+        assert!(Simulation::new(SimulationConfig { amounts: AmountDistribution::Uniform { min: 5.0, max: 1.0 }, ..config(1) }).is_err());
+    }
+
+    #[test]
+    fn test_parse_workload() {
+        assert_eq!("uniform:1:500".parse::<AmountDistribution>().unwrap(), AmountDistribution::Uniform { min: 1.0, max: 500.0 });
+        assert_eq!("LogNormal:50:1.2".parse::<AmountDistribution>().unwrap(), AmountDistribution::LogNormal { median: 50.0, sigma: 1.2 });
+        assert!("normal:1:2".parse::<AmountDistribution>().is_err());
+        assert!("uniform:1".parse::<AmountDistribution>().is_err());
+
+        assert_eq!(
+            simulation::parse_currency_mix("usd:0.7, EUR:0.3,GBP").unwrap(),
+            vec![("USD".to_string(), 0.7), ("EUR".to_string(), 0.3), ("GBP".to_string(), 1.0)]
+        );
+        assert!(simulation::parse_currency_mix("USD:x").is_err());
+        assert!(simulation::parse_currency_mix("USD:0").is_err());
+    }
+
+    #[test]
+    fn test_cli_simulate() {
+        let args = ["simulate", "--seed", "42", "--customers", "20", "--days", "5", "--currencies", "USD:2,EUR:1", "--json"];
+        let output = Command::new(env!("CARGO_BIN_EXE_wallet_cli")).args(args).output().unwrap();
+        assert!(output.status.success());
+        let report: SimulationReport = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(report.seed, 42);
+        assert_eq!(report.customers, 20);
+        assert_eq!(report.daily.len(), 5);
+        assert!(report.balances.keys().all(|currency| currency == "USD" || currency == "EUR"));
+
+        let again = Command::new(env!("CARGO_BIN_EXE_wallet_cli")).args(args).output().unwrap();
+        assert_eq!(output.stdout, again.stdout);
+
+        let output = Command::new(env!("CARGO_BIN_EXE_wallet_cli"))
+            .args(["simulate", "--customers", "20", "--days", "5"])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert!(String::from_utf8(output.stdout).unwrap().contains("failure rate"));
+
+        let output = Command::new(env!("CARGO_BIN_EXE_wallet_cli"))
+            .args(["simulate", "--amounts", "pareto:1:2"])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.code(), Some(2));
+    }
+}
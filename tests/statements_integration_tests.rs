@@ -0,0 +1,142 @@
+#[cfg(test)]
+mod tests {
+    use wallet_system::account_number::AccountNumber;
+    use wallet_system::accounts::{Account, AccountType};
+    use wallet_system::basic_account::BasicAccount;
+    use wallet_system::multi_currency_wallet::MultiCurrencyWallet;
+    use wallet_system::premium_account::PremiumAccount;
+    use wallet_system::statements::{AccountStatement, WalletStatement};
+    use wallet_system::transactions::{self, Transaction, TransactionKind};
+    use wallet_system::wallets::Wallet;
+
+    fn at(date: &str) -> u64 {
+        transactions::parse_date(date).unwrap() + 12 * 3_600
+    }
+
+    fn transaction(kind: TransactionKind, amount: f64, balance_after: f64, counterparty: Option<&str>, date: &str) -> Transaction {
+        let mut transaction = Transaction::new(kind, amount, balance_after, counterparty);
+        transaction.timestamp = at(date);
+        transaction
+    }
+
+    /// Premium USD account with activity in December, January and February
+    fn premium_account() -> PremiumAccount {
+        let history = vec![
+            transaction(TransactionKind::Deposit, 100.0, 100.0, None, "2023-12-20"),
+            transaction(TransactionKind::Withdrawal, 150.0, -50.0, None, "2024-01-05"),
+            transaction(TransactionKind::TransferIn, 80.0, 30.0, Some("Basic-7KQ2M9X4TR-40"), "2024-01-10"),
+            transaction(TransactionKind::Fee, 2.5, 27.5, None, "2024-01-31"),
+            transaction(TransactionKind::Interest, 0.25, 27.75, None, "2024-01-31"),
+            transaction(TransactionKind::Deposit, 10.0, 37.75, None, "2024-02-02"),
+        ];
+        PremiumAccount::restore(AccountNumber::generate(AccountType::Premium), "USD", 37.75, 200.0, history)
+    }
+
+    #[test]
+    fn test_date_helpers() {
+        assert_eq!(transactions::parse_date("1970-01-01").unwrap(), 0);
+        assert_eq!(transactions::parse_date("2024-02-29").unwrap(), 1_709_164_800);
+        assert_eq!(transactions::format_timestamp(1_709_164_800 + 3_661), "2024-02-29 01:01:01");
+        assert!(transactions::parse_date("2023-02-29").is_err());
+        assert!(transactions::parse_date("29/02/2024").is_err());
+    }
+
+    #[test]
+    fn test_account_statement_for_period() {
+        let account = premium_account();
+        let statement = AccountStatement::generate(&account, transactions::parse_date("2024-01-01").unwrap(), transactions::parse_date("2024-02-01").unwrap() - 1).unwrap();
+
+        assert_eq!(statement.opening_balance, 100.0);
+        assert_eq!(statement.lines.len(), 4);
+        assert_eq!(statement.lines[0].amount, -150.0);
+        assert_eq!(statement.lines[0].running_balance, -50.0);
+        assert_eq!(statement.lines[1].counterparty_account_number.as_deref(), Some("Basic-7KQ2M9X4TR-40"));
+        assert_eq!(statement.total_fees, 2.5);
+        assert_eq!(statement.total_interest, 0.25);
+        assert_eq!(statement.closing_balance, 27.75);
+        assert_eq!(statement.overdraft_limit, Some(200.0));
+
+        assert!(AccountStatement::generate(&account, 10, 5).is_err());
+    }
+
+    #[test]
+    fn test_renderings() {
+        let account = premium_account();
+        let statement = AccountStatement::generate(&account, transactions::parse_date("2024-01-01").unwrap(), transactions::parse_date("2024-02-01").unwrap() - 1).unwrap();
+
+        let text = statement.to_text();
+        assert!(text.contains("Period: 2024-01-01 00:00:00 to 2024-01-31 23:59:59 UTC"));
+        assert!(text.contains("Overdraft limit: 200.00"));
+        assert!(text.contains("Opening balance: 100.00"));
+        assert!(text.contains("Fees: 2.50"));
+        assert!(text.contains("Closing balance: 27.75"));
+
+        let csv = statement.to_csv();
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows[0], "account_number,currency,date,type,amount,balance,counterparty");
+        assert_eq!(rows.len(), 1 + 1 + 4 + 3);
+        assert!(rows[1].ends_with(",OpeningBalance,,100.00,"));
+        assert!(rows[3].ends_with(",TransferIn,80.00,30.00,Basic-7KQ2M9X4TR-40"));
+        assert!(rows[6].ends_with(",ClosingBalance,,27.75,"));
+
+        let html = statement.to_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<style>"));
+        assert!(!html.contains("<link"));
+        assert_eq!(html.matches("<tr>").count(), 5);
+    }
+
+    #[test]
+    fn test_fees_and_interest_are_recorded() {
+        let mut account = BasicAccount::new("EUR");
+        account.deposit(10.0);
+        assert!(account.charge_fee(1.0).is_successful);
+        assert!(account.pay_interest(0.5).is_successful);
+        assert!(!account.charge_fee(50.0).is_successful);
+        let kinds: Vec<TransactionKind> = account.get_history().iter().map(|t| t.kind.clone()).collect();
+        assert_eq!(kinds, vec![TransactionKind::Deposit, TransactionKind::Fee, TransactionKind::Interest]);
+        assert_eq!(account.get_balance(), 9.5);
+    }
+
+    #[test]
+    fn test_wallet_statement() {
+        let mut wallet = MultiCurrencyWallet::new();
+        wallet.add_account(Box::new(premium_account())).unwrap();
+        wallet.add_account(Box::new(BasicAccount::new("EUR"))).unwrap();
+        wallet.add_account(Box::new(BasicAccount::new("USD"))).unwrap();
+        wallet.deposit_to_account(wallet.get_accounts()[2].get_account_number().to_string().as_str(), 5.0);
+
+        let statement = WalletStatement::generate(&wallet, 0, u64::MAX).unwrap();
+        assert_eq!(statement.accounts.len(), 3);
+        assert_eq!(statement.closing_balances().get("USD"), Some(&42.75));
+        assert_eq!(statement.closing_balances().get("EUR"), Some(&0.0));
+        assert!(statement.accounts[1].overdraft_limit.is_none());
+        assert!(statement.to_text().contains("  USD: 42.75"));
+        assert_eq!(statement.to_csv().lines().filter(|row| row.contains("OpeningBalance")).count(), 3);
+        assert_eq!(statement.to_html().matches("<section>").count(), 3);
+    }
+
+    #[test]
+    fn test_wallet_statement_includes_accounts_closed_during_the_period() {
+        let mut wallet = MultiCurrencyWallet::new();
+        wallet.add_account(Box::new(BasicAccount::new("USD"))).unwrap();
+        wallet.add_account(Box::new(BasicAccount::new("EUR"))).unwrap();
+        let account_number = wallet.get_accounts()[1].get_account_number().to_string();
+        wallet.deposit_to_account(&account_number, 30.0);
+        wallet.withdraw_from_account(&account_number, 30.0);
+        wallet.close_account(&account_number).unwrap();
+
+        let now = transactions::current_timestamp();
+        let statement = WalletStatement::generate(&wallet, 0, now).unwrap();
+        assert_eq!(statement.accounts.len(), 2);
+        let closed = &statement.accounts[1];
+        assert_eq!(closed.account_number, account_number);
+        assert_eq!(closed.lines.len(), 2);
+        assert_eq!(closed.closing_balance, 0.0);
+        assert_eq!(statement.closing_balances().get("EUR"), Some(&0.0));
+
+        // a period that starts after the account was closed leaves it out
+        let statement = WalletStatement::generate(&wallet, now + 60, now + 120).unwrap();
+        assert_eq!(statement.accounts.len(), 1);
+    }
+}
@@ -0,0 +1,371 @@
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+    use std::rc::Rc;
+    use std::time::{Duration, Instant};
+    use wallet_system::accounts::Account;
+    use wallet_system::basic_account::BasicAccount;
+    use wallet_system::basic_wallet::BasicWallet;
+    use wallet_system::customer::{ContactDetails, Customer, KycTier};
+    use wallet_system::events::{DeliveryMode, EventBus, WalletEvent};
+    use wallet_system::exchange::StaticExchangeRates;
+    use wallet_system::multi_currency_wallet::MultiCurrencyWallet;
+    use wallet_system::premium_account::PremiumAccount;
+    use wallet_system::service::{ServiceErrorKind, TransferRequest, WalletService};
+    use wallet_system::test_support::{
+        self, AccountCall, Fault, FaultInjector, FaultyAccount, FaultyWallet, InvariantHarness, Operation, Snapshot, WalletCall,
+    };
+    use wallet_system::transactions::TransactionKind;
+    use wallet_system::wallets::Wallet;
+
+    #[test]
+    fn test_invariants_hold_for_random_sequences() {
+        let mut rolled_back = 0;
+        for seed in 0..50 {
+            let mut harness = InvariantHarness::new(seed);
+            let summary = harness.run(300).unwrap();
+            assert_eq!(summary.steps, 300);
+            assert!(summary.succeeded > 0 && summary.failed > 0, "seed {} exercised only one outcome", seed);
+            rolled_back += summary.rolled_back;
+        }
+        assert!(rolled_back > 0, "no transfer was refused after debiting the sender");
+    }
+
+    #[test]
+    fn test_same_seed_replays_same_sequence() {
+        let mut first = InvariantHarness::new(42);
+        let mut second = InvariantHarness::new(42);
+        assert_eq!(first.run(100).unwrap(), second.run(100).unwrap());
+        assert_eq!(first.get_log(), second.get_log());
+        let mut other = InvariantHarness::new(43);
+        other.run(100).unwrap();
+        assert_ne!(first.get_log(), other.get_log());
+    }
+
+    #[test]
+    fn test_scripted_steps() {
+        let mut harness = InvariantHarness::new(7);
+        let usd = "USD".to_string();
+        assert!(harness.step(&Operation::Deposit { wallet: 0, currency: usd.clone(), amount: 100.0 }).unwrap());
+        assert!(!harness.step(&Operation::Withdraw { wallet: 0, currency: usd.clone(), amount: 100.01 }).unwrap());
+        assert!(!harness.step(&Operation::Transfer { from_wallet: 0, to_wallet: 1, currency: usd.clone(), amount: 10.0 }).unwrap());
+        assert!(harness.step(&Operation::Transfer { from_wallet: 0, to_wallet: 2, currency: usd.clone(), amount: 60.0 }).unwrap());
+        assert!(!harness.step(&Operation::Deposit { wallet: 2, currency: "XXX".to_string(), amount: 5.0 }).unwrap());
+        assert!(!harness.step(&Operation::RefusedTransfer { from_wallet: 2, to_wallet: 0, currency: usd.clone(), amount: 20.0 }).unwrap());
+        // refused before the sender was debited; the refusal must not linger
+        assert!(!harness.step(&Operation::RefusedTransfer { from_wallet: 0, to_wallet: 2, currency: usd.clone(), amount: 500.0 }).unwrap());
+        assert!(harness.step(&Operation::Transfer { from_wallet: 0, to_wallet: 2, currency: usd.clone(), amount: 10.0 }).unwrap());
+        assert_eq!(harness.get_external_flows()["USD"], 100.0);
+        assert_eq!(harness.get_wallets()[2].balance("USD").unwrap(), 70.0);
+        assert_eq!(harness.run(0).unwrap().rolled_back, 0);
+    }
+
+    #[test]
+    fn test_checks_detect_violations() {
+        let mut wallets: Vec<Box<dyn Wallet>> = vec![
+            Box::new(BasicWallet::new(BasicAccount::new("USD"))),
+            Box::new(BasicWallet::new(BasicAccount::new("USD"))),
+        ];
+        let before = Snapshot::capture(wallets.iter().map(|wallet| wallet.as_ref()));
+        wallets[0].deposit("USD", 10.0);
+        let after = Snapshot::capture(wallets.iter().map(|wallet| wallet.as_ref()));
+
+        assert!(test_support::check_untouched(&before, &after).unwrap_err().starts_with("Failed operation changed accounts"));
+        let flows = BTreeMap::from([("USD".to_string(), 0.0)]);
+        assert_eq!(
+            test_support::check_conservation(&after, &flows).unwrap_err(),
+            "USD total is 10 but external deposits and withdrawals add up to 0"
+        );
+
+        let mut overdrawn = after.clone();
+        overdrawn.accounts.values_mut().next().unwrap().balance = -0.5;
+        assert!(test_support::check_overdraft_floors(&overdrawn).is_err());
+
+        // existing balances count as money already in the system
+        let mut harness = InvariantHarness::with_wallets(1, wallets.into_iter().map(FaultyWallet::new).collect()).unwrap();
+        assert_eq!(harness.get_external_flows()["USD"], 10.0);
+        harness.run(50).unwrap();
+        assert!(InvariantHarness::with_wallets(1, Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_harness_checks_rolled_back_transfers() {
+        let mut harness = InvariantHarness::new(3);
+        let usd = "USD".to_string();
+        assert!(harness.step(&Operation::Deposit { wallet: 0, currency: usd.clone(), amount: 100.0 }).unwrap());
+        harness.get_wallets()[2].get_faults().inject_once(WalletCall::ReceiveTransfer, fail("Recipient wallet locked"));
+        assert!(!harness.step(&Operation::Transfer { from_wallet: 0, to_wallet: 2, currency: usd.clone(), amount: 60.0 }).unwrap());
+        assert_eq!(harness.get_wallets()[0].get_accounts()[0].get_history().len(), 1);
+        assert!(harness.step(&Operation::Transfer { from_wallet: 0, to_wallet: 2, currency: usd.clone(), amount: 60.0 }).unwrap());
+        assert_eq!(harness.get_wallets()[0].balance("USD").unwrap(), 40.0);
+    }
+
+    fn fail(message: &str) -> Fault {
+        Fault::Fail(message.to_string())
+    }
+
+    /// USD wallet holding 100
+    fn funded_wallet() -> BasicWallet<BasicAccount> {
+        let mut wallet = BasicWallet::new(BasicAccount::new("USD"));
+        wallet.deposit("USD", 100.0);
+        wallet
+    }
+
+    /// USD wallet whose account can be told to refuse incoming transfers
+    fn faulty_recipient() -> (BasicWallet<FaultyAccount>, FaultInjector<AccountCall>) {
+        let account = FaultyAccount::new(Box::new(BasicAccount::new("USD")));
+        let faults = account.get_faults();
+        faults.inject(AccountCall::DepositFrom, fail("Recipient ledger unavailable"));
+        (BasicWallet::new(account), faults)
+    }
+
+    /// Kinds of the account's history entries, oldest first
+    fn history_kinds(account: &dyn Account) -> Vec<TransactionKind> {
+        account.get_history().iter().map(|transaction| transaction.kind.clone()).collect()
+    }
+
+    fn rollbacks(bus: &EventBus) -> Rc<RefCell<Vec<WalletEvent>>> {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let recorded = Rc::clone(&events);
+        bus.subscribe(move |event| {
+            if let WalletEvent::TransferRolledBack { .. } = event {
+                recorded.borrow_mut().push(event.clone());
+            }
+        });
+        events
+    }
+
+    #[test]
+    fn test_fault_injector_schedules() {
+        let faults: FaultInjector<AccountCall> = FaultInjector::new();
+        faults.inject_once(AccountCall::Deposit, fail("first"));
+        faults.inject_nth(AccountCall::Deposit, 3, fail("third"));
+        assert_eq!(faults.check(AccountCall::Deposit), Err("first".to_string()));
+        assert_eq!(faults.check(AccountCall::Deposit), Ok(()));
+        assert_eq!(faults.check(AccountCall::Deposit), Err("third".to_string()));
+        assert_eq!(faults.check(AccountCall::Deposit), Ok(()));
+        assert_eq!(faults.check(AccountCall::Withdraw), Ok(()));
+        assert_eq!(faults.get_calls(AccountCall::Deposit), 4);
+        assert_eq!(faults.get_calls(AccountCall::Withdraw), 1);
+
+        faults.inject(AccountCall::Withdraw, fail("always"));
+        let handle = faults.clone();
+        assert!(handle.check(AccountCall::Withdraw).is_err());
+        assert!(handle.check(AccountCall::Withdraw).is_err());
+        faults.clear();
+        assert!(handle.check(AccountCall::Withdraw).is_ok());
+        assert_eq!(faults.get_calls(AccountCall::Withdraw), 4);
+
+        faults.inject(AccountCall::Withdraw, fail("withdraw"));
+        faults.inject(AccountCall::Deposit, fail("deposit"));
+        faults.disarm(AccountCall::Withdraw);
+        assert!(faults.check(AccountCall::Withdraw).is_ok());
+        assert!(faults.check(AccountCall::Deposit).is_err());
+    }
+
+    #[test]
+    fn test_faulty_account_delegates_and_fails() {
+        let mut account = FaultyAccount::new(Box::new(BasicAccount::new("EUR")));
+        let faults = account.get_faults();
+        assert!(account.deposit(50.0).is_successful);
+        faults.inject_once(AccountCall::Withdraw, fail("Core banking timeout"));
+        let response = account.withdraw(10.0);
+        assert!(!response.is_successful);
+        assert_eq!(response.error_message.unwrap(), "Core banking timeout");
+        assert_eq!(response.balance, 50.0);
+        assert!(account.withdraw(10.0).is_successful);
+        assert_eq!(account.get_balance(), 40.0);
+        assert_eq!(account.get_history().len(), 2);
+    }
+
+    #[test]
+    fn test_panic_and_delay_faults() {
+        let mut account = FaultyAccount::new(Box::new(BasicAccount::new("USD")));
+        account.get_faults().inject_once(AccountCall::Deposit, Fault::Panic("deposit must not be called".to_string()));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| account.deposit(1.0)));
+        assert!(result.is_err());
+
+        account.get_faults().inject_once(AccountCall::Deposit, Fault::Delay(Duration::from_millis(20)));
+        let started = Instant::now();
+        assert!(account.deposit(5.0).is_successful);
+        assert!(started.elapsed() >= Duration::from_millis(20));
+        assert_eq!(account.get_balance(), 5.0);
+    }
+
+    #[test]
+    fn test_failed_checks_never_reach_the_recipient() {
+        let mut sender = BasicWallet::new(BasicAccount::new("USD"));
+        let mut recipient = FaultyWallet::new(Box::new(BasicWallet::new(BasicAccount::new("USD"))));
+        recipient.get_faults().inject(WalletCall::ReceiveTransfer, Fault::Panic("recipient credited".to_string()));
+        let response = sender.transfer(&mut recipient, "USD", 10.0);
+        assert_eq!(response.error_message.unwrap(), "Insufficient funds");
+        assert_eq!(recipient.get_faults().get_calls(WalletCall::ReceiveTransfer), 0);
+    }
+
+    #[test]
+    fn test_basic_wallet_transfer_rolls_back() {
+        let bus = EventBus::new(DeliveryMode::Synchronous);
+        let events = rollbacks(&bus);
+        let mut sender = funded_wallet();
+        sender.set_event_bus(Some(bus));
+
+        let (mut recipient, faults) = faulty_recipient();
+        let response = sender.transfer(&mut recipient, "USD", 40.0);
+        assert!(!response.is_successful);
+        assert_eq!(response.error_message.unwrap(), "Recipient ledger unavailable");
+        assert_eq!(sender.balance("USD").unwrap(), 100.0);
+        assert_eq!(recipient.balance("USD").unwrap(), 0.0);
+        assert_eq!(faults.get_calls(AccountCall::DepositFrom), 1);
+        assert_eq!(history_kinds(sender.get_accounts()[0]), vec![TransactionKind::Deposit]);
+        assert!(recipient.get_accounts()[0].get_history().is_empty());
+
+        let recipient_account_number = recipient.get_accounts()[0].get_account_number().to_string();
+        let sender_account_number = sender.get_accounts()[0].get_account_number().to_string();
+        let response = sender.transfer_from_account(&sender_account_number, &mut recipient, &recipient_account_number, 25.0);
+        assert!(!response.is_successful);
+        assert_eq!(sender.balance("USD").unwrap(), 100.0);
+        assert_eq!(history_kinds(sender.get_accounts()[0]), vec![TransactionKind::Deposit]);
+        assert_eq!(events.borrow().len(), 2);
+
+        faults.clear();
+        assert!(sender.transfer(&mut recipient, "USD", 40.0).is_successful);
+        assert_eq!(sender.balance("USD").unwrap(), 60.0);
+        assert_eq!(recipient.balance("USD").unwrap(), 40.0);
+    }
+
+    #[test]
+    fn test_multi_currency_wallet_transfer_rolls_back() {
+        let bus = EventBus::new(DeliveryMode::Synchronous);
+        let events = rollbacks(&bus);
+        let mut sender = MultiCurrencyWallet::new();
+        sender.add_account(Box::new(PremiumAccount::new("USD", 50.0))).unwrap();
+        sender.deposit("USD", 10.0);
+        sender.set_event_bus(Some(bus));
+
+        let mut recipient = FaultyWallet::new(Box::new(BasicWallet::new(BasicAccount::new("USD"))));
+        recipient.get_faults().inject(WalletCall::ReceiveTransfer, fail("Recipient wallet locked"));
+        // the withdrawal draws on the overdraft, which must be undone as well
+        let response = sender.transfer(&mut recipient, "USD", 45.0);
+        assert_eq!(response.error_message.unwrap(), "Recipient wallet locked");
+        assert_eq!(sender.balance("USD").unwrap(), 10.0);
+        assert_eq!(history_kinds(sender.get_accounts()[0]), vec![TransactionKind::Deposit]);
+
+        let sender_account_number = sender.get_accounts()[0].get_account_number().to_string();
+        let recipient_account_number = recipient.get_accounts()[0].get_account_number().to_string();
+        let response = sender.transfer_from_account(&sender_account_number, &mut recipient, &recipient_account_number, 5.0);
+        assert!(!response.is_successful);
+        assert_eq!(sender.balance("USD").unwrap(), 10.0);
+        assert_eq!(recipient.balance("USD").unwrap(), 0.0);
+        assert_eq!(history_kinds(sender.get_accounts()[0]), vec![TransactionKind::Deposit]);
+        assert!(recipient.get_accounts()[0].get_history().is_empty());
+        assert_eq!(events.borrow().len(), 2);
+
+        let result = sender.close_account_sweeping_to_wallet(&sender_account_number, &mut recipient, &recipient_account_number);
+        assert_eq!(result.err().unwrap(), "Recipient wallet locked");
+        assert_eq!(sender.balance("USD").unwrap(), 10.0);
+        assert_eq!(history_kinds(sender.get_accounts()[0]), vec![TransactionKind::Deposit]);
+        assert!(sender.get_closed_accounts().is_empty());
+    }
+
+    #[test]
+    fn test_account_transfer_rolls_back() {
+        let mut recipient = FaultyAccount::new(Box::new(BasicAccount::new("USD")));
+        recipient.get_faults().inject(AccountCall::DepositFrom, fail("Recipient account frozen"));
+
+        let mut basic = BasicAccount::new("USD");
+        basic.deposit(30.0);
+        let response = basic.transfer(&mut recipient, 20.0);
+        assert!(!response.is_successful);
+        assert_eq!(response.error_message.unwrap(), "Recipient account frozen");
+        assert_eq!(response.balance, 30.0);
+        assert_eq!(basic.get_balance(), 30.0);
+        assert_eq!(history_kinds(&basic), vec![TransactionKind::Deposit]);
+
+        let mut premium = PremiumAccount::new("USD", 100.0);
+        let response = premium.transfer(&mut recipient, 80.0);
+        assert_eq!(response.error_message.unwrap(), "Recipient account frozen");
+        assert_eq!(premium.get_balance(), 0.0);
+        assert!(premium.get_history().is_empty());
+        assert_eq!(recipient.get_balance(), 0.0);
+        assert!(recipient.get_history().is_empty());
+    }
+
+    #[test]
+    fn test_internal_transfer_and_sweep_roll_back() {
+        let mut wallet = MultiCurrencyWallet::new();
+        wallet.add_account(Box::new(BasicAccount::new("USD"))).unwrap();
+        let target = FaultyAccount::new(Box::new(BasicAccount::new("USD")));
+        let faults = target.get_faults();
+        wallet.add_account(Box::new(target)).unwrap();
+        wallet.deposit("USD", 70.0);
+        let from = wallet.get_accounts()[0].get_account_number().to_string();
+        let to = wallet.get_accounts()[1].get_account_number().to_string();
+
+        faults.inject(AccountCall::DepositFrom, fail("Target account unavailable"));
+        let response = wallet.internal_transfer(&from, &to, 30.0).unwrap();
+        assert!(!response.is_successful);
+        assert_eq!(wallet.get_account_by_number(&from).unwrap().get_balance(), 70.0);
+        assert_eq!(history_kinds(wallet.get_account_by_number(&from).unwrap()), vec![TransactionKind::Deposit]);
+
+        assert_eq!(wallet.close_account_sweeping_to(&from, &to).err().unwrap(), "Target account unavailable");
+        assert_eq!(wallet.get_account_by_number(&from).unwrap().get_balance(), 70.0);
+        assert_eq!(history_kinds(wallet.get_account_by_number(&from).unwrap()), vec![TransactionKind::Deposit]);
+        assert_eq!(wallet.get_account_by_number(&to).unwrap().get_balance(), 0.0);
+        assert!(wallet.get_account_by_number(&to).unwrap().get_history().is_empty());
+
+        faults.clear();
+        wallet.close_account_sweeping_to(&from, &to).unwrap();
+        assert_eq!(wallet.get_account_by_number(&to).unwrap().get_balance(), 70.0);
+    }
+
+    #[test]
+    fn test_exchange_rolls_back() {
+        let mut rates = StaticExchangeRates::new();
+        rates.set_rate("USD", "EUR", 0.9).unwrap();
+        let mut wallet = MultiCurrencyWallet::new();
+        wallet.add_account(Box::new(BasicAccount::new("USD"))).unwrap();
+        let euros = FaultyAccount::new(Box::new(BasicAccount::new("EUR")));
+        euros.get_faults().inject_once(AccountCall::DepositFrom, fail("EUR ledger unavailable"));
+        wallet.add_account(Box::new(euros)).unwrap();
+        wallet.deposit("USD", 100.0);
+
+        let response = wallet.exchange("USD", "EUR", 50.0, &rates);
+        assert!(!response.is_successful);
+        assert_eq!(response.error_message.unwrap(), "EUR ledger unavailable");
+        assert_eq!(wallet.balance("USD").unwrap(), 100.0);
+        assert_eq!(wallet.balance("EUR").unwrap(), 0.0);
+        assert_eq!(history_kinds(wallet.get_account_by_currency("USD").unwrap()), vec![TransactionKind::Deposit]);
+        assert!(wallet.get_exchange_history().is_empty());
+
+        assert!(wallet.exchange("USD", "EUR", 50.0, &rates).is_successful);
+        assert_eq!(wallet.balance("USD").unwrap(), 50.0);
+    }
+
+    #[test]
+    fn test_customer_and_service_transfers_roll_back() {
+        let contact = ContactDetails { email: "ada@example.com".to_string(), phone: None, address: None };
+        let mut customer = Customer::new("Ada", contact, KycTier::Verified);
+        let from_id = customer.add_wallet(Box::new(funded_wallet())).unwrap().get_wallet_id().to_string();
+        let (recipient, _faults) = faulty_recipient();
+        let to_id = customer.add_wallet(Box::new(recipient)).unwrap().get_wallet_id().to_string();
+        let response = customer.transfer_between_wallets(&from_id, &to_id, "USD", 30.0).unwrap();
+        assert!(!response.is_successful);
+        assert_eq!(customer.total_balance("USD"), 100.0);
+        assert_eq!(customer.get_wallet(&from_id).unwrap().balance("USD").unwrap(), 100.0);
+        assert_eq!(history_kinds(customer.get_wallet(&from_id).unwrap().get_accounts()[0]), vec![TransactionKind::Deposit]);
+
+        let mut service = WalletService::new();
+        let from_id = service.add_wallet(Box::new(funded_wallet())).unwrap().wallet_id;
+        let recipient = FaultyWallet::new(Box::new(BasicWallet::new(BasicAccount::new("USD"))));
+        recipient.get_faults().inject(WalletCall::ReceiveTransfer, fail("Recipient wallet locked"));
+        let to_id = service.add_wallet(Box::new(recipient)).unwrap().wallet_id;
+        let request = TransferRequest { to_wallet_id: to_id.clone(), currency: "USD".to_string(), amount: 30.0 };
+        let error = service.transfer(&from_id, &request).err().unwrap();
+        assert_eq!(error.message, "Recipient wallet locked");
+        assert_ne!(error.kind, ServiceErrorKind::NotFound);
+        assert_eq!(service.balance(&from_id, "USD").unwrap().balance, 100.0);
+        assert_eq!(history_kinds(service.wallet(&from_id).unwrap().get_accounts()[0]), vec![TransactionKind::Deposit]);
+        assert_eq!(service.balance(&to_id, "USD").unwrap().balance, 0.0);
+    }
+}
@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod tests {
+    use wallet_system::basic_account::BasicAccount;
+    use wallet_system::exchange::{ExchangeRateSource, StaticExchangeRates};
+    use wallet_system::multi_currency_wallet::MultiCurrencyWallet;
+    use wallet_system::premium_account::PremiumAccount;
+    use wallet_system::wallets::Wallet;
+
+    fn rates() -> StaticExchangeRates {
+        let mut rates = StaticExchangeRates::new();
+        rates.set_rate("USD", "EUR", 0.5).unwrap();
+        rates.set_rate("GBP", "EUR", 2.0).unwrap();
+        rates
+    }
+
+    #[test]
+    fn test_static_rates_derive_inverse() {
+        let rates = rates();
+        assert_eq!(rates.get_rate("USD", "EUR"), Some(0.5));
+        assert_eq!(rates.get_rate("EUR", "USD"), Some(2.0));
+        assert_eq!(rates.get_rate("EUR", "EUR"), Some(1.0));
+        assert_eq!(rates.get_rate("USD", "JPY"), None);
+    }
+
+    #[test]
+    fn test_invalid_rate_rejected() {
+        let mut rates = StaticExchangeRates::new();
+        assert!(rates.set_rate("USD", "EUR", 0.0).is_err());
+        assert!(rates.set_rate("USD", "EUR", -1.0).is_err());
+    }
+
+    #[test]
+    fn test_wallet_valuation_in_base_currency() {
+        let mut wallet = MultiCurrencyWallet::new();
+        wallet.set_base_currency("EUR");
+        wallet.add_account(Box::new(BasicAccount::new("USD"))).expect("Failed to add account");
+        wallet.add_account(Box::new(BasicAccount::new("EUR"))).expect("Failed to add account");
+        wallet.add_account(Box::new(PremiumAccount::new("GBP", 100.0))).expect("Failed to add account");
+        wallet.deposit("USD", 100.0);
+        wallet.deposit("EUR", 20.0);
+        wallet.withdraw("GBP", 10.0);
+
+        let valuation = wallet.valuation(&rates()).expect("Failed to value wallet");
+        assert_eq!(valuation.base_currency, "EUR");
+        assert_eq!(valuation.wallet_id, wallet.get_wallet_id());
+        assert_eq!(valuation.accounts.len(), 3);
+        assert_eq!(valuation.accounts[0].value, 50.0);
+        assert_eq!(valuation.accounts[1].value, 20.0);
+        // the overdrawn premium account reduces the total
+        assert_eq!(valuation.accounts[2].balance, -10.0);
+        assert_eq!(valuation.accounts[2].value, -20.0);
+        assert_eq!(valuation.total, 50.0);
+        assert_eq!(valuation.rates_used.len(), 3);
+        assert_eq!(valuation.rates_used[0].rate, 0.5);
+    }
+
+    #[test]
+    fn test_wallet_valuation_missing_rate() {
+        let mut wallet = MultiCurrencyWallet::new();
+        wallet.add_account(Box::new(BasicAccount::new("JPY"))).expect("Failed to add account");
+        let result = wallet.valuation(&rates());
+        assert_eq!(result.unwrap_err(), "No exchange rate available from JPY to USD");
+    }
+}